@@ -0,0 +1,440 @@
+//! Registry of named eBay seller accounts.
+//!
+//! The Node.js scripts assume a single seller configured in `.env`. Here each
+//! account carries its own credentials, environment, site and token vault, and
+//! every job takes an [`Account`] handle so it can be run against all stores
+//! in one pass with [`AccountRegistry::run_all`].
+//!
+//! On disk the registry is one encrypted file plus one token vault per account:
+//!
+//! ```text
+//! <dir>/accounts.vault
+//! <dir>/tokens/<name>.vault
+//! ```
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+use url::Url;
+
+use crate::adapters::ebay::app_token::{AppTokenSource, DEFAULT_APP_SCOPE};
+use crate::adapters::ebay::oauth::DEFAULT_SCOPES;
+use crate::adapters::ebay::rate_limit::{Api, RateLimitConfig, RateLimiter, Throttle};
+use crate::adapters::ebay::retry::{Retrier, RetryPolicy};
+use crate::adapters::ebay::token_provider::TokenSource;
+use crate::adapters::ebay::vault::{self, unix_now, StoredTokens, TokenVault, VaultKey};
+use crate::adapters::ebay::{
+    AppTokenCache, OAuthClient, OAuthConfig, RedirectListener, UserTokenProvider,
+};
+use crate::environment::Environment;
+use crate::error::{Error, Result};
+
+const REGISTRY_FILE: &str = "accounts.vault";
+const TOKENS_DIR: &str = "tokens";
+const RATE_LIMITS_FILE: &str = "rate_limits.json";
+
+fn default_marketplace_id() -> String {
+    "EBAY_US".to_string()
+}
+
+fn default_scopes() -> Vec<String> {
+    DEFAULT_SCOPES.iter().map(|s| s.to_string()).collect()
+}
+
+/// Everything needed to talk to eBay as one seller.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AccountConfig {
+    /// Handle used to refer to the account, e.g. `"bedding-outlet"`.
+    pub name: String,
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(default)]
+    pub dev_id: Option<String>,
+    pub ru_name: String,
+    #[serde(default)]
+    pub environment: Environment,
+    /// Trading API site ID (`X-EBAY-API-SITEID`), 0 = US.
+    #[serde(default)]
+    pub site_id: u32,
+    /// REST marketplace ID (`X-EBAY-C-MARKETPLACE-ID`).
+    #[serde(default = "default_marketplace_id")]
+    pub marketplace_id: String,
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<String>,
+}
+
+impl std::fmt::Debug for AccountConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountConfig")
+            .field("name", &self.name)
+            .field("client_id", &self.client_id)
+            .field("environment", &self.environment)
+            .field("site_id", &self.site_id)
+            .field("marketplace_id", &self.marketplace_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AccountConfig {
+    pub fn oauth_config(&self) -> OAuthConfig {
+        OAuthConfig {
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            ru_name: self.ru_name.clone(),
+            environment: self.environment,
+            scopes: self.scopes.clone(),
+        }
+    }
+}
+
+/// Non-secret view of an account for the front end.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSummary {
+    pub name: String,
+    pub environment: Environment,
+    pub site_id: u32,
+    pub marketplace_id: String,
+    pub authorized: bool,
+    pub token_expires_at: Option<u64>,
+}
+
+#[derive(Debug)]
+struct AccountInner {
+    config: AccountConfig,
+    user_tokens: Arc<UserTokenProvider>,
+    app_tokens: Arc<AppTokenCache>,
+    limiter: Arc<RateLimiter>,
+    retrier: Arc<Retrier>,
+}
+
+/// Cheap-to-clone handle to one registered account.
+#[derive(Debug, Clone)]
+pub struct Account {
+    inner: Arc<AccountInner>,
+}
+
+impl Account {
+    fn new(
+        config: AccountConfig,
+        tokens_path: PathBuf,
+        key: VaultKey,
+        limiter: Arc<RateLimiter>,
+        retrier: Arc<Retrier>,
+    ) -> Self {
+        let identity = Throttle::new(limiter.clone(), Api::Identity, config.client_id.clone());
+        let oauth = OAuthClient::new(config.oauth_config()).with_throttle(identity.clone());
+        let vault = TokenVault::new(tokens_path, key);
+        let app_tokens = AppTokenCache::new(config.client_id.clone(), config.client_secret.clone())
+            .with_throttle(identity);
+
+        Self {
+            inner: Arc::new(AccountInner {
+                user_tokens: Arc::new(UserTokenProvider::new(oauth, vault)),
+                app_tokens: Arc::new(app_tokens),
+                limiter,
+                retrier,
+                config,
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.inner.config.name
+    }
+
+    pub fn config(&self) -> &AccountConfig {
+        &self.inner.config
+    }
+
+    pub fn environment(&self) -> Environment {
+        self.inner.config.environment
+    }
+
+    pub fn site_id(&self) -> u32 {
+        self.inner.config.site_id
+    }
+
+    pub fn marketplace_id(&self) -> &str {
+        &self.inner.config.marketplace_id
+    }
+
+    /// Self-refreshing user token for the Sell and Trading APIs.
+    pub fn user_tokens(&self) -> Arc<dyn TokenSource> {
+        self.inner.user_tokens.clone()
+    }
+
+    /// Application token for the Buy APIs.
+    pub fn app_tokens(&self, scopes: &[&str]) -> Arc<dyn TokenSource> {
+        let scopes = if scopes.is_empty() {
+            vec![DEFAULT_APP_SCOPE.to_string()]
+        } else {
+            scopes.iter().map(|s| s.to_string()).collect()
+        };
+        Arc::new(AppTokenSource::new(
+            self.inner.app_tokens.clone(),
+            self.environment(),
+            scopes,
+        ))
+    }
+
+    /// Limiter shared with the other accounts in the registry.
+    pub fn rate_limiter(&self) -> Arc<RateLimiter> {
+        self.inner.limiter.clone()
+    }
+
+    /// Retry policy and circuit breakers shared with the other accounts.
+    pub fn retrier(&self) -> Arc<Retrier> {
+        self.inner.retrier.clone()
+    }
+
+    /// Rate limits for calls made with the application token.
+    pub fn app_throttle(&self, api: Api) -> Throttle {
+        Throttle::new(
+            self.inner.limiter.clone(),
+            api,
+            self.config().client_id.clone(),
+        )
+    }
+
+    /// Rate limits for calls made with this account's user token, counted
+    /// against both the application and the user budgets.
+    pub fn user_throttle(&self, api: Api) -> Throttle {
+        self.app_throttle(api).for_user(self.name())
+    }
+
+    pub fn stored_tokens(&self) -> Result<Option<StoredTokens>> {
+        self.inner.user_tokens.vault().load()
+    }
+
+    /// Run the consent flow for this account and store the resulting tokens.
+    pub async fn authorize<F>(
+        &self,
+        listener: &RedirectListener,
+        open: F,
+        timeout: Duration,
+    ) -> Result<StoredTokens>
+    where
+        F: FnOnce(&Url),
+    {
+        let oauth = OAuthClient::new(self.config().oauth_config())
+            .with_throttle(self.app_throttle(Api::Identity));
+        let response = oauth.authorize(listener, open, timeout).await?;
+
+        let mut tokens = StoredTokens::from_response(&response, self.environment(), unix_now());
+        if tokens.scopes.is_empty() {
+            tokens.scopes = self.config().scopes.clone();
+        }
+        self.inner.user_tokens.replace(tokens.clone()).await?;
+        Ok(tokens)
+    }
+
+    pub fn summary(&self) -> AccountSummary {
+        let tokens = self.stored_tokens().ok().flatten();
+        AccountSummary {
+            name: self.name().to_string(),
+            environment: self.environment(),
+            site_id: self.site_id(),
+            marketplace_id: self.marketplace_id().to_string(),
+            authorized: tokens.is_some(),
+            token_expires_at: tokens.map(|t| t.expires_at),
+        }
+    }
+}
+
+/// Settings shared by every account in a registry.
+#[derive(Debug, Clone, Default)]
+pub struct RegistryOptions {
+    pub rate_limits: RateLimitConfig,
+    pub retry: RetryPolicy,
+}
+
+#[derive(Debug)]
+pub struct AccountRegistry {
+    dir: PathBuf,
+    key: VaultKey,
+    limiter: Arc<RateLimiter>,
+    retrier: Arc<Retrier>,
+    accounts: Vec<Account>,
+}
+
+impl AccountRegistry {
+    /// Open the registry stored in `dir`, creating an empty one if needed.
+    pub fn open(dir: impl Into<PathBuf>, key: VaultKey) -> Result<Self> {
+        Self::open_with_options(dir, key, RegistryOptions::default())
+    }
+
+    /// Like [`Self::open`], with custom rate limits and retry policy.
+    pub fn open_with_options(
+        dir: impl Into<PathBuf>,
+        key: VaultKey,
+        options: RegistryOptions,
+    ) -> Result<Self> {
+        let dir = dir.into();
+        let configs: Vec<AccountConfig> =
+            vault::read_encrypted(&dir.join(REGISTRY_FILE), &key)?.unwrap_or_default();
+        let limiter = Arc::new(RateLimiter::open(
+            dir.join(RATE_LIMITS_FILE),
+            options.rate_limits,
+        )?);
+        let retrier = Arc::new(Retrier::new(options.retry));
+
+        let mut registry = Self {
+            dir,
+            key,
+            limiter,
+            retrier,
+            accounts: Vec::new(),
+        };
+        registry.accounts = configs
+            .into_iter()
+            .map(|config| registry.build(config))
+            .collect();
+        Ok(registry)
+    }
+
+    /// Limiter shared by every account in the registry.
+    pub fn rate_limiter(&self) -> Arc<RateLimiter> {
+        self.limiter.clone()
+    }
+
+    pub fn accounts(&self) -> &[Account] {
+        &self.accounts
+    }
+
+    pub fn summaries(&self) -> Vec<AccountSummary> {
+        self.accounts.iter().map(Account::summary).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<Account> {
+        self.accounts
+            .iter()
+            .find(|account| account.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::Account(format!("unknown account: {name}")))
+    }
+
+    /// Register a new account and persist the registry.
+    pub fn add(&mut self, config: AccountConfig) -> Result<Account> {
+        validate_name(&config.name)?;
+        if self.get(&config.name).is_ok() {
+            return Err(Error::Account(format!(
+                "account already exists: {}",
+                config.name
+            )));
+        }
+
+        let account = self.build(config);
+        self.accounts.push(account.clone());
+        self.persist()?;
+        Ok(account)
+    }
+
+    /// Replace an account's settings, keeping its stored tokens unless the
+    /// environment changes: tokens from one environment are rejected by the
+    /// other, so the account has to be authorized again.
+    pub fn update(&mut self, config: AccountConfig) -> Result<Account> {
+        let index = self
+            .accounts
+            .iter()
+            .position(|account| account.name() == config.name)
+            .ok_or_else(|| Error::Account(format!("unknown account: {}", config.name)))?;
+
+        let previous = &self.accounts[index];
+        if previous.environment() != config.environment {
+            log::info!(
+                "Account {} moved from {:?} to {:?}, clearing its stored tokens",
+                config.name,
+                previous.environment(),
+                config.environment
+            );
+            previous.inner.user_tokens.vault().revoke()?;
+        }
+
+        let account = self.build(config);
+        self.accounts[index] = account.clone();
+        self.persist()?;
+        Ok(account)
+    }
+
+    /// Remove an account and revoke its stored tokens.
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        let account = self.get(name)?;
+        account.inner.user_tokens.vault().revoke()?;
+        self.accounts.retain(|a| a.name() != name);
+        self.persist()
+    }
+
+    /// Run `job` against every account concurrently. Results come back in
+    /// registry order, one per account, so a failing store does not stop
+    /// the others.
+    pub async fn run_all<F, Fut, T>(&self, job: F) -> Vec<(String, Result<T>)>
+    where
+        F: Fn(Account) -> Fut,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let mut set = JoinSet::new();
+        for (index, account) in self.accounts.iter().enumerate() {
+            let fut = job(account.clone());
+            set.spawn(async move { (index, fut.await) });
+        }
+
+        let mut results: Vec<Option<Result<T>>> = self.accounts.iter().map(|_| None).collect();
+        while let Some(joined) = set.join_next().await {
+            match joined {
+                Ok((index, result)) => results[index] = Some(result),
+                Err(err) => log::error!("Account job panicked: {err}"),
+            }
+        }
+
+        self.accounts
+            .iter()
+            .zip(results)
+            .map(|(account, result)| {
+                let result = result
+                    .unwrap_or_else(|| Err(Error::Account("job did not complete".to_string())));
+                (account.name().to_string(), result)
+            })
+            .collect()
+    }
+
+    fn build(&self, config: AccountConfig) -> Account {
+        let path = tokens_path(&self.dir, &config.name);
+        Account::new(
+            config,
+            path,
+            self.key.clone(),
+            self.limiter.clone(),
+            self.retrier.clone(),
+        )
+    }
+
+    fn persist(&self) -> Result<()> {
+        let configs: Vec<&AccountConfig> = self.accounts.iter().map(Account::config).collect();
+        vault::write_encrypted(&self.dir.join(REGISTRY_FILE), &self.key, &configs)
+    }
+}
+
+fn tokens_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(TOKENS_DIR).join(format!("{name}.vault"))
+}
+
+/// Names end up in file paths, so keep them to a safe character set.
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Account(format!(
+            "invalid account name {name:?}: use letters, digits, '-' or '_'"
+        )))
+    }
+}
@@ -0,0 +1,351 @@
+//! Live call quotas from the Developer Analytics API.
+//!
+//! The scripts hard-code their limits in `CONFIG`. This reads the real
+//! numbers from `getRateLimits` (application quotas, app token) and
+//! `getUserRateLimits` (per-user quotas, user token) and writes them into the
+//! [`RateLimiter`] budgets. If the endpoint cannot be reached the limiter
+//! keeps its configured defaults.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use url::Url;
+
+use super::rate_limit::{Api, Budget, BudgetScope, RateLimiter};
+use super::retry::{self, Retrier};
+use super::token_provider::{send_authorized, TokenSource};
+use super::vault::unix_now;
+use crate::accounts::Account;
+use crate::environment::Environment;
+use crate::error::{Error, Result};
+
+pub const RATE_LIMIT_PATH: &str = "/developer/analytics/v1_beta/rate_limit/";
+pub const USER_RATE_LIMIT_PATH: &str = "/developer/analytics/v1_beta/user_rate_limit/";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitsResponse {
+    #[serde(default)]
+    pub rate_limits: Vec<RateLimit>,
+}
+
+/// Quotas for one API, e.g. `apiContext: "sell"`, `apiName: "Marketing"`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    #[serde(default)]
+    pub api_context: String,
+    #[serde(default)]
+    pub api_name: String,
+    #[serde(default)]
+    pub api_version: Option<String>,
+    #[serde(default)]
+    pub resources: Vec<RateResource>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateResource {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub rates: Vec<Rate>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rate {
+    #[serde(default)]
+    pub count: Option<u64>,
+    #[serde(default)]
+    pub limit: Option<u64>,
+    #[serde(default)]
+    pub remaining: Option<u64>,
+    #[serde(default)]
+    pub reset: Option<DateTime<Utc>>,
+    /// Length of the quota window in seconds.
+    #[serde(default)]
+    pub time_window: Option<u64>,
+}
+
+/// Where the budgets shown in the UI came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaSource {
+    Live,
+    Defaults,
+}
+
+/// Last refresh result for the front end.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaStatus {
+    pub source: QuotaSource,
+    pub refreshed_at: Option<u64>,
+    pub error: Option<String>,
+    pub budgets: Vec<Budget>,
+}
+
+#[derive(Clone)]
+pub struct AnalyticsClient {
+    http: reqwest::Client,
+    base_url: Url,
+    app_tokens: Arc<dyn TokenSource>,
+    user_tokens: Option<Arc<dyn TokenSource>>,
+    retrier: Option<Arc<Retrier>>,
+}
+
+impl std::fmt::Debug for AnalyticsClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnalyticsClient")
+            .field("base_url", &self.base_url.as_str())
+            .finish_non_exhaustive()
+    }
+}
+
+impl AnalyticsClient {
+    pub fn new(environment: Environment, app_tokens: Arc<dyn TokenSource>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: environment.api_base_url(),
+            app_tokens,
+            user_tokens: None,
+            retrier: None,
+        }
+    }
+
+    pub fn for_account(account: &Account) -> Self {
+        Self::new(account.environment(), account.app_tokens(&[]))
+            .with_user_tokens(account.user_tokens())
+            .with_retrier(account.retrier())
+    }
+
+    /// Also read the per-user quotas with this user token.
+    pub fn with_user_tokens(mut self, tokens: Arc<dyn TokenSource>) -> Self {
+        self.user_tokens = Some(tokens);
+        self
+    }
+
+    pub fn with_retrier(mut self, retrier: Arc<Retrier>) -> Self {
+        self.retrier = Some(retrier);
+        self
+    }
+
+    /// Override the API host (used by tests).
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// `getRateLimits`: application quotas.
+    pub async fn rate_limits(&self) -> Result<RateLimitsResponse> {
+        self.get(RATE_LIMIT_PATH, self.app_tokens.as_ref()).await
+    }
+
+    /// `getUserRateLimits`: quotas for the user behind the user token.
+    pub async fn user_rate_limits(&self) -> Result<Option<RateLimitsResponse>> {
+        match &self.user_tokens {
+            Some(tokens) => Ok(Some(self.get(USER_RATE_LIMIT_PATH, tokens.as_ref()).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get(&self, path: &str, tokens: &dyn TokenSource) -> Result<RateLimitsResponse> {
+        match &self.retrier {
+            Some(retrier) => {
+                retrier
+                    .run("developer.analytics", || self.get_once(path, tokens))
+                    .await
+            }
+            None => self.get_once(path, tokens).await,
+        }
+    }
+
+    async fn get_once(&self, path: &str, tokens: &dyn TokenSource) -> Result<RateLimitsResponse> {
+        let url = self.base_url.join(path)?;
+        let response = send_authorized(tokens, |token| {
+            self.http
+                .get(url.clone())
+                .bearer_auth(token)
+                .header("Accept", "application/json")
+        })
+        .await?;
+
+        let status = response.status();
+        let retry_after = retry::retry_after(response.headers());
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(Error::Api {
+                status: status.as_u16(),
+                message: body,
+                retry_after,
+            });
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+}
+
+/// Keeps one account's limiter budgets in line with what eBay reports.
+#[derive(Debug)]
+pub struct QuotaMonitor {
+    client: AnalyticsClient,
+    limiter: Arc<RateLimiter>,
+    app_id: String,
+    user: String,
+    status: Mutex<Option<QuotaStatus>>,
+}
+
+impl QuotaMonitor {
+    pub fn new(
+        client: AnalyticsClient,
+        limiter: Arc<RateLimiter>,
+        app_id: impl Into<String>,
+        user: impl Into<String>,
+    ) -> Self {
+        Self {
+            client,
+            limiter,
+            app_id: app_id.into(),
+            user: user.into(),
+            status: Mutex::new(None),
+        }
+    }
+
+    pub fn for_account(account: &Account) -> Self {
+        Self::new(
+            AnalyticsClient::for_account(account),
+            account.rate_limiter(),
+            account.config().client_id.clone(),
+            account.name(),
+        )
+    }
+
+    /// Fetch the live quotas and apply them. Failures are logged and leave
+    /// the current budgets in place; the returned status says which was used.
+    pub async fn refresh(&self) -> QuotaStatus {
+        let result = self.fetch_and_apply().await;
+        let status = match result {
+            Ok(()) => QuotaStatus {
+                source: QuotaSource::Live,
+                refreshed_at: Some(unix_now()),
+                error: None,
+                budgets: self.limiter.budgets(),
+            },
+            Err(err) => {
+                log::warn!("Could not read rate limits, using defaults: {err}");
+                let previous = self.status.lock().await.clone();
+                QuotaStatus {
+                    source: previous
+                        .as_ref()
+                        .map_or(QuotaSource::Defaults, |status| status.source),
+                    refreshed_at: previous.and_then(|status| status.refreshed_at),
+                    error: Some(err.to_string()),
+                    budgets: self.limiter.budgets(),
+                }
+            }
+        };
+
+        *self.status.lock().await = Some(status.clone());
+        status
+    }
+
+    /// Result of the last [`Self::refresh`], if any.
+    pub async fn status(&self) -> Option<QuotaStatus> {
+        self.status.lock().await.clone()
+    }
+
+    /// Refresh now and then every `interval` until the handle is aborted.
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.refresh().await;
+            }
+        })
+    }
+
+    async fn fetch_and_apply(&self) -> Result<()> {
+        let now = unix_now();
+        let app = self.client.rate_limits().await?;
+        for budget in budgets_from(&app, BudgetScope::App, &self.app_id, now) {
+            self.limiter.set_budget(budget)?;
+        }
+
+        if let Some(user) = self.client.user_rate_limits().await? {
+            for budget in budgets_from(&user, BudgetScope::User, &self.user, now) {
+                self.limiter.set_budget(budget)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which limiter bucket an `apiName` belongs to.
+pub fn api_for(rate_limit: &RateLimit) -> Option<Api> {
+    let name = rate_limit.api_name.to_ascii_lowercase();
+    if name.contains("trading") {
+        Some(Api::Trading)
+    } else if name.contains("marketing") {
+        Some(Api::Marketing)
+    } else if name.contains("browse") {
+        Some(Api::Browse)
+    } else if name.contains("identity") || name.contains("oauth") {
+        Some(Api::Identity)
+    } else {
+        None
+    }
+}
+
+/// One budget per API we limit. When an API reports several resources or
+/// windows, the one with the fewest calls left wins.
+pub fn budgets_from(
+    response: &RateLimitsResponse,
+    scope: BudgetScope,
+    owner: &str,
+    now: u64,
+) -> Vec<Budget> {
+    let mut budgets: Vec<Budget> = Vec::new();
+
+    for rate_limit in &response.rate_limits {
+        let Some(api) = api_for(rate_limit) else {
+            continue;
+        };
+
+        for rate in rate_limit.resources.iter().flat_map(|r| &r.rates) {
+            let Some(limit) = rate.limit else {
+                continue;
+            };
+            let remaining = rate
+                .remaining
+                .unwrap_or_else(|| limit.saturating_sub(rate.count.unwrap_or(0)));
+            let window_secs = rate.time_window.unwrap_or(super::rate_limit::DAY_SECS);
+            let resets_at = rate
+                .reset
+                .and_then(|reset| u64::try_from(reset.timestamp()).ok())
+                .unwrap_or(now + window_secs);
+
+            let budget = Budget {
+                scope,
+                owner: owner.to_string(),
+                api,
+                limit: Some(limit),
+                used: limit.saturating_sub(remaining),
+                window_secs,
+                resets_at,
+            };
+
+            match budgets.iter_mut().find(|b| b.api == api) {
+                Some(existing) if existing.remaining() <= budget.remaining() => {}
+                Some(existing) => *existing = budget,
+                None => budgets.push(budget),
+            }
+        }
+    }
+
+    budgets
+}
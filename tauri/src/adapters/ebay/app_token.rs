@@ -0,0 +1,183 @@
+//! Application (client-credentials) token cache for the Buy APIs.
+//!
+//! Replaces `BuyApiClient.getAccessToken` in `ebay-buyer-listings.js`. One
+//! token is kept per environment and scope set, and concurrent callers wait
+//! on a single fetch instead of each requesting their own token.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex as StdMutex};
+
+use tokio::sync::Mutex;
+use url::Url;
+
+use super::oauth::{OAuthClient, OAuthConfig};
+use super::rate_limit::Throttle;
+use super::token_provider::{is_token_expired, BoxFuture, TokenSource, EXPIRY_BUFFER_SECS};
+use super::vault::unix_now;
+use crate::environment::Environment;
+use crate::error::Result;
+
+/// Scope requested for Browse API calls when none is given.
+pub const DEFAULT_APP_SCOPE: &str = "https://api.ebay.com/oauth/api_scope";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    environment: Environment,
+    scopes: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: u64,
+}
+
+type Slot = Arc<Mutex<Option<CachedToken>>>;
+
+#[derive(Debug)]
+pub struct AppTokenCache {
+    sandbox: OAuthClient,
+    production: OAuthClient,
+    slots: StdMutex<HashMap<CacheKey, Slot>>,
+}
+
+impl AppTokenCache {
+    pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        let client_id = client_id.into();
+        let client_secret = client_secret.into();
+        let client = |environment| {
+            OAuthClient::new(OAuthConfig {
+                client_id: client_id.clone(),
+                client_secret: client_secret.clone(),
+                ru_name: String::new(),
+                environment,
+                scopes: vec![DEFAULT_APP_SCOPE.to_string()],
+            })
+        };
+
+        Self {
+            sandbox: client(Environment::Sandbox),
+            production: client(Environment::Production),
+            slots: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    /// Override the token endpoint for one environment (used by tests).
+    pub fn with_token_url(mut self, environment: Environment, token_url: Url) -> Self {
+        match environment {
+            Environment::Sandbox => self.sandbox = self.sandbox.with_token_url(token_url),
+            Environment::Production => self.production = self.production.with_token_url(token_url),
+        }
+        self
+    }
+
+    /// Count token requests against the Identity API limits.
+    pub fn with_throttle(mut self, throttle: Throttle) -> Self {
+        self.sandbox = self.sandbox.with_throttle(throttle.clone());
+        self.production = self.production.with_throttle(throttle);
+        self
+    }
+
+    /// Application token for `environment` and `scopes`, fetched only when
+    /// there is no cached token or it is within the expiry buffer. No scopes
+    /// means [`DEFAULT_APP_SCOPE`].
+    pub async fn token(&self, environment: Environment, scopes: &[&str]) -> Result<String> {
+        let scopes = or_default(scopes);
+        let slot = self.slot(environment, scopes);
+        let mut cached = slot.lock().await;
+
+        if let Some(token) = cached.as_ref() {
+            if !is_token_expired(token.expires_at, EXPIRY_BUFFER_SECS, unix_now()) {
+                return Ok(token.access_token.clone());
+            }
+        }
+
+        log::info!("Getting application access token...");
+        let scopes: Vec<String> = scopes.iter().map(|s| s.to_string()).collect();
+        let response = self.client(environment).request_app_token(&scopes).await?;
+        log::info!("Access token obtained successfully");
+
+        let token = CachedToken {
+            access_token: response.access_token,
+            expires_at: unix_now() + response.expires_in,
+        };
+        let access_token = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+
+    /// Drop the cached token if it is still `stale`.
+    pub async fn invalidate(&self, environment: Environment, scopes: &[&str], stale: &str) {
+        let slot = self.slot(environment, or_default(scopes));
+        let mut cached = slot.lock().await;
+        if cached.as_ref().map(|t| t.access_token.as_str()) == Some(stale) {
+            *cached = None;
+        }
+    }
+
+    fn client(&self, environment: Environment) -> &OAuthClient {
+        match environment {
+            Environment::Sandbox => &self.sandbox,
+            Environment::Production => &self.production,
+        }
+    }
+
+    fn slot(&self, environment: Environment, scopes: &[&str]) -> Slot {
+        let key = CacheKey {
+            environment,
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+        };
+        self.slots
+            .lock()
+            .expect("app token cache lock poisoned")
+            .entry(key)
+            .or_default()
+            .clone()
+    }
+}
+
+fn or_default<'a>(scopes: &'a [&'a str]) -> &'a [&'a str] {
+    if scopes.is_empty() {
+        &[DEFAULT_APP_SCOPE]
+    } else {
+        scopes
+    }
+}
+
+/// [`TokenSource`] view of the cache for a fixed environment and scope set,
+/// so Buy API clients can use it like a user token provider.
+#[derive(Debug, Clone)]
+pub struct AppTokenSource {
+    cache: Arc<AppTokenCache>,
+    environment: Environment,
+    scopes: Vec<String>,
+}
+
+impl AppTokenSource {
+    pub fn new(cache: Arc<AppTokenCache>, environment: Environment, scopes: Vec<String>) -> Self {
+        Self {
+            cache,
+            environment,
+            scopes,
+        }
+    }
+
+    fn scope_refs(&self) -> Vec<&str> {
+        self.scopes.iter().map(String::as_str).collect()
+    }
+}
+
+impl TokenSource for AppTokenSource {
+    fn bearer_token(&self) -> BoxFuture<'_, Result<String>> {
+        Box::pin(async move { self.cache.token(self.environment, &self.scope_refs()).await })
+    }
+
+    fn invalidate<'a>(&'a self, stale: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.cache
+                .invalidate(self.environment, &self.scope_refs(), stale)
+                .await;
+            Ok(())
+        })
+    }
+}
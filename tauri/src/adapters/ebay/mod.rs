@@ -0,0 +1,18 @@
+//! Authentication for the eBay APIs.
+
+pub mod analytics;
+pub mod app_token;
+pub mod oauth;
+pub mod rate_limit;
+pub mod redirect_listener;
+pub mod retry;
+pub mod token_provider;
+pub mod vault;
+
+pub use app_token::{AppTokenCache, AppTokenSource};
+pub use oauth::{OAuthClient, OAuthConfig, TokenResponse};
+pub use rate_limit::{Api, RateLimiter, Throttle};
+pub use redirect_listener::{AuthorizationCallback, RedirectListener};
+pub use retry::{Retrier, RetryPolicy};
+pub use token_provider::{send_authorized, TokenSource, UserTokenProvider};
+pub use vault::{StoredTokens, TokenVault, VaultKey};
@@ -0,0 +1,270 @@
+//! eBay OAuth authorization-code flow.
+//!
+//! Port of `ebay-user-token.js`:
+//! 1. Builds the consent URL the user opens in a browser
+//! 2. Captures `code` and `state` on a local redirect listener
+//! 3. Exchanges the code for access and refresh tokens
+
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::rate_limit::Throttle;
+use super::redirect_listener::RedirectListener;
+use crate::environment::Environment;
+use crate::error::{Error, Result};
+
+pub const TOKEN_PATH: &str = "/identity/v1/oauth2/token";
+
+/// Scopes requested when none are configured.
+pub const DEFAULT_SCOPES: &[&str] = &[
+    "https://api.ebay.com/oauth/api_scope",
+    "https://api.ebay.com/oauth/api_scope/sell.inventory.readonly",
+    "https://api.ebay.com/oauth/api_scope/sell.inventory",
+    MARKETING_READ_SCOPE,
+    MARKETING_SCOPE,
+];
+
+/// Needed to create, change, pause or delete promotions.
+pub const MARKETING_SCOPE: &str = "https://api.ebay.com/oauth/api_scope/sell.marketing";
+
+/// Enough to list promotions and read their reports.
+pub const MARKETING_READ_SCOPE: &str =
+    "https://api.ebay.com/oauth/api_scope/sell.marketing.readonly";
+
+/// How long `authorize` waits for the user to finish consent.
+pub const DEFAULT_CONSENT_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone)]
+pub struct OAuthConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    /// eBay RuName, sent as `redirect_uri`. Its accept URL must point at the
+    /// redirect listener.
+    pub ru_name: String,
+    pub environment: Environment,
+    pub scopes: Vec<String>,
+}
+
+impl OAuthConfig {
+    pub fn new(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        ru_name: impl Into<String>,
+        environment: Environment,
+    ) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            ru_name: ru_name.into(),
+            environment,
+            scopes: DEFAULT_SCOPES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    pub fn scope_string(&self) -> String {
+        self.scopes.join(" ")
+    }
+}
+
+/// Body returned by `/identity/v1/oauth2/token`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub expires_in: u64,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub refresh_token_expires_in: Option<u64>,
+    pub token_type: String,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthErrorBody {
+    error: String,
+    #[serde(default)]
+    error_description: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuthClient {
+    http: reqwest::Client,
+    config: OAuthConfig,
+    auth_url: Url,
+    token_url: Url,
+    throttle: Option<Throttle>,
+}
+
+impl OAuthClient {
+    pub fn new(config: OAuthConfig) -> Self {
+        let auth_url = config.environment.auth_url();
+        let token_url = config
+            .environment
+            .api_base_url()
+            .join(TOKEN_PATH)
+            .expect("static URL");
+
+        Self {
+            http: reqwest::Client::new(),
+            config,
+            auth_url,
+            token_url,
+            throttle: None,
+        }
+    }
+
+    /// Override the consent page URL (used by tests and custom deployments).
+    pub fn with_auth_url(mut self, auth_url: Url) -> Self {
+        self.auth_url = auth_url;
+        self
+    }
+
+    /// Override the token endpoint URL (used by tests and custom deployments).
+    pub fn with_token_url(mut self, token_url: Url) -> Self {
+        self.token_url = token_url;
+        self
+    }
+
+    /// Count token requests against the Identity API limits.
+    pub fn with_throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    pub fn config(&self) -> &OAuthConfig {
+        &self.config
+    }
+
+    pub fn token_url(&self) -> &Url {
+        &self.token_url
+    }
+
+    /// Build the consent URL the user opens to authorize the application.
+    pub fn consent_url(&self, state: &str) -> Url {
+        let mut url = self.auth_url.clone();
+        url.query_pairs_mut()
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("response_type", "code")
+            .append_pair("redirect_uri", &self.config.ru_name)
+            .append_pair("scope", &self.config.scope_string())
+            .append_pair("state", state);
+        url
+    }
+
+    /// Exchange an authorization code for access and refresh tokens.
+    pub async fn exchange_code(&self, code: &str) -> Result<TokenResponse> {
+        self.request_token(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.ru_name),
+        ])
+        .await
+    }
+
+    /// Get a new access token with the refresh-token grant. `scopes` must be
+    /// the same as, or a subset of, the scopes originally consented to.
+    pub async fn refresh_access_token(
+        &self,
+        refresh_token: &str,
+        scopes: &[String],
+    ) -> Result<TokenResponse> {
+        let scope = scopes.join(" ");
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ];
+        if !scope.is_empty() {
+            form.push(("scope", &scope));
+        }
+        self.request_token(&form).await
+    }
+
+    /// Get an application token with the client-credentials grant.
+    pub async fn request_app_token(&self, scopes: &[String]) -> Result<TokenResponse> {
+        let scope = scopes.join(" ");
+        self.request_token(&[("grant_type", "client_credentials"), ("scope", &scope)])
+            .await
+    }
+
+    /// Run the whole flow: send the user to the consent page via `open`,
+    /// wait on `listener` for the redirect, validate `state` and exchange
+    /// the code.
+    pub async fn authorize<F>(
+        &self,
+        listener: &RedirectListener,
+        open: F,
+        timeout: Duration,
+    ) -> Result<TokenResponse>
+    where
+        F: FnOnce(&Url),
+    {
+        let state = generate_state();
+        open(&self.consent_url(&state));
+
+        let callback = tokio::time::timeout(timeout, listener.wait_for_callback())
+            .await
+            .map_err(|_| Error::Timeout("authorization redirect"))??;
+
+        if callback.state.as_deref() != Some(state.as_str()) {
+            return Err(Error::StateMismatch);
+        }
+
+        self.exchange_code(&callback.code).await
+    }
+
+    /// POST a form to the token endpoint with the client's Basic credentials.
+    pub(crate) async fn request_token(&self, form: &[(&str, &str)]) -> Result<TokenResponse> {
+        if let Some(throttle) = &self.throttle {
+            throttle.acquire().await?;
+        }
+
+        let response = self
+            .http
+            .post(self.token_url.clone())
+            .basic_auth(&self.config.client_id, Some(&self.config.client_secret))
+            .form(form)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if status.is_success() {
+            // The body carries tokens: report where parsing failed, not what.
+            return serde_json::from_str(&body).map_err(|err| {
+                Error::InvalidResponse(format!(
+                    "failed to parse token response ({} bytes): {:?} error at line {} column {}",
+                    body.len(),
+                    err.classify(),
+                    err.line(),
+                    err.column()
+                ))
+            });
+        }
+
+        match serde_json::from_str::<OAuthErrorBody>(&body) {
+            Ok(err) => Err(Error::OAuth {
+                status: status.as_u16(),
+                error: err.error,
+                description: err.error_description,
+            }),
+            Err(_) => Err(Error::OAuth {
+                status: status.as_u16(),
+                error: "http_error".to_string(),
+                description: body,
+            }),
+        }
+    }
+}
+
+/// Random, URL-safe value for the `state` parameter.
+pub fn generate_state() -> String {
+    let bytes: [u8; 32] = rand::rng().random();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
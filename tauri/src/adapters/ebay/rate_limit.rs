@@ -0,0 +1,590 @@
+//! Shared rate limiting for every eBay client.
+//!
+//! Replaces the `RateLimiter` class copied into the exporter, discount manager
+//! and buyer listings scripts (a sliding array of timestamps at a hard-coded
+//! `REQUESTS_PER_SECOND`). Each API family gets its own token bucket, and
+//! calls are also counted against daily budgets per application (client ID)
+//! and per user (account), which are kept on disk so they survive restarts.
+//! When a budget is nearly spent the limiter either refuses the call or waits
+//! for the budget to reset, depending on [`BudgetPolicy`].
+//!
+//! Counting a call only touches memory. The budget file is rewritten every
+//! [`RateLimitConfig::persist_every`] calls or
+//! [`RateLimitConfig::persist_interval`], whichever comes first, on a blocking
+//! thread outside the lock, and once more when the limiter is dropped.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::vault::{unix_now, write_atomic};
+use crate::error::{Error, Result};
+
+const FILE_VERSION: u32 = 1;
+
+/// eBay's call limits are counted over a rolling day.
+pub const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// API family with its own per-second rate and daily limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Api {
+    Trading,
+    Marketing,
+    Browse,
+    Identity,
+}
+
+impl Api {
+    pub const ALL: [Api; 4] = [Api::Trading, Api::Marketing, Api::Browse, Api::Identity];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Api::Trading => "trading",
+            Api::Marketing => "marketing",
+            Api::Browse => "browse",
+            Api::Identity => "identity",
+        }
+    }
+}
+
+impl fmt::Display for Api {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Who a daily budget belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetScope {
+    /// Application-level limit, keyed by client ID.
+    App,
+    /// User-level limit, keyed by account name.
+    User,
+}
+
+/// What to do with a call once a daily budget is down to its reserve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPolicy {
+    /// Fail with [`Error::QuotaExhausted`].
+    #[default]
+    Refuse,
+    /// Wait until the budget resets.
+    Queue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketConfig {
+    /// Sustained calls per second.
+    pub per_second: f64,
+    /// Calls that may go out back to back before the rate applies.
+    pub burst: u32,
+}
+
+impl BucketConfig {
+    pub const fn new(per_second: f64, burst: u32) -> Self {
+        Self { per_second, burst }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub buckets: HashMap<Api, BucketConfig>,
+    /// Default daily call limit per application. APIs not listed are counted
+    /// but not limited.
+    pub app_limits: HashMap<Api, u64>,
+    /// Default daily call limit per user.
+    pub user_limits: HashMap<Api, u64>,
+    /// Share of each budget held back, e.g. `0.02` stops at 98% used.
+    pub reserve: f64,
+    pub policy: BudgetPolicy,
+    /// Calls counted between writes of the budget file.
+    pub persist_every: u32,
+    /// Longest a counted call goes unwritten.
+    pub persist_interval: Duration,
+}
+
+impl Default for RateLimitConfig {
+    /// Per-second rates from the scripts' `CONFIG` blocks; daily limits are
+    /// eBay's standard application quotas.
+    fn default() -> Self {
+        Self {
+            buckets: HashMap::from([
+                (Api::Trading, BucketConfig::new(2.0, 2)),
+                (Api::Marketing, BucketConfig::new(5.0, 5)),
+                (Api::Browse, BucketConfig::new(5.0, 5)),
+                (Api::Identity, BucketConfig::new(1.0, 2)),
+            ]),
+            app_limits: HashMap::from([
+                (Api::Trading, 5_000),
+                (Api::Marketing, 10_000),
+                (Api::Browse, 5_000),
+                (Api::Identity, 1_000),
+            ]),
+            user_limits: HashMap::new(),
+            reserve: 0.02,
+            policy: BudgetPolicy::Refuse,
+            persist_every: 20,
+            persist_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Daily budget for one scope, owner and API, as stored and shown in the UI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Budget {
+    pub scope: BudgetScope,
+    /// Client ID for [`BudgetScope::App`], account name for [`BudgetScope::User`].
+    pub owner: String,
+    pub api: Api,
+    /// `None` when the calls are only counted.
+    pub limit: Option<u64>,
+    pub used: u64,
+    pub window_secs: u64,
+    /// Unix time at which `used` goes back to zero.
+    pub resets_at: u64,
+}
+
+impl Budget {
+    pub fn remaining(&self) -> Option<u64> {
+        self.limit.map(|limit| limit.saturating_sub(self.used))
+    }
+
+    fn roll_over(&mut self, now: u64) {
+        if now >= self.resets_at {
+            self.used = 0;
+            self.resets_at = now + self.window_secs;
+        }
+    }
+
+    /// Calls left before the reserve is reached.
+    fn spendable(&self, reserve: f64) -> Option<u64> {
+        let limit = self.limit?;
+        let held_back = (limit as f64 * reserve).ceil() as u64;
+        Some(limit.saturating_sub(held_back).saturating_sub(self.used))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BudgetKey {
+    scope: BudgetScope,
+    owner: String,
+    api: Api,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BudgetFile {
+    version: u32,
+    budgets: Vec<Budget>,
+}
+
+/// Budgets and how far they are ahead of the file.
+#[derive(Debug)]
+struct Budgets {
+    map: HashMap<BudgetKey, Budget>,
+    /// Calls counted since the last snapshot was taken.
+    unsaved: u32,
+    snapshot_at: Instant,
+    /// Bumped for every snapshot, so an older one never overwrites a newer.
+    generation: u64,
+}
+
+impl Budgets {
+    fn new(map: HashMap<BudgetKey, Budget>) -> Self {
+        Self {
+            map,
+            unsaved: 0,
+            snapshot_at: Instant::now(),
+            generation: 0,
+        }
+    }
+}
+
+/// Serialized budgets waiting to be written.
+struct Snapshot {
+    generation: u64,
+    bytes: Vec<u8>,
+}
+
+/// Token bucket that hands out reservations, so waiting happens outside the
+/// lock and callers are served in the order they arrived.
+#[derive(Debug)]
+struct TokenBucket {
+    config: BucketConfig,
+    state: StdMutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: BucketConfig) -> Self {
+        Self {
+            config,
+            state: StdMutex::new(BucketState {
+                tokens: f64::from(config.burst.max(1)),
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take one token and return how long to wait before using it.
+    fn reserve(&self) -> Duration {
+        let rate = self.config.per_second.max(f64::MIN_POSITIVE);
+        let burst = f64::from(self.config.burst.max(1));
+        let mut state = self.state.lock().expect("bucket lock poisoned");
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.updated).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * rate).min(burst);
+        state.updated = now;
+        state.tokens -= 1.0;
+
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / rate)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: HashMap<Api, TokenBucket>,
+    budgets: StdMutex<Budgets>,
+    path: Option<PathBuf>,
+    /// Generation of the snapshot in the file; held while writing it.
+    written: Arc<StdMutex<u64>>,
+}
+
+impl RateLimiter {
+    /// Limiter whose budgets live only in memory.
+    pub fn new(config: RateLimitConfig) -> Self {
+        let buckets = config
+            .buckets
+            .iter()
+            .map(|(api, bucket)| (*api, TokenBucket::new(*bucket)))
+            .collect();
+
+        Self {
+            config,
+            buckets,
+            budgets: StdMutex::new(Budgets::new(HashMap::new())),
+            path: None,
+            written: Arc::new(StdMutex::new(0)),
+        }
+    }
+
+    /// Limiter whose budgets are loaded from and saved to `path`.
+    pub fn open(path: impl Into<PathBuf>, config: RateLimitConfig) -> Result<Self> {
+        let path = path.into();
+        let mut limiter = Self::new(config);
+
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                let file: BudgetFile = serde_json::from_slice(&bytes)?;
+                // Counts in a layout this build does not know would be
+                // misread, and saving would overwrite them.
+                if file.version != FILE_VERSION {
+                    return Err(Error::InvalidResponse(format!(
+                        "rate limit file {} is version {}, this app reads version {FILE_VERSION}",
+                        path.display(),
+                        file.version
+                    )));
+                }
+                let budgets = file
+                    .budgets
+                    .into_iter()
+                    .map(|budget| (key_of(&budget), budget))
+                    .collect();
+                limiter.budgets = StdMutex::new(Budgets::new(budgets));
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        limiter.path = Some(path);
+        Ok(limiter)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Wait for a slot to call `api` as `app_id` (and `user`, if the call is
+    /// made with a user token), and count the call against both budgets.
+    pub async fn acquire(&self, api: Api, app_id: &str, user: Option<&str>) -> Result<()> {
+        loop {
+            match self.try_spend(api, app_id, user)? {
+                Spend::Counted(snapshot) => {
+                    if let Some(snapshot) = snapshot {
+                        self.write_in_background(snapshot).await?;
+                    }
+                    break;
+                }
+                Spend::Wait(wait) => {
+                    log::warn!(
+                        "Daily {api} budget nearly used up, waiting {}s for it to reset",
+                        wait.as_secs()
+                    );
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+
+        if let Some(bucket) = self.buckets.get(&api) {
+            let wait = bucket.reserve();
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every budget seen so far, for display.
+    pub fn budgets(&self) -> Vec<Budget> {
+        let now = unix_now();
+        let budgets = self.budgets.lock().expect("budget lock poisoned");
+        let mut list: Vec<Budget> = budgets
+            .map
+            .values()
+            .cloned()
+            .map(|mut budget| {
+                budget.roll_over(now);
+                budget
+            })
+            .collect();
+        list.sort_by(|a, b| (a.scope, &a.owner, a.api).cmp(&(b.scope, &b.owner, b.api)));
+        list
+    }
+
+    pub fn budget(&self, scope: BudgetScope, owner: &str, api: Api) -> Budget {
+        let mut budgets = self.budgets.lock().expect("budget lock poisoned");
+        let budget = self.entry(&mut budgets.map, scope, owner, api, unix_now());
+        budget.clone()
+    }
+
+    /// Overwrite a budget, e.g. with the live numbers eBay reports, and
+    /// write the budget file.
+    pub fn set_budget(&self, budget: Budget) -> Result<()> {
+        {
+            let mut budgets = self.budgets.lock().expect("budget lock poisoned");
+            budgets.map.insert(key_of(&budget), budget);
+            budgets.unsaved += 1;
+        }
+        self.flush()
+    }
+
+    /// Write the budget file now if any call has not been written yet.
+    pub fn flush(&self) -> Result<()> {
+        let snapshot = {
+            let mut budgets = self.budgets.lock().expect("budget lock poisoned");
+            if budgets.unsaved == 0 {
+                return Ok(());
+            }
+            self.snapshot(&mut budgets)?
+        };
+        match (snapshot, &self.path) {
+            (Some(snapshot), Some(path)) => write_snapshot(path, &self.written, snapshot),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check both budgets and count the call if they allow it. Returns how
+    /// long to wait when the policy is to queue, or the budgets to write
+    /// when a write is due.
+    fn try_spend(&self, api: Api, app_id: &str, user: Option<&str>) -> Result<Spend> {
+        let now = unix_now();
+        let mut budgets = self.budgets.lock().expect("budget lock poisoned");
+
+        let mut scopes = vec![(BudgetScope::App, app_id)];
+        scopes.extend(user.map(|user| (BudgetScope::User, user)));
+
+        for (scope, owner) in &scopes {
+            let reserve = self.config.reserve;
+            let budget = self.entry(&mut budgets.map, *scope, owner, api, now);
+            if budget.spendable(reserve) == Some(0) {
+                return match self.config.policy {
+                    BudgetPolicy::Queue => Ok(Spend::Wait(Duration::from_secs(
+                        budget.resets_at.saturating_sub(now).max(1),
+                    ))),
+                    BudgetPolicy::Refuse => Err(Error::QuotaExhausted(format!(
+                        "daily {api} budget for {} {owner} is used up ({} of {} calls) until {}",
+                        scope_name(*scope),
+                        budget.used,
+                        budget.limit.unwrap_or_default(),
+                        budget.resets_at
+                    ))),
+                };
+            }
+        }
+
+        for (scope, owner) in &scopes {
+            self.entry(&mut budgets.map, *scope, owner, api, now).used += 1;
+        }
+        budgets.unsaved += 1;
+        let due = budgets.unsaved >= self.config.persist_every.max(1)
+            || budgets.snapshot_at.elapsed() >= self.config.persist_interval;
+        if !due {
+            return Ok(Spend::Counted(None));
+        }
+        Ok(Spend::Counted(self.snapshot(&mut budgets)?))
+    }
+
+    /// The budget for `key`, created from the configured defaults and rolled
+    /// over if its window has passed.
+    fn entry<'b>(
+        &self,
+        budgets: &'b mut HashMap<BudgetKey, Budget>,
+        scope: BudgetScope,
+        owner: &str,
+        api: Api,
+        now: u64,
+    ) -> &'b mut Budget {
+        let key = BudgetKey {
+            scope,
+            owner: owner.to_string(),
+            api,
+        };
+        let budget = budgets.entry(key).or_insert_with(|| {
+            let limits = match scope {
+                BudgetScope::App => &self.config.app_limits,
+                BudgetScope::User => &self.config.user_limits,
+            };
+            Budget {
+                scope,
+                owner: owner.to_string(),
+                api,
+                limit: limits.get(&api).copied(),
+                used: 0,
+                window_secs: DAY_SECS,
+                resets_at: now + DAY_SECS,
+            }
+        });
+        budget.roll_over(now);
+        budget
+    }
+
+    /// Serialize the budgets for writing and mark them saved. `None` for a
+    /// limiter without a file.
+    fn snapshot(&self, budgets: &mut Budgets) -> Result<Option<Snapshot>> {
+        if self.path.is_none() {
+            budgets.unsaved = 0;
+            return Ok(None);
+        }
+        let file = BudgetFile {
+            version: FILE_VERSION,
+            budgets: budgets.map.values().cloned().collect(),
+        };
+        let bytes = serde_json::to_vec_pretty(&file)?;
+        budgets.unsaved = 0;
+        budgets.snapshot_at = Instant::now();
+        budgets.generation += 1;
+        Ok(Some(Snapshot {
+            generation: budgets.generation,
+            bytes,
+        }))
+    }
+
+    /// Write `snapshot` on a blocking thread, so the disk is never waited on
+    /// under the budget lock or on a runtime worker.
+    async fn write_in_background(&self, snapshot: Snapshot) -> Result<()> {
+        let Some(path) = self.path.clone() else {
+            return Ok(());
+        };
+        let written = self.written.clone();
+        tokio::task::spawn_blocking(move || write_snapshot(&path, &written, snapshot))
+            .await
+            .map_err(|err| Error::Io(std::io::Error::other(err)))?
+    }
+}
+
+impl Drop for RateLimiter {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            log::warn!("Could not save call budgets: {err}");
+        }
+    }
+}
+
+/// Outcome of [`RateLimiter::try_spend`].
+enum Spend {
+    /// The call was counted; write the snapshot if there is one.
+    Counted(Option<Snapshot>),
+    /// A budget is at its reserve; try again after this long.
+    Wait(Duration),
+}
+
+/// Write `snapshot` unless a newer one is already on disk.
+fn write_snapshot(path: &Path, written: &StdMutex<u64>, snapshot: Snapshot) -> Result<()> {
+    let mut written = written.lock().expect("budget file lock poisoned");
+    if snapshot.generation <= *written {
+        return Ok(());
+    }
+    write_atomic(path, &snapshot.bytes)?;
+    *written = snapshot.generation;
+    Ok(())
+}
+
+/// A limiter bound to one API and identity, handed to a client so it can call
+/// [`Throttle::acquire`] before every request.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    limiter: Arc<RateLimiter>,
+    api: Api,
+    app_id: String,
+    user: Option<String>,
+}
+
+impl Throttle {
+    pub fn new(limiter: Arc<RateLimiter>, api: Api, app_id: impl Into<String>) -> Self {
+        Self {
+            limiter,
+            api,
+            app_id: app_id.into(),
+            user: None,
+        }
+    }
+
+    /// Also count calls against `user`'s budget.
+    pub fn for_user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    pub fn api(&self) -> Api {
+        self.api
+    }
+
+    pub async fn acquire(&self) -> Result<()> {
+        self.limiter
+            .acquire(self.api, &self.app_id, self.user.as_deref())
+            .await
+    }
+}
+
+fn key_of(budget: &Budget) -> BudgetKey {
+    BudgetKey {
+        scope: budget.scope,
+        owner: budget.owner.clone(),
+        api: budget.api,
+    }
+}
+
+fn scope_name(scope: BudgetScope) -> &'static str {
+    match scope {
+        BudgetScope::App => "app",
+        BudgetScope::User => "user",
+    }
+}
@@ -0,0 +1,183 @@
+//! Loopback HTTP listener that captures the OAuth redirect.
+//!
+//! The RuName's accept URL points at this listener, so eBay sends the browser
+//! here with `code` and `state` instead of the user copying them into `.env`.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use url::Url;
+
+use crate::error::{Error, Result};
+
+/// Upper bound on the request line and headers we are willing to read.
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+
+/// How long a connection may take to send its request before it is dropped.
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SUCCESS_PAGE: &str = "<html><body><h2>eBay authorization complete</h2>\
+<p>You can close this window and return to the app.</p></body></html>";
+
+const FAILURE_PAGE: &str = "<html><body><h2>eBay authorization failed</h2>\
+<p>Return to the app for details.</p></body></html>";
+
+/// Query parameters eBay appends to the accept URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationCallback {
+    pub code: String,
+    pub state: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct RedirectListener {
+    listener: TcpListener,
+    path: String,
+    read_timeout: Duration,
+}
+
+impl RedirectListener {
+    /// Bind to `addr` (e.g. `127.0.0.1:8080`) and accept redirects on `path`.
+    pub async fn bind(addr: impl ToSocketAddrs, path: &str) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let path = if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{path}")
+        };
+
+        Ok(Self {
+            listener,
+            path,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+        })
+    }
+
+    /// How long each connection may take to send its request. One that stays
+    /// idle longer (a browser's speculative preconnect, say) is dropped so
+    /// the redirect behind it is still accepted.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// URL to register as the RuName's accept URL.
+    pub fn redirect_url(&self) -> Result<Url> {
+        let url = format!("http://{}{}", self.local_addr()?, self.path);
+        Ok(Url::parse(&url)?)
+    }
+
+    /// Accept connections until one hits the callback path, then return its
+    /// `code` and `state`. Other paths (favicon requests etc.) get a 404.
+    pub async fn wait_for_callback(&self) -> Result<AuthorizationCallback> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+
+            let read = tokio::time::timeout(self.read_timeout, read_request_target(&mut stream));
+            let target = match read.await {
+                Ok(Ok(target)) => target,
+                Ok(Err(_)) => {
+                    let _ = respond(&mut stream, "400 Bad Request", FAILURE_PAGE).await;
+                    continue;
+                }
+                Err(_) => {
+                    log::debug!("Dropping a redirect connection that sent no request");
+                    continue;
+                }
+            };
+
+            let url = Url::parse("http://localhost")?.join(&target)?;
+            if url.path() != self.path {
+                let _ = respond(&mut stream, "404 Not Found", "").await;
+                continue;
+            }
+
+            let result = parse_callback(&url);
+            let (status, page) = match result {
+                Ok(_) => ("200 OK", SUCCESS_PAGE),
+                Err(_) => ("200 OK", FAILURE_PAGE),
+            };
+            let _ = respond(&mut stream, status, page).await;
+            return result;
+        }
+    }
+}
+
+/// Read the request line and headers, returning the request target.
+async fn read_request_target(stream: &mut TcpStream) -> Result<String> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+
+    let mut read = request_line.len();
+    loop {
+        let mut header = String::new();
+        let n = reader.read_line(&mut header).await?;
+        read += n;
+        if n == 0 || header == "\r\n" || header == "\n" {
+            break;
+        }
+        if read > MAX_REQUEST_BYTES {
+            return Err(Error::InvalidResponse(
+                "redirect request too large".to_string(),
+            ));
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => Ok(target.to_string()),
+        _ => Err(Error::InvalidResponse(format!(
+            "unexpected redirect request: {}",
+            request_line.trim_end()
+        ))),
+    }
+}
+
+fn parse_callback(url: &Url) -> Result<AuthorizationCallback> {
+    let mut code = None;
+    let mut state = None;
+    let mut error = None;
+    let mut error_description = None;
+
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "code" => code = Some(value.into_owned()),
+            "state" => state = Some(value.into_owned()),
+            "error" => error = Some(value.into_owned()),
+            "error_description" => error_description = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+
+    if let Some(error) = error {
+        let message = match error_description {
+            Some(description) => format!("{error}: {description}"),
+            None => error,
+        };
+        return Err(Error::AuthorizationDenied(message));
+    }
+
+    match code {
+        Some(code) if !code.is_empty() => Ok(AuthorizationCallback { code, state }),
+        _ => Err(Error::AuthorizationDenied(
+            "redirect did not include an authorization code".to_string(),
+        )),
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,305 @@
+//! Retry policy shared by every eBay client.
+//!
+//! The scripts each had their own loop: `TradingApiClient.makeRequest` retried
+//! everything twice, `MarketingApiClient` only what `isRetryableError` found
+//! in the message text, and Trading faults such as 518 (call usage limit) were
+//! never treated as throttling. Here errors are classified once, retried with
+//! exponential backoff and jitter (or the server's `Retry-After`), and each
+//! endpoint has a circuit breaker so a service that keeps failing is not
+//! hammered.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex as StdMutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+use crate::error::{Error, Result};
+use crate::internal::ebay::trading::client::INVALID_TOKEN_CODES;
+
+/// Trading API codes for call usage limits.
+const TRADING_THROTTLE_CODES: &[&str] = &["518"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Network trouble or a 5xx; worth retrying.
+    Transient,
+    /// eBay asked us to slow down; retry after a wait.
+    Throttled,
+    /// Token or consent problem; retrying the same request will not help.
+    Auth,
+    /// The request itself is wrong.
+    Validation,
+    Fatal,
+}
+
+impl ErrorClass {
+    pub fn is_retryable(self) -> bool {
+        matches!(self, ErrorClass::Transient | ErrorClass::Throttled)
+    }
+}
+
+pub fn classify(err: &Error) -> ErrorClass {
+    match err {
+        Error::Http(err) if err.is_builder() => ErrorClass::Fatal,
+        Error::Http(err) => match err.status() {
+            Some(status) => classify_status(status.as_u16()),
+            None => ErrorClass::Transient,
+        },
+        Error::Api { status, .. } => classify_status(*status),
+        Error::Trading(errors) => {
+            let has_any = |codes: &[&str]| codes.iter().any(|code| errors.has_code(code));
+            if has_any(TRADING_THROTTLE_CODES) {
+                ErrorClass::Throttled
+            } else if has_any(INVALID_TOKEN_CODES) {
+                ErrorClass::Auth
+            } else if errors
+                .errors
+                .iter()
+                .any(|e| e.error_classification.as_deref() == Some("SystemError"))
+            {
+                ErrorClass::Transient
+            } else {
+                ErrorClass::Validation
+            }
+        }
+        Error::OAuth { status, .. } if *status >= 500 => ErrorClass::Transient,
+        Error::OAuth { .. }
+        | Error::AuthorizationDenied(_)
+        | Error::StateMismatch
+        | Error::NotAuthorized(_) => ErrorClass::Auth,
+        Error::Timeout(_) => ErrorClass::Transient,
+        // The limiter has already decided; waiting here would bypass it.
+        Error::QuotaExhausted(_) | Error::CircuitOpen(_) => ErrorClass::Fatal,
+        _ => ErrorClass::Fatal,
+    }
+}
+
+fn classify_status(status: u16) -> ErrorClass {
+    match status {
+        429 => ErrorClass::Throttled,
+        408 | 500 | 502 | 503 | 504 => ErrorClass::Transient,
+        401 | 403 => ErrorClass::Auth,
+        400..=499 => ErrorClass::Validation,
+        _ => ErrorClass::Fatal,
+    }
+}
+
+/// `Retry-After` as either delay-seconds or an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value)
+        .ok()?
+        .with_timezone(&Utc);
+    (at - Utc::now()).to_std().ok()
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled on each further attempt.
+    pub base_delay: Duration,
+    /// Base delay when eBay reports throttling without a `Retry-After`.
+    pub throttle_delay: Duration,
+    /// Longest single wait. A `Retry-After` beyond this is not waited out.
+    pub max_delay: Duration,
+    /// Consecutive failures that open an endpoint's circuit.
+    pub failure_threshold: u32,
+    /// How long an open circuit rejects calls before letting one through.
+    pub open_for: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(2),
+            throttle_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(120),
+            failure_threshold: 5,
+            open_for: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before retry number `retry` (1-based), with equal jitter: half
+    /// the delay is fixed and half is random.
+    pub fn backoff(&self, class: ErrorClass, retry: u32) -> Duration {
+        let base = match class {
+            ErrorClass::Throttled => self.throttle_delay,
+            _ => self.base_delay,
+        };
+        let delay = base
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay);
+        let half = delay / 2;
+        half + half.mul_f64(rand::rng().random::<f64>())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Breaker {
+    failures: u32,
+    open_until: Option<Instant>,
+    /// When the one call let through a half-open circuit started.
+    probe_started: Option<Instant>,
+}
+
+/// What a finished call says about its endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Success,
+    /// Network trouble or a 5xx: the service may be down.
+    Outage,
+    /// Any other failure; says nothing about the service either way.
+    Other,
+}
+
+/// Runs requests under a [`RetryPolicy`] and tracks a circuit breaker per
+/// endpoint. Share one instance between clients.
+#[derive(Debug, Default)]
+pub struct Retrier {
+    policy: RetryPolicy,
+    breakers: StdMutex<HashMap<String, Breaker>>,
+}
+
+impl Retrier {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            breakers: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Run `op`, retrying transient and throttled failures. `endpoint` names
+    /// the circuit, e.g. `"trading"` or `"sell.marketing"`.
+    pub async fn run<T, F, Fut>(&self, endpoint: &str, mut op: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            self.check_circuit(endpoint)?;
+
+            let err = match op().await {
+                Ok(value) => {
+                    self.record(endpoint, Outcome::Success);
+                    if attempt > 1 {
+                        log::info!("Request succeeded on attempt {attempt}: {endpoint}");
+                    }
+                    return Ok(value);
+                }
+                Err(err) => err,
+            };
+
+            let class = classify(&err);
+            let outcome = match class {
+                ErrorClass::Transient => Outcome::Outage,
+                _ => Outcome::Other,
+            };
+            let opened = self.record(endpoint, outcome);
+            if opened || !class.is_retryable() || attempt >= self.policy.max_attempts {
+                return Err(err);
+            }
+
+            let wait = match server_delay(&err) {
+                Some(wait) if wait > self.policy.max_delay => return Err(err),
+                Some(wait) => wait,
+                None => self.policy.backoff(class, attempt),
+            };
+            log::warn!(
+                "Request failed (attempt {attempt}/{}): {err}. Retrying in {}ms...",
+                self.policy.max_attempts,
+                wait.as_millis()
+            );
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+
+    fn check_circuit(&self, endpoint: &str) -> Result<()> {
+        let mut breakers = self.breakers.lock().expect("breaker lock poisoned");
+        let Some(breaker) = breakers.get_mut(endpoint) else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        match breaker.open_until {
+            Some(until) if now < until => Err(Error::CircuitOpen(format!(
+                "{endpoint} failed {} times in a row, retrying in {}s",
+                breaker.failures,
+                until.saturating_duration_since(now).as_secs()
+            ))),
+            // Half-open: one call goes through to test the endpoint; the
+            // rest wait for its outcome. A probe that never reported back
+            // (its caller gave up) is replaced after `open_for`.
+            Some(_)
+                if breaker
+                    .probe_started
+                    .is_some_and(|started| now < started + self.policy.open_for) =>
+            {
+                Err(Error::CircuitOpen(format!(
+                    "{endpoint} failed {} times in a row, waiting on a test call",
+                    breaker.failures
+                )))
+            }
+            Some(_) => {
+                breaker.probe_started = Some(now);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Count a finished call against the endpoint's circuit. Only a success
+    /// closes it; only an outage counts towards opening it. Returns true if
+    /// the circuit is now open.
+    fn record(&self, endpoint: &str, outcome: Outcome) -> bool {
+        let mut breakers = self.breakers.lock().expect("breaker lock poisoned");
+        let breaker = breakers.entry(endpoint.to_string()).or_default();
+        let probed = breaker.probe_started.take().is_some();
+        match outcome {
+            Outcome::Success => {
+                if probed {
+                    log::info!("{endpoint} is answering again, resuming calls");
+                }
+                *breaker = Breaker::default();
+                return false;
+            }
+            // The endpoint answered; let the next call test it.
+            Outcome::Other => return false,
+            Outcome::Outage => breaker.failures += 1,
+        }
+
+        if probed
+            || (breaker.failures >= self.policy.failure_threshold && breaker.open_until.is_none())
+        {
+            log::warn!(
+                "{endpoint} failed {} times in a row, pausing calls for {}s",
+                breaker.failures,
+                self.policy.open_for.as_secs()
+            );
+            breaker.open_until = Some(Instant::now() + self.policy.open_for);
+        }
+        breaker.open_until.is_some()
+    }
+}
+
+fn server_delay(err: &Error) -> Option<Duration> {
+    match err {
+        Error::Api { retry_after, .. } => *retry_after,
+        _ => None,
+    }
+}
@@ -0,0 +1,179 @@
+//! Self-refreshing bearer tokens shared by every API client.
+//!
+//! Port of `refreshTokenIfNeeded` from `ebay-refresh-token.js`, except clients
+//! ask the provider for a token on every request instead of reading
+//! `EBAY_ACCESS_TOKEN` once, so a long run survives the token expiring.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use tokio::sync::Mutex;
+
+use super::oauth::OAuthClient;
+use super::vault::{unix_now, StoredTokens, TokenVault};
+use crate::error::{Error, Result};
+
+/// Refresh this many seconds before expiry, same as `isTokenExpired`.
+pub const EXPIRY_BUFFER_SECS: u64 = 300;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Anything that can hand out a bearer token for an API request.
+pub trait TokenSource: Send + Sync {
+    /// A token that is valid for at least the expiry buffer.
+    fn bearer_token(&self) -> BoxFuture<'_, Result<String>>;
+
+    /// Mark `stale` as rejected (e.g. after a 401) so the next call to
+    /// `bearer_token` fetches a new one.
+    fn invalidate<'a>(&'a self, stale: &'a str) -> BoxFuture<'a, Result<()>>;
+}
+
+/// True if a token expiring at `expires_at` is expired or will be within
+/// `buffer_secs` of `now`.
+pub fn is_token_expired(expires_at: u64, buffer_secs: u64, now: u64) -> bool {
+    now + buffer_secs > expires_at
+}
+
+/// User token provider backed by the vault and the refresh-token grant.
+///
+/// The cached token set sits behind an async mutex, so when many tasks find
+/// the token expired only the first one refreshes; the rest wait and then
+/// reuse the new token.
+#[derive(Debug)]
+pub struct UserTokenProvider {
+    oauth: OAuthClient,
+    vault: TokenVault,
+    cached: Mutex<Option<StoredTokens>>,
+}
+
+impl UserTokenProvider {
+    pub fn new(oauth: OAuthClient, vault: TokenVault) -> Self {
+        Self {
+            oauth,
+            vault,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub fn vault(&self) -> &TokenVault {
+        &self.vault
+    }
+
+    /// Save a freshly authorized token set and use it from now on.
+    pub async fn replace(&self, tokens: StoredTokens) -> Result<()> {
+        let mut cached = self.cached.lock().await;
+        self.vault.save(&tokens)?;
+        *cached = Some(tokens);
+        Ok(())
+    }
+
+    /// Current access token, refreshing it first if it is about to expire.
+    pub async fn access_token(&self) -> Result<String> {
+        let mut cached = self.cached.lock().await;
+
+        if cached.is_none() {
+            *cached = self.vault.load()?;
+        }
+
+        let tokens = cached.as_ref().ok_or_else(|| {
+            Error::NotAuthorized("no tokens stored, run the authorization flow first".to_string())
+        })?;
+
+        if !is_token_expired(tokens.expires_at, EXPIRY_BUFFER_SECS, unix_now()) {
+            return Ok(tokens.access_token.clone());
+        }
+
+        log::info!("Token expired or will expire soon, refreshing...");
+        let refreshed = self.refresh(tokens).await?;
+        let access_token = refreshed.access_token.clone();
+        *cached = Some(refreshed);
+        Ok(access_token)
+    }
+
+    /// Force a refresh if `stale` is still the cached token. If another task
+    /// already replaced it, this is a no-op.
+    pub async fn refresh_if_current(&self, stale: &str) -> Result<()> {
+        let mut cached = self.cached.lock().await;
+
+        if cached.is_none() {
+            *cached = self.vault.load()?;
+        }
+
+        let Some(tokens) = cached.as_ref() else {
+            return Err(Error::NotAuthorized(
+                "no tokens stored, run the authorization flow first".to_string(),
+            ));
+        };
+
+        if tokens.access_token != stale {
+            return Ok(());
+        }
+
+        let refreshed = self.refresh(tokens).await?;
+        *cached = Some(refreshed);
+        Ok(())
+    }
+
+    async fn refresh(&self, tokens: &StoredTokens) -> Result<StoredTokens> {
+        let refresh_token = tokens.refresh_token.as_deref().ok_or_else(|| {
+            Error::NotAuthorized("no refresh token stored, re-authorize the account".to_string())
+        })?;
+
+        if let Some(expires_at) = tokens.refresh_token_expires_at {
+            if is_token_expired(expires_at, 0, unix_now()) {
+                return Err(Error::NotAuthorized(
+                    "refresh token has expired, re-authorize the account".to_string(),
+                ));
+            }
+        }
+
+        // The code grant does not echo scopes back, so fall back to the ones
+        // the client asked consent for.
+        let scopes = if tokens.scopes.is_empty() {
+            &self.oauth.config().scopes
+        } else {
+            &tokens.scopes
+        };
+
+        let response = self
+            .oauth
+            .refresh_access_token(refresh_token, scopes)
+            .await?;
+        log::info!(
+            "Token refreshed successfully, new token expires in: {} seconds",
+            response.expires_in
+        );
+
+        self.vault.rotate(&response, tokens.environment)
+    }
+}
+
+impl TokenSource for UserTokenProvider {
+    fn bearer_token(&self) -> BoxFuture<'_, Result<String>> {
+        Box::pin(self.access_token())
+    }
+
+    fn invalidate<'a>(&'a self, stale: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.refresh_if_current(stale))
+    }
+}
+
+/// Send a request built by `build` with a bearer token from `tokens`. On a
+/// 401 the token is invalidated and the request is sent once more with a
+/// fresh token.
+pub async fn send_authorized<F>(tokens: &dyn TokenSource, build: F) -> Result<reqwest::Response>
+where
+    F: Fn(&str) -> reqwest::RequestBuilder,
+{
+    let token = tokens.bearer_token().await?;
+    let response = build(&token).send().await?;
+
+    if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+        return Ok(response);
+    }
+
+    log::warn!("Request was rejected with 401, retrying with a fresh token");
+    tokens.invalidate(&token).await?;
+    let token = tokens.bearer_token().await?;
+    Ok(build(&token).send().await?)
+}
@@ -0,0 +1,306 @@
+//! Encrypted on-disk token store.
+//!
+//! Replaces `updateEnvFile` in `ebay-user-token.js` and `ebay-refresh-token.js`,
+//! which rewrote `.env` with regexes and left tokens in plaintext. Tokens are
+//! sealed with XChaCha20-Poly1305 under a key derived from a passphrase
+//! (Argon2id) or read from a machine key file, and written atomically.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::oauth::TokenResponse;
+use crate::environment::Environment;
+use crate::error::{Error, Result};
+
+const FORMAT_VERSION: u32 = 1;
+const ASSOCIATED_DATA: &[u8] = b"ebay-connect-vault-v1";
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Current time as a Unix timestamp in seconds.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Token set kept in the vault.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredTokens {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Unix timestamp (seconds) when the access token expires.
+    pub expires_at: u64,
+    #[serde(default)]
+    pub refresh_token_expires_at: Option<u64>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    pub environment: Environment,
+}
+
+impl StoredTokens {
+    /// Build a token set from a token endpoint response received at `now`.
+    pub fn from_response(response: &TokenResponse, environment: Environment, now: u64) -> Self {
+        Self {
+            access_token: response.access_token.clone(),
+            refresh_token: response.refresh_token.clone(),
+            expires_at: now + response.expires_in,
+            refresh_token_expires_at: response.refresh_token_expires_in.map(|secs| now + secs),
+            scopes: response
+                .scope
+                .as_deref()
+                .map(|s| s.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default(),
+            environment,
+        }
+    }
+}
+
+/// Secret the vault is encrypted with.
+#[derive(Clone)]
+pub enum VaultKey {
+    /// Stretched with Argon2id using a per-file salt.
+    Passphrase(String),
+    /// Raw 256-bit key, e.g. loaded with [`VaultKey::from_key_file`].
+    Machine([u8; KEY_LEN]),
+}
+
+impl std::fmt::Debug for VaultKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VaultKey::Passphrase(_) => f.write_str("VaultKey::Passphrase(..)"),
+            VaultKey::Machine(_) => f.write_str("VaultKey::Machine(..)"),
+        }
+    }
+}
+
+impl VaultKey {
+    /// Load the machine key at `path`, generating it on first use.
+    pub fn from_key_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match fs::read(path) {
+            Ok(bytes) => {
+                let key: [u8; KEY_LEN] = bytes.as_slice().try_into().map_err(|_| {
+                    Error::Vault(format!("{} is not a valid key file", path.display()))
+                })?;
+                Ok(VaultKey::Machine(key))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let key: [u8; KEY_LEN] = rand::rng().random();
+                write_atomic(path, &key)?;
+                Ok(VaultKey::Machine(key))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn derive(&self, salt: &[u8]) -> Result<[u8; KEY_LEN]> {
+        match self {
+            VaultKey::Machine(key) => Ok(*key),
+            VaultKey::Passphrase(passphrase) => {
+                let mut key = [0u8; KEY_LEN];
+                Argon2::default()
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .map_err(|err| Error::Vault(format!("key derivation failed: {err}")))?;
+                Ok(key)
+            }
+        }
+    }
+}
+
+/// Encrypted envelope written to disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug)]
+pub struct TokenVault {
+    path: PathBuf,
+    key: VaultKey,
+}
+
+impl TokenVault {
+    pub fn new(path: impl Into<PathBuf>, key: VaultKey) -> Self {
+        Self {
+            path: path.into(),
+            key,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read and decrypt the stored tokens. Returns `None` if nothing is saved.
+    pub fn load(&self) -> Result<Option<StoredTokens>> {
+        read_encrypted(&self.path, &self.key)
+    }
+
+    /// Encrypt and atomically replace the stored tokens.
+    pub fn save(&self, tokens: &StoredTokens) -> Result<()> {
+        write_encrypted(&self.path, &self.key, tokens)
+    }
+
+    /// Store a refreshed token response. eBay does not always return a new
+    /// refresh token, so the previous one is kept when it is missing.
+    pub fn rotate(
+        &self,
+        response: &TokenResponse,
+        environment: Environment,
+    ) -> Result<StoredTokens> {
+        let previous = self.load()?;
+        let mut tokens = StoredTokens::from_response(response, environment, unix_now());
+
+        if let Some(previous) = previous {
+            if tokens.refresh_token.is_none() {
+                tokens.refresh_token = previous.refresh_token;
+                tokens.refresh_token_expires_at = previous.refresh_token_expires_at;
+            }
+            if tokens.scopes.is_empty() {
+                tokens.scopes = previous.scopes;
+            }
+        }
+
+        self.save(&tokens)?;
+        Ok(tokens)
+    }
+
+    /// Re-encrypt the stored tokens under a new key.
+    pub fn rekey(&mut self, key: VaultKey) -> Result<()> {
+        let tokens = self.load()?;
+        self.key = key;
+        match tokens {
+            Some(tokens) => self.save(&tokens),
+            None => Ok(()),
+        }
+    }
+
+    /// Forget the stored tokens. The user must re-authorize afterwards.
+    pub fn revoke(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Read and decrypt a value written by [`write_encrypted`]. Returns `None`
+/// if the file does not exist.
+pub(crate) fn read_encrypted<T: DeserializeOwned>(
+    path: &Path,
+    key: &VaultKey,
+) -> Result<Option<T>> {
+    let raw = match fs::read(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let envelope: Envelope = serde_json::from_slice(&raw)?;
+    if envelope.version != FORMAT_VERSION {
+        return Err(Error::Vault(format!(
+            "unsupported vault version {}",
+            envelope.version
+        )));
+    }
+
+    let salt = decode(&envelope.salt)?;
+    let nonce = decode(&envelope.nonce)?;
+    let ciphertext = decode(&envelope.ciphertext)?;
+    if nonce.len() != NONCE_LEN {
+        return Err(Error::Vault("vault nonce has the wrong length".to_string()));
+    }
+
+    let cipher = XChaCha20Poly1305::new(&key.derive(&salt)?.into());
+    let plaintext = cipher
+        .decrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: ASSOCIATED_DATA,
+            },
+        )
+        .map_err(|_| Error::Vault("wrong key or corrupted vault".to_string()))?;
+
+    Ok(Some(serde_json::from_slice(&plaintext)?))
+}
+
+/// Serialize `value`, encrypt it under `key` and atomically write it to `path`.
+pub(crate) fn write_encrypted<T: Serialize>(path: &Path, key: &VaultKey, value: &T) -> Result<()> {
+    let mut rng = rand::rng();
+    let salt: [u8; SALT_LEN] = rng.random();
+    let nonce: [u8; NONCE_LEN] = rng.random();
+
+    let plaintext = serde_json::to_vec(value)?;
+    let cipher = XChaCha20Poly1305::new(&key.derive(&salt)?.into());
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &plaintext,
+                aad: ASSOCIATED_DATA,
+            },
+        )
+        .map_err(|_| Error::Vault("encryption failed".to_string()))?;
+
+    let envelope = Envelope {
+        version: FORMAT_VERSION,
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    };
+
+    write_atomic(path, &serde_json::to_vec_pretty(&envelope)?)
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(value)
+        .map_err(|err| Error::Vault(format!("corrupted vault: {err}")))
+}
+
+/// Write to a sibling temp file, flush it to disk, then rename over `path`.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
@@ -0,0 +1,4 @@
+//! Authentication logic for the separate APIs: anything related to handling
+//! tokens lives here.
+
+pub mod ebay;
@@ -0,0 +1,64 @@
+//! eBay environments (sandbox or production) and their hosts.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    #[default]
+    Sandbox,
+    Production,
+}
+
+impl Environment {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Environment::Sandbox => "sandbox",
+            Environment::Production => "production",
+        }
+    }
+
+    /// Host serving the REST and Trading APIs.
+    pub fn api_host(self) -> &'static str {
+        match self {
+            Environment::Sandbox => "api.sandbox.ebay.com",
+            Environment::Production => "api.ebay.com",
+        }
+    }
+
+    /// Base URL for API calls, e.g. `https://api.ebay.com/`.
+    pub fn api_base_url(self) -> Url {
+        Url::parse(&format!("https://{}/", self.api_host())).expect("static URL")
+    }
+
+    /// Consent page the user is sent to during the authorization-code flow.
+    pub fn auth_url(self) -> Url {
+        let url = match self {
+            Environment::Sandbox => "https://auth.sandbox.ebay.com/oauth2/authorize",
+            Environment::Production => "https://auth.ebay.com/oauth2/authorize",
+        };
+        Url::parse(url).expect("static URL")
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Environment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sandbox" => Ok(Environment::Sandbox),
+            "production" => Ok(Environment::Production),
+            other => Err(format!("unknown eBay environment: {other}")),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Error type shared by every module in the backend.
+
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("invalid URL: {0}")]
+    Url(#[from] url::ParseError),
+
+    #[error("XML error: {0}")]
+    Xml(String),
+
+    /// Reading or writing an XLSX workbook failed.
+    #[error("spreadsheet error: {0}")]
+    Spreadsheet(String),
+
+    /// Building or writing a Parquet file failed, or a value did not fit the
+    /// file's schema.
+    #[error("Parquet error: {0}")]
+    Parquet(String),
+
+    /// An export profile is malformed.
+    #[error("export profile error: {0}")]
+    Profile(String),
+
+    /// A promotion renewal rule is malformed.
+    #[error("renewal rule error: {0}")]
+    Rule(String),
+
+    /// eBay does not support the operation for this kind of promotion.
+    #[error("promotion error: {0}")]
+    Promotion(String),
+
+    /// A promotion plan cannot be applied as made.
+    #[error("promotion plan error: {0}")]
+    Plan(String),
+
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    /// A REST or XML API answered with a non-success HTTP status.
+    #[error("HTTP {status}: {message}")]
+    Api {
+        status: u16,
+        message: String,
+        /// The response's `Retry-After`, if it had one.
+        retry_after: Option<std::time::Duration>,
+    },
+
+    /// `Ack` was Failure on a Trading API call.
+    #[error("{0}")]
+    Trading(crate::internal::ebay::trading::TradingErrors),
+
+    /// The identity endpoint rejected a token request (e.g. `invalid_grant`).
+    #[error("OAuth error (HTTP {status}): {error}: {description}")]
+    OAuth {
+        status: u16,
+        error: String,
+        description: String,
+    },
+
+    /// The user declined consent, or eBay redirected back with an error.
+    #[error("authorization was declined: {0}")]
+    AuthorizationDenied(String),
+
+    /// The `state` returned on the redirect did not match the one we sent.
+    #[error("OAuth state mismatch")]
+    StateMismatch,
+
+    /// No usable tokens: the account has to go through consent again.
+    #[error("not authorized: {0}")]
+    NotAuthorized(String),
+
+    #[error("timed out waiting for {0}")]
+    Timeout(&'static str),
+
+    #[error("invalid response: {0}")]
+    InvalidResponse(String),
+
+    /// A stored record asked for by ID (a sync, say) does not exist.
+    #[error("not found: {0}")]
+    NotFound(String),
+
+    #[error("account error: {0}")]
+    Account(String),
+
+    #[error("token vault error: {0}")]
+    Vault(String),
+
+    /// A daily call budget is down to its reserve and the policy is to refuse.
+    #[error("call limit reached: {0}")]
+    QuotaExhausted(String),
+
+    /// An endpoint kept failing and its circuit breaker is open.
+    #[error("service unavailable: {0}")]
+    CircuitOpen(String),
+}
+
+impl From<quick_xml::DeError> for Error {
+    fn from(err: quick_xml::DeError) -> Self {
+        Error::Xml(err.to_string())
+    }
+}
+
+impl From<quick_xml::SeError> for Error {
+    fn from(err: quick_xml::SeError) -> Self {
+        Error::Xml(err.to_string())
+    }
+}
+
+impl From<rust_xlsxwriter::XlsxError> for Error {
+    fn from(err: rust_xlsxwriter::XlsxError) -> Self {
+        Error::Spreadsheet(err.to_string())
+    }
+}
+
+impl From<calamine::XlsxError> for Error {
+    fn from(err: calamine::XlsxError) -> Self {
+        Error::Spreadsheet(err.to_string())
+    }
+}
+
+impl From<parquet::errors::ParquetError> for Error {
+    fn from(err: parquet::errors::ParquetError) -> Self {
+        Error::Parquet(err.to_string())
+    }
+}
+
+impl From<arrow_schema::ArrowError> for Error {
+    fn from(err: arrow_schema::ArrowError) -> Self {
+        Error::Parquet(err.to_string())
+    }
+}
@@ -0,0 +1,451 @@
+//! Named column profiles for the listings export.
+//!
+//! `processSellerListings` always emits every exporter column. A profile picks
+//! which of them go into the workbook, in what order and under what header,
+//! and can add columns derived from others (price plus shipping, sell-through
+//! and so on). Profiles live in a JSON file:
+//!
+//! ```json
+//! {
+//!   "profiles": [
+//!     {
+//!       "name": "pricing review",
+//!       "columns": [
+//!         { "column": "Item ID" },
+//!         { "column": "Current Price", "label": "Price" },
+//!         {
+//!           "label": "Price + Shipping",
+//!           "derive": { "op": "sum", "columns": ["Current Price", "Shipping Cost"] }
+//!         }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! [`ProfileSet::load`] starts from the built-in profiles ("pricing review"
+//! and "inventory audit"); a profile in the file with the same name replaces
+//! the built-in one.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use rust_xlsxwriter::Workbook;
+use serde::{Deserialize, Serialize};
+
+use super::listing_rows::{self, Cell, ColumnKind, ListingRow};
+use super::workbook::{save_export, shaped_workbook, Column, ExportResult};
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProfile {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub columns: Vec<ProfileColumn>,
+    /// Columns kept in view when scrolling sideways.
+    #[serde(default = "default_frozen_columns")]
+    pub frozen_columns: u16,
+}
+
+fn default_frozen_columns() -> u16 {
+    1
+}
+
+/// One workbook column: either an exporter column (`column`) or a value
+/// derived from several (`derive`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileColumn {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    /// Header text; defaults to the column name. Required for derived
+    /// columns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derive: Option<Derivation>,
+    /// How values are typed and formatted; defaults to the source column's
+    /// kind, or what the derivation produces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ColumnKind>,
+}
+
+impl ProfileColumn {
+    pub fn source(column: impl Into<String>) -> Self {
+        Self {
+            column: Some(column.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn derived(label: impl Into<String>, derive: Derivation) -> Self {
+        Self {
+            label: Some(label.into()),
+            derive: Some(derive),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_kind(mut self, kind: ColumnKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// The header this column is written under.
+    pub fn header(&self) -> &str {
+        self.label
+            .as_deref()
+            .or(self.column.as_deref())
+            .unwrap_or_default()
+    }
+
+    fn resolved_kind(&self) -> ColumnKind {
+        if let Some(kind) = self.kind {
+            return kind;
+        }
+        match (&self.column, &self.derive) {
+            (Some(name), _) => source_kind(name),
+            (None, Some(derive)) => derive.kind(),
+            (None, None) => ColumnKind::Text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeriveOp {
+    /// Sum of the non-empty values.
+    Sum,
+    /// First minus second.
+    Difference,
+    Product,
+    /// First divided by second.
+    Ratio,
+    /// First as a percentage of second.
+    Percent,
+    /// Non-empty values joined by `separator`.
+    Concat,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Derivation {
+    pub op: DeriveOp,
+    pub columns: Vec<String>,
+    /// For `concat`; a space by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+}
+
+impl Derivation {
+    pub fn new(op: DeriveOp, columns: &[&str]) -> Self {
+        Self {
+            op,
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            separator: None,
+        }
+    }
+
+    /// Sums and differences of prices are prices; other arithmetic is a plain
+    /// decimal.
+    fn kind(&self) -> ColumnKind {
+        match self.op {
+            DeriveOp::Concat => ColumnKind::Text,
+            DeriveOp::Sum | DeriveOp::Difference
+                if self
+                    .columns
+                    .iter()
+                    .all(|c| source_kind(c) == ColumnKind::Price) =>
+            {
+                ColumnKind::Price
+            }
+            DeriveOp::Sum | DeriveOp::Difference | DeriveOp::Product
+                if self
+                    .columns
+                    .iter()
+                    .all(|c| source_kind(c) == ColumnKind::Integer) =>
+            {
+                ColumnKind::Integer
+            }
+            _ => ColumnKind::Decimal,
+        }
+    }
+
+    fn apply(&self, row: &ListingRow) -> Cell {
+        let values: Vec<Option<f64>> = self
+            .columns
+            .iter()
+            .map(|c| source_kind(c).coerce(row.get(c).clone()).as_f64())
+            .collect();
+        let all = || values.iter().copied().collect::<Option<Vec<f64>>>();
+        let value = match self.op {
+            DeriveOp::Concat => return self.concat(row),
+            DeriveOp::Sum => values.iter().flatten().copied().reduce(|a, b| a + b),
+            DeriveOp::Product => all().map(|v| v.into_iter().product()),
+            DeriveOp::Difference => all().map(|v| v[0] - v[1]),
+            DeriveOp::Ratio => all().filter(|v| v[1] != 0.0).map(|v| v[0] / v[1]),
+            DeriveOp::Percent => all().filter(|v| v[1] != 0.0).map(|v| v[0] / v[1] * 100.0),
+        };
+        value.map_or(Cell::Empty, Cell::Number)
+    }
+
+    fn concat(&self, row: &ListingRow) -> Cell {
+        let separator = self.separator.as_deref().unwrap_or(" ");
+        let parts: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| row.get(c))
+            .filter(|cell| !cell.is_empty())
+            .map(Cell::to_string)
+            .collect();
+        Cell::Text(parts.join(separator))
+    }
+}
+
+fn source_kind(name: &str) -> ColumnKind {
+    listing_rows::column(name).map_or(ColumnKind::Text, |spec| spec.kind)
+}
+
+impl ExportProfile {
+    pub fn new(name: impl Into<String>, columns: Vec<ProfileColumn>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            columns,
+            frozen_columns: default_frozen_columns(),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Check the profile can be applied: every column has a source or a
+    /// derivation (not both), every column it reads is an exporter column or
+    /// an item specific (`Specific: …`), headers are unique, and each
+    /// derivation has as many inputs as its operation takes.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(Error::Profile(format!("{}: {message}", self.name)));
+        if self.columns.is_empty() {
+            return invalid("no columns".to_string());
+        }
+
+        let mut headers = HashSet::new();
+        for (index, column) in self.columns.iter().enumerate() {
+            let position = index + 1;
+            match (&column.column, &column.derive) {
+                (Some(_), Some(_)) => {
+                    return invalid(format!("column {position} has both column and derive"));
+                }
+                (None, None) => {
+                    return invalid(format!("column {position} has neither column nor derive"));
+                }
+                (None, Some(_)) if column.label.is_none() => {
+                    return invalid(format!("derived column {position} needs a label"));
+                }
+                (None, Some(derive)) => {
+                    let arity_ok = match derive.op {
+                        DeriveOp::Difference | DeriveOp::Ratio | DeriveOp::Percent => {
+                            derive.columns.len() == 2
+                        }
+                        DeriveOp::Sum | DeriveOp::Product | DeriveOp::Concat => {
+                            !derive.columns.is_empty()
+                        }
+                    };
+                    if !arity_ok {
+                        return invalid(format!(
+                            "{:?}: {:?} cannot take {} column(s)",
+                            column.header(),
+                            derive.op,
+                            derive.columns.len()
+                        ));
+                    }
+                }
+                (Some(_), None) => {}
+            }
+            let unknown = column
+                .column
+                .iter()
+                .chain(column.derive.iter().flat_map(|derive| &derive.columns))
+                .find(|name| {
+                    listing_rows::column(name).is_none()
+                        && listing_rows::specific_name(name).is_none()
+                });
+            if let Some(name) = unknown {
+                return invalid(format!("column {position} reads unknown column {name:?}"));
+            }
+            if column.header().is_empty() {
+                return invalid(format!("column {position} has an empty header"));
+            }
+            if !headers.insert(column.header()) {
+                return invalid(format!("duplicate header {:?}", column.header()));
+            }
+        }
+        Ok(())
+    }
+
+    /// The workbook columns, under their profile headers.
+    pub fn columns(&self) -> Vec<Column> {
+        self.columns
+            .iter()
+            .map(|column| Column::new(column.header(), column.resolved_kind()))
+            .collect()
+    }
+
+    /// `row` reshaped to the profile: keyed by header, derived columns
+    /// filled in. The source "Currency" is carried along (unless a column is
+    /// headed "Currency") so prices keep their currency format.
+    pub fn apply(&self, row: &ListingRow) -> ListingRow {
+        let mut out = ListingRow::default();
+        for column in &self.columns {
+            let kind = column.resolved_kind();
+            let value = match (&column.column, &column.derive) {
+                (Some(name), _) => row.get(name).clone(),
+                (None, Some(derive)) => derive.apply(row),
+                (None, None) => Cell::Empty,
+            };
+            let value = match kind.coerce(value) {
+                Cell::Number(n) if kind == ColumnKind::Integer && n.fract() == 0.0 => {
+                    Cell::Integer(n as i64)
+                }
+                value => value,
+            };
+            out.set(column.header(), value);
+        }
+        if !self.columns.iter().any(|c| c.header() == "Currency") {
+            out.set("Currency", row.get("Currency").clone());
+        }
+        out
+    }
+
+    /// `rows` reshaped to the profile, on the "All Listings" sheet, with the
+    /// usual "Summary" (computed from the full rows).
+    pub fn workbook(&self, rows: &[ListingRow]) -> Result<Workbook> {
+        self.validate()?;
+        let shaped: Vec<ListingRow> = rows.iter().map(|row| self.apply(row)).collect();
+        shaped_workbook(rows, &shaped, &self.columns(), self.frozen_columns)
+    }
+
+    /// Write the profile's workbook to `path`.
+    pub fn export(&self, rows: &[ListingRow], path: impl AsRef<Path>) -> Result<ExportResult> {
+        let path = path.as_ref();
+        log::info!(
+            "Creating Excel file with profile {:?}: {}",
+            self.name,
+            path.display()
+        );
+        save_export(self.workbook(rows)?, path, rows.len())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfileSet {
+    pub profiles: Vec<ExportProfile>,
+}
+
+impl ProfileSet {
+    /// The profiles that ship with the app.
+    pub fn builtin() -> Self {
+        use DeriveOp::*;
+        Self {
+            profiles: vec![
+                ExportProfile::new(
+                    "pricing review",
+                    vec![
+                        ProfileColumn::source("Item ID"),
+                        ProfileColumn::source("SKU"),
+                        ProfileColumn::source("Title"),
+                        ProfileColumn::source("Current Price").with_label("Price"),
+                        ProfileColumn::source("Shipping Cost").with_label("Shipping"),
+                        ProfileColumn::derived(
+                            "Price + Shipping",
+                            Derivation::new(Sum, &["Current Price", "Shipping Cost"]),
+                        ),
+                        ProfileColumn::source("Best Offer Enabled").with_label("Best Offer"),
+                        ProfileColumn::source("Auto Accept Price"),
+                        ProfileColumn::source("Min Accept Price"),
+                        ProfileColumn::source("Watch Count").with_label("Watchers"),
+                        ProfileColumn::source("Hit Count").with_label("Views"),
+                        ProfileColumn::derived(
+                            "Watchers per 100 Views",
+                            Derivation::new(Percent, &["Watch Count", "Hit Count"]),
+                        ),
+                        ProfileColumn::source("Quantity Sold").with_label("Sold"),
+                        ProfileColumn::source("Time Left (Days)"),
+                    ],
+                )
+                .with_description("Prices, offers and demand per listing"),
+                ExportProfile::new(
+                    "inventory audit",
+                    vec![
+                        ProfileColumn::source("Item ID"),
+                        ProfileColumn::source("SKU"),
+                        ProfileColumn::source("Title"),
+                        ProfileColumn::source("Listing Status Category").with_label("Status"),
+                        ProfileColumn::source("Quantity"),
+                        ProfileColumn::source("Quantity Sold").with_label("Sold"),
+                        ProfileColumn::source("Quantity Available").with_label("Available"),
+                        ProfileColumn::derived(
+                            "Sell-Through %",
+                            Derivation::new(Percent, &["Quantity Sold", "Quantity"]),
+                        ),
+                        ProfileColumn::source("Condition Name").with_label("Condition"),
+                        ProfileColumn::source("Location"),
+                        ProfileColumn::source("Start Time"),
+                        ProfileColumn::source("End Time"),
+                    ],
+                )
+                .with_description("Stock levels and sell-through per listing"),
+            ],
+        }
+    }
+
+    /// Profiles from a JSON file, validated.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let set: Self = serde_json::from_str(json)?;
+        set.profiles.iter().try_for_each(ExportProfile::validate)?;
+        Ok(set)
+    }
+
+    /// The built-in profiles overridden and extended by those in `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let mut set = Self::builtin();
+        set.merge(Self::from_json(&fs::read_to_string(path)?)?);
+        Ok(set)
+    }
+
+    /// Add `other`'s profiles, replacing any with the same name (ignoring
+    /// case).
+    pub fn merge(&mut self, other: ProfileSet) {
+        for profile in other.profiles {
+            match self
+                .profiles
+                .iter_mut()
+                .find(|p| p.name.eq_ignore_ascii_case(&profile.name))
+            {
+                Some(existing) => *existing = profile,
+                None => self.profiles.push(profile),
+            }
+        }
+    }
+
+    /// Look a profile up by name, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&ExportProfile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.iter().map(|profile| profile.name.as_str())
+    }
+}
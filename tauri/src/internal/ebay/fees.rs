@@ -0,0 +1,472 @@
+//! Fees per listing from the seller's account activity.
+//!
+//! `calculateTotalFees` added up the `ListingFee` and `FinalValueFee` on each
+//! item, and eBay mostly leaves the final value fee out of listing responses.
+//! `GetAccount` lists every fee and credit actually charged, most of them with
+//! the Item ID they belong to. This pulls those entries for a date range,
+//! sorts them into insertion, final value, promoted-listing, international and
+//! other fees, and totals them per listing and per day, week or month.
+//!
+//! Credits (`Credit…` entry types, e.g. a final value fee refunded after a
+//! cancelled order) count against the fee they reverse. Payments the seller
+//! made to eBay are left out.
+
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::listing_rows::{Cell, ListingRow};
+use super::trading::calls::{AccountEntry, AccountHistorySelection};
+use super::trading::types::Pagination;
+use super::trading::{GetAccount, TradingClient};
+use crate::error::Result;
+
+/// Largest `EntriesPerPage` `GetAccount` accepts.
+pub const MAX_ENTRIES_PER_PAGE: u32 = 2000;
+
+/// Range covered by one `BetweenSpecifiedDates` request.
+pub const DEFAULT_WINDOW_DAYS: u64 = 30;
+
+/// `AccountDetailEntryCodeType` values that are not `Other`, with the credits
+/// that reverse them.
+pub const ENTRY_KINDS: &[(&str, FeeKind)] = &[
+    ("FeeInsertion", FeeKind::Insertion),
+    ("CreditInsertion", FeeKind::Insertion),
+    ("FeeFinalValue", FeeKind::FinalValue),
+    ("CreditFinalValue", FeeKind::FinalValue),
+    ("FeeFinalValueShipping", FeeKind::FinalValue),
+    ("CreditFinalValueShipping", FeeKind::FinalValue),
+    ("PromotedListingFee", FeeKind::PromotedListing),
+    ("CreditPromotedListingFee", FeeKind::PromotedListing),
+    ("FeeInternationalListing", FeeKind::International),
+    ("CreditInternationalListing", FeeKind::International),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FeeKind {
+    Insertion,
+    FinalValue,
+    PromotedListing,
+    International,
+    Other,
+}
+
+impl FeeKind {
+    pub const ALL: [FeeKind; 5] = [
+        FeeKind::Insertion,
+        FeeKind::FinalValue,
+        FeeKind::PromotedListing,
+        FeeKind::International,
+        FeeKind::Other,
+    ];
+
+    /// Sort an `AccountDetailEntryCodeType` (`FeeInsertion`,
+    /// `FeeFinalValueShipping`, `CreditFinalValue`, …) into a kind. Codes
+    /// missing from [`ENTRY_KINDS`] are `Other`.
+    pub fn classify(entry_type: &str) -> FeeKind {
+        ENTRY_KINDS
+            .iter()
+            .find(|(code, _)| *code == entry_type)
+            .map_or(FeeKind::Other, |(_, kind)| *kind)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FeeKind::Insertion => "Insertion Fee",
+            FeeKind::FinalValue => "Final Value Fee",
+            FeeKind::PromotedListing => "Promoted Listing Fee",
+            FeeKind::International => "International Fee",
+            FeeKind::Other => "Other Fees",
+        }
+    }
+}
+
+/// One fee or credit. `amount` is negative for credits.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeEntry {
+    pub date: DateTime<Utc>,
+    /// Empty for account-level charges such as store subscriptions.
+    pub item_id: Option<String>,
+    pub title: Option<String>,
+    pub kind: FeeKind,
+    /// The raw `AccountDetailsEntryType`.
+    pub entry_type: String,
+    pub description: Option<String>,
+    pub amount: f64,
+    pub currency: String,
+    pub ref_number: Option<String>,
+}
+
+impl FeeEntry {
+    /// The fee in `entry`, or `None` for payments and undated entries.
+    /// `account_currency` applies when the amount has no `currencyID`.
+    pub fn from_account_entry(entry: &AccountEntry, account_currency: &str) -> Option<Self> {
+        let entry_type = entry.account_details_entry_type.clone().unwrap_or_default();
+        if entry_type.to_ascii_lowercase().contains("payment") {
+            return None;
+        }
+        let amount = entry
+            .net_detail_amount
+            .as_ref()
+            .or(entry.gross_detail_amount.as_ref())?;
+        // Credits are reported with either sign; normalise them to negative.
+        let value = if entry_type.starts_with("Credit") {
+            -amount.value.abs()
+        } else {
+            amount.value
+        };
+
+        Some(Self {
+            date: entry.date?,
+            item_id: entry
+                .item_id
+                .clone()
+                .filter(|id| !id.is_empty() && id != "0"),
+            title: entry.title.clone(),
+            kind: FeeKind::classify(&entry_type),
+            entry_type,
+            description: entry.description.clone(),
+            amount: value,
+            currency: amount
+                .currency_id
+                .clone()
+                .unwrap_or_else(|| account_currency.to_string()),
+            ref_number: entry.ref_number.clone(),
+        })
+    }
+}
+
+/// Amounts by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeBreakdown {
+    pub insertion: f64,
+    pub final_value: f64,
+    pub promoted_listing: f64,
+    pub international: f64,
+    pub other: f64,
+    pub total: f64,
+}
+
+impl FeeBreakdown {
+    pub fn get(&self, kind: FeeKind) -> f64 {
+        match kind {
+            FeeKind::Insertion => self.insertion,
+            FeeKind::FinalValue => self.final_value,
+            FeeKind::PromotedListing => self.promoted_listing,
+            FeeKind::International => self.international,
+            FeeKind::Other => self.other,
+        }
+    }
+
+    fn add(&mut self, kind: FeeKind, amount: f64) {
+        let slot = match kind {
+            FeeKind::Insertion => &mut self.insertion,
+            FeeKind::FinalValue => &mut self.final_value,
+            FeeKind::PromotedListing => &mut self.promoted_listing,
+            FeeKind::International => &mut self.international,
+            FeeKind::Other => &mut self.other,
+        };
+        *slot = round2(*slot + amount);
+        self.total = round2(self.total + amount);
+    }
+}
+
+/// Fees charged for one listing, in one currency.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemFees {
+    pub item_id: String,
+    pub title: Option<String>,
+    pub sku: Option<String>,
+    pub currency: String,
+    #[serde(flatten)]
+    pub fees: FeeBreakdown,
+    pub entries: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Period {
+    Day,
+    /// ISO weeks, starting on Monday.
+    Week,
+    Month,
+}
+
+impl Period {
+    /// First day of the period `date` falls in.
+    pub fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Days::new(date.weekday().num_days_from_monday().into()),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// `2024-03-05`, `2024-W10` or `2024-03`.
+    pub fn label(self, start: NaiveDate) -> String {
+        match self {
+            Period::Day => start.format("%Y-%m-%d").to_string(),
+            Period::Week => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// Fees charged in one period, in one currency.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodFees {
+    pub label: String,
+    pub start: NaiveDate,
+    pub currency: String,
+    #[serde(flatten)]
+    pub fees: FeeBreakdown,
+    /// Part of `fees.total` not tied to a listing.
+    pub account_level: f64,
+}
+
+/// Fee entries for a date range, with per-listing and per-period views.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeLedger {
+    pub entries: Vec<FeeEntry>,
+}
+
+impl FeeLedger {
+    pub fn new(entries: Vec<FeeEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Fees per listing, largest total first. Titles and SKUs come from
+    /// `listings` when the listing is there, otherwise from the entries.
+    pub fn by_item(&self, listings: &[ListingRow]) -> Vec<ItemFees> {
+        let rows: BTreeMap<&str, &ListingRow> =
+            listings.iter().map(|row| (row.item_id(), row)).collect();
+        let text = |row: Option<&&ListingRow>, column: &str| {
+            row.and_then(|row| row.get(column).as_str())
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+
+        let mut items: BTreeMap<(&str, &str), ItemFees> = BTreeMap::new();
+        for entry in &self.entries {
+            let Some(item_id) = entry.item_id.as_deref() else {
+                continue;
+            };
+            let fees = items
+                .entry((item_id, entry.currency.as_str()))
+                .or_insert_with(|| {
+                    let row = rows.get(item_id);
+                    ItemFees {
+                        item_id: item_id.to_string(),
+                        title: text(row, "Title").or_else(|| entry.title.clone()),
+                        sku: text(row, "SKU"),
+                        currency: entry.currency.clone(),
+                        fees: FeeBreakdown::default(),
+                        entries: 0,
+                    }
+                });
+            fees.fees.add(entry.kind, entry.amount);
+            fees.entries += 1;
+        }
+
+        let mut items: Vec<ItemFees> = items.into_values().collect();
+        items.sort_by(|a, b| b.fees.total.total_cmp(&a.fees.total));
+        items
+    }
+
+    /// Totals per period and currency, oldest first.
+    pub fn by_period(&self, period: Period) -> Vec<PeriodFees> {
+        let mut periods: BTreeMap<(NaiveDate, &str), PeriodFees> = BTreeMap::new();
+        for entry in &self.entries {
+            let start = period.start_of(entry.date.date_naive());
+            let totals = periods
+                .entry((start, entry.currency.as_str()))
+                .or_insert_with(|| PeriodFees {
+                    label: period.label(start),
+                    start,
+                    currency: entry.currency.clone(),
+                    fees: FeeBreakdown::default(),
+                    account_level: 0.0,
+                });
+            totals.fees.add(entry.kind, entry.amount);
+            if entry.item_id.is_none() {
+                totals.account_level = round2(totals.account_level + entry.amount);
+            }
+        }
+        periods.into_values().collect()
+    }
+
+    /// Put the charged fees into the exporter's fee columns: insertion fees
+    /// as "Listing Fee" and final value fees as "Final Value Fee", each only
+    /// when the ledger has entries of that kind, so the item's own figure
+    /// stays otherwise. "Total Fees" is those two columns plus every other
+    /// kind charged. Listings charged in more than one currency are left as
+    /// they are, since their fees cannot be added up; their Item IDs are
+    /// returned.
+    pub fn apply(&self, rows: &mut [ListingRow]) -> Vec<String> {
+        let mut by_item: BTreeMap<&str, BTreeMap<&str, (FeeBreakdown, Vec<FeeKind>)>> =
+            BTreeMap::new();
+        for entry in &self.entries {
+            let Some(item_id) = entry.item_id.as_deref() else {
+                continue;
+            };
+            let (fees, kinds) = by_item
+                .entry(item_id)
+                .or_default()
+                .entry(entry.currency.as_str())
+                .or_default();
+            fees.add(entry.kind, entry.amount);
+            if !kinds.contains(&entry.kind) {
+                kinds.push(entry.kind);
+            }
+        }
+
+        let mut mixed = Vec::new();
+        for row in rows {
+            let Some(currencies) = by_item.get(row.item_id()) else {
+                continue;
+            };
+            let [(_, (fees, kinds))] = currencies.iter().collect::<Vec<_>>()[..] else {
+                log::warn!(
+                    "Item {} was charged fees in {} currencies; keeping its listed fees",
+                    row.item_id(),
+                    currencies.len()
+                );
+                mixed.push(row.item_id().to_string());
+                continue;
+            };
+            if kinds.contains(&FeeKind::Insertion) {
+                row.set("Listing Fee", Cell::Number(fees.insertion));
+            }
+            if kinds.contains(&FeeKind::FinalValue) {
+                row.set("Final Value Fee", Cell::Number(fees.final_value));
+            }
+            let listed = |column: &str| row.get(column).as_f64().unwrap_or(0.0);
+            let total = listed("Listing Fee")
+                + listed("Final Value Fee")
+                + fees.promoted_listing
+                + fees.international
+                + fees.other;
+            row.set("Total Fees", Cell::Number(round2(total)));
+        }
+        mixed
+    }
+}
+
+pub struct FeeFetcher<'a> {
+    client: &'a TradingClient,
+    entries_per_page: u32,
+    window: chrono::Duration,
+    page_delay: Duration,
+}
+
+impl<'a> FeeFetcher<'a> {
+    pub fn new(client: &'a TradingClient) -> Self {
+        Self {
+            client,
+            entries_per_page: MAX_ENTRIES_PER_PAGE,
+            window: chrono::Duration::days(DEFAULT_WINDOW_DAYS as i64),
+            page_delay: Duration::from_millis(500),
+        }
+    }
+
+    pub fn with_entries_per_page(mut self, entries_per_page: u32) -> Self {
+        self.entries_per_page = entries_per_page.clamp(1, MAX_ENTRIES_PER_PAGE);
+        self
+    }
+
+    /// Range covered by each request (at least a day).
+    pub fn with_window(mut self, window: chrono::Duration) -> Self {
+        self.window = window.max(chrono::Duration::days(1));
+        self
+    }
+
+    pub fn with_page_delay(mut self, delay: Duration) -> Self {
+        self.page_delay = delay;
+        self
+    }
+
+    /// Every fee and credit posted from `from` to `to`.
+    pub async fn fetch(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<FeeLedger> {
+        log::info!("Fetching account fees from {from} to {to}...");
+        let mut entries = Vec::new();
+        let mut start = from;
+        while start < to {
+            let end = (start + self.window).min(to);
+            entries.extend(self.fetch_window(start, end).await?);
+            start = end;
+        }
+        // Both windows include their shared boundary, so an entry posted
+        // exactly on it comes back twice.
+        let mut seen = HashSet::new();
+        entries.retain(|entry: &FeeEntry| {
+            seen.insert((
+                entry.ref_number.clone(),
+                entry.entry_type.clone(),
+                entry.date,
+                entry.amount.to_bits(),
+                entry.item_id.clone(),
+            ))
+        });
+        entries.sort_by_key(|entry| entry.date);
+        log::info!("Found {} fee entries", entries.len());
+        Ok(FeeLedger::new(entries))
+    }
+
+    async fn fetch_window(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<FeeEntry>> {
+        let mut entries = Vec::new();
+        let mut page_number = 1;
+        loop {
+            let response = self
+                .client
+                .execute(&GetAccount {
+                    account_history_selection: Some(AccountHistorySelection::BetweenSpecifiedDates),
+                    begin_date: Some(from),
+                    end_date: Some(to),
+                    exclude_balance: Some(true),
+                    exclude_summary: Some(true),
+                    pagination: Some(Pagination {
+                        entries_per_page: self.entries_per_page,
+                        page_number,
+                    }),
+                    ..GetAccount::default()
+                })
+                .await?;
+            let currency = response.currency.clone().unwrap_or_default();
+            let total_pages = response
+                .pagination_result
+                .as_ref()
+                .map_or(1, |p| p.total_number_of_pages);
+            let page = response.into_entries();
+            let found = page.len();
+            entries.extend(
+                page.iter()
+                    .filter_map(|entry| FeeEntry::from_account_entry(entry, &currency)),
+            );
+
+            log::info!(
+                "Account entries {from} to {to}, page {page_number}/{total_pages}: {found} entries"
+            );
+            if found == 0 || page_number >= total_pages {
+                break;
+            }
+            page_number += 1;
+            tokio::time::sleep(self.page_delay).await;
+        }
+        Ok(entries)
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
@@ -0,0 +1,270 @@
+//! Change report between two listing snapshots.
+//!
+//! Either side can be a stored sync from the [`Warehouse`] or an exporter
+//! workbook (JS or Rust). Listings are matched by Item ID and every column is
+//! compared after coercing both values to the column's type, so a string
+//! "12.50" from an old JS export equals the number 12.5 from a sync.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+use serde::Serialize;
+
+use super::listing_rows::{self, Cell, ListingRow, LISTING_COLUMNS};
+use super::warehouse::Warehouse;
+use super::workbook::{self, write_cell};
+use crate::adapters::ebay::vault::write_atomic;
+use crate::error::{Error, Result};
+
+pub const DIFF_SHEET: &str = "Changes";
+
+/// Columns that change on every sync without anyone touching the listing.
+/// Not ignored by default; pass them to [`DiffOptions::ignore_columns`] to
+/// leave them out.
+pub const VOLATILE_COLUMNS: &[&str] = &["Time Left", "Time Left (Days)"];
+
+/// One side of a diff.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub label: String,
+    pub rows: Vec<ListingRow>,
+}
+
+impl Snapshot {
+    /// The catalog of `account` as it stood after `sync_id` (see
+    /// [`Warehouse::snapshot_at`]), so a listing a later full sync no longer
+    /// saw shows up as removed.
+    pub fn from_sync(warehouse: &Warehouse, account: &str, sync_id: i64) -> Result<Self> {
+        let sync = warehouse
+            .syncs(account)?
+            .into_iter()
+            .find(|sync| sync.id == sync_id)
+            .ok_or_else(|| Error::NotFound(format!("{account} has no sync {sync_id}")))?;
+        Ok(Self {
+            label: format!(
+                "sync {} ({})",
+                sync.id,
+                sync.started_at.format("%Y-%m-%d %H:%M")
+            ),
+            rows: warehouse.snapshot_at(account, sync_id)?,
+        })
+    }
+
+    /// The "All Listings" sheet of an exporter workbook.
+    pub fn from_xlsx(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        Ok(Self {
+            label: path.file_name().map_or_else(
+                || path.display().to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            ),
+            rows: workbook::read_listings(path)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Columns left out of the comparison.
+    pub ignore_columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub column: String,
+    pub before: Cell,
+    pub after: Cell,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedListing {
+    pub item_id: String,
+    /// Title in the newer snapshot.
+    pub title: String,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffReport {
+    pub from: String,
+    pub to: String,
+    pub generated_at: DateTime<Utc>,
+    pub summary: DiffSummary,
+    /// Listings only in the newer snapshot.
+    pub added: Vec<ListingRow>,
+    /// Listings only in the older snapshot.
+    pub removed: Vec<ListingRow>,
+    pub changed: Vec<ChangedListing>,
+}
+
+/// Compare `from` (older) with `to` (newer). Results are ordered by Item ID.
+pub fn diff(from: &Snapshot, to: &Snapshot, options: &DiffOptions) -> DiffReport {
+    let before: HashMap<&str, &ListingRow> =
+        from.rows.iter().map(|row| (row.item_id(), row)).collect();
+    let after: HashMap<&str, &ListingRow> =
+        to.rows.iter().map(|row| (row.item_id(), row)).collect();
+    let item_ids: BTreeSet<&str> = before.keys().chain(after.keys()).copied().collect();
+
+    let mut report = DiffReport {
+        from: from.label.clone(),
+        to: to.label.clone(),
+        generated_at: Utc::now(),
+        summary: DiffSummary::default(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+
+    for item_id in item_ids {
+        match (before.get(item_id), after.get(item_id)) {
+            (None, Some(row)) => report.added.push((*row).clone()),
+            (Some(row), None) => report.removed.push((*row).clone()),
+            (Some(old), Some(new)) => {
+                let changes = compare(old, new, options);
+                if changes.is_empty() {
+                    report.summary.unchanged += 1;
+                } else {
+                    report.changed.push(ChangedListing {
+                        item_id: item_id.to_string(),
+                        title: new.get("Title").to_string(),
+                        changes,
+                    });
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    report.summary.added = report.added.len();
+    report.summary.removed = report.removed.len();
+    report.summary.changed = report.changed.len();
+    log::info!(
+        "Diff {} -> {}: {} added, {} removed, {} changed, {} unchanged",
+        report.from,
+        report.to,
+        report.summary.added,
+        report.summary.removed,
+        report.summary.changed,
+        report.summary.unchanged
+    );
+    report
+}
+
+/// Field changes between two versions of one listing, in workbook column
+/// order followed by any extra columns either row carries.
+fn compare(old: &ListingRow, new: &ListingRow, options: &DiffOptions) -> Vec<FieldChange> {
+    let extra: BTreeSet<&str> = old
+        .columns()
+        .chain(new.columns())
+        .map(|(name, _)| name)
+        .filter(|name| listing_rows::column(name).is_none())
+        .collect();
+    let columns = LISTING_COLUMNS.iter().map(|spec| spec.name).chain(extra);
+
+    columns
+        .filter(|column| !options.ignore_columns.iter().any(|c| c == column))
+        .filter_map(|column| {
+            let kind = listing_rows::column(column).map(|spec| spec.kind);
+            let coerce = |cell: &Cell| match kind {
+                Some(kind) => kind.coerce(cell.clone()),
+                None => cell.clone(),
+            };
+            let (before, after) = (coerce(old.get(column)), coerce(new.get(column)));
+            (!same(&before, &after)).then(|| FieldChange {
+                column: column.to_string(),
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+fn same(a: &Cell, b: &Cell) -> bool {
+    match (a, b) {
+        (Cell::Text(_), _) | (_, Cell::Text(_)) | (Cell::Bool(_), _) | (_, Cell::Bool(_)) => a == b,
+        _ => match (a.as_f64(), b.as_f64()) {
+            (Some(x), Some(y)) => (x - y).abs() < 1e-9,
+            _ => a.is_empty() && b.is_empty(),
+        },
+    }
+}
+
+impl DiffReport {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_atomic(path.as_ref(), self.to_json()?.as_bytes())
+    }
+
+    /// Add a "Changes" sheet: one row per added or removed listing and one
+    /// per changed field.
+    pub fn add_sheet(&self, workbook: &mut Workbook) -> Result<()> {
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(DIFF_SHEET)?;
+        self.fill_sheet(sheet)
+    }
+
+    pub fn write_xlsx(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut workbook = Workbook::new();
+        self.add_sheet(&mut workbook)?;
+        workbook.save(path.as_ref())?;
+        Ok(())
+    }
+
+    fn fill_sheet(&self, sheet: &mut Worksheet) -> Result<()> {
+        const HEADERS: [(&str, f64); 6] = [
+            ("Change", 10.0),
+            ("Item ID", 16.0),
+            ("Title", 50.0),
+            ("Column", 24.0),
+            ("Before", 30.0),
+            ("After", 30.0),
+        ];
+        let bold = Format::new().set_bold();
+        for (col, (header, width)) in (0u16..).zip(HEADERS) {
+            sheet.write_string_with_format(0, col, header, &bold)?;
+            sheet.set_column_width(col, width)?;
+        }
+
+        let mut row = 0u32;
+        for (change, listings) in [("Added", &self.added), ("Removed", &self.removed)] {
+            for listing in listings {
+                row += 1;
+                sheet.write_string(row, 0, change)?;
+                sheet.write_string(row, 1, listing.item_id())?;
+                write_cell(sheet, row, 2, listing.get("Title"), None)?;
+            }
+        }
+        for listing in &self.changed {
+            for field in &listing.changes {
+                row += 1;
+                sheet.write_string(row, 0, "Changed")?;
+                sheet.write_string(row, 1, &listing.item_id)?;
+                sheet.write_string(row, 2, &listing.title)?;
+                sheet.write_string(row, 3, &field.column)?;
+                write_cell(sheet, row, 4, &field.before, None)?;
+                write_cell(sheet, row, 5, &field.after, None)?;
+            }
+        }
+
+        sheet.set_freeze_panes(1, 0)?;
+        sheet.autofilter(0, 0, row, HEADERS.len() as u16 - 1)?;
+        Ok(())
+    }
+}
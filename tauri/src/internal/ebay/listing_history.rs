@@ -0,0 +1,404 @@
+//! Historical listing crawler built on `GetSellerList`.
+//!
+//! Port of `fetchHistoricalListings` / `fetchListingsInTimeWindow` from
+//! `ebay-listings-exporter.js`, with the guesswork taken out:
+//!
+//! - Windows are contiguous and always shorter than eBay's 121-day
+//!   `StartTimeFrom`..`StartTimeTo` limit (the JS skipped a day between them).
+//! - The walk stops at the seller's registration date from `GetUser` rather
+//!   than after three empty windows. The empty-window rule is only used when
+//!   the registration date is unavailable.
+//! - Errors are returned as they are instead of being matched on "time" or
+//!   "date" in the message.
+//! - Each window is paged until `HasMoreItems` is false, and items seen in an
+//!   earlier window or page are dropped by `ItemID`.
+//!
+//! With a checkpoint path set, progress is saved after every page: the crawl
+//! position goes to `<checkpoint>` and the items found so far to
+//! `<checkpoint>.items.jsonl`. A crawl that is cut off picks up from the last
+//! saved page next time. Both files are removed once the crawl finishes.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::trading::calls::{DetailLevel, GranularityLevel};
+use super::trading::types::Pagination;
+use super::trading::{GetSellerList, GetUser, Item, TradingClient};
+use crate::adapters::ebay::vault::write_atomic;
+use crate::error::{Error, Result};
+
+/// `StartTimeTo - StartTimeFrom` must be less than this many days.
+pub const MAX_WINDOW_DAYS: i64 = 121;
+
+const CHECKPOINT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct HistoryCrawlConfig {
+    /// Length of each window; clamped below [`MAX_WINDOW_DAYS`].
+    pub window: TimeDelta,
+    /// `EntriesPerPage`, at most 200 for `GetSellerList`.
+    pub entries_per_page: u32,
+    /// Oldest listing start time to look for. Looked up with `GetUser` when
+    /// not set.
+    pub floor: Option<DateTime<Utc>>,
+    /// Consecutive empty windows before giving up when no floor is known.
+    pub max_empty_windows: u32,
+    /// Hard cap on the number of windows (50 covers about 16 years).
+    pub max_windows: u32,
+    pub page_delay: Duration,
+    pub window_delay: Duration,
+}
+
+impl Default for HistoryCrawlConfig {
+    fn default() -> Self {
+        Self {
+            window: TimeDelta::days(120),
+            entries_per_page: 100,
+            floor: None,
+            max_empty_windows: 3,
+            max_windows: 50,
+            page_delay: Duration::from_millis(300),
+            window_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Crawl position saved between pages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CrawlState {
+    version: u32,
+    /// End of the first (most recent) window. Windows are laid out backwards
+    /// from here, so a resumed crawl sees the same windows.
+    anchor: DateTime<Utc>,
+    floor: Option<DateTime<Utc>>,
+    window: u32,
+    next_page: u32,
+    empty_windows: u32,
+    /// Items fetched in the current window, including ones already seen.
+    window_items: u32,
+}
+
+/// Time range covered by one `GetSellerList` window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub index: u32,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+pub struct ListingHistoryCrawler<'a> {
+    client: &'a TradingClient,
+    config: HistoryCrawlConfig,
+    checkpoint: Option<PathBuf>,
+}
+
+impl<'a> ListingHistoryCrawler<'a> {
+    pub fn new(client: &'a TradingClient) -> Self {
+        Self {
+            client,
+            config: HistoryCrawlConfig::default(),
+            checkpoint: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: HistoryCrawlConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Save progress to `path` so an interrupted crawl can resume.
+    pub fn with_checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    /// Delete any saved progress so the next crawl starts from today.
+    pub fn reset(&self) -> Result<()> {
+        if let Some(path) = &self.checkpoint {
+            remove_if_exists(path)?;
+            remove_if_exists(&items_path(path))?;
+        }
+        Ok(())
+    }
+
+    /// Walk back from now (or from the saved position) to the floor and
+    /// return every listing found, newest window first.
+    pub async fn crawl(&self) -> Result<Vec<Item>> {
+        let (mut state, mut items) = match self.load()? {
+            Some((state, items)) => {
+                log::info!(
+                    "Resuming historical search at window {}, page {} ({} listings so far)",
+                    state.window + 1,
+                    state.next_page,
+                    items.len()
+                );
+                (state, items)
+            }
+            None => (self.start().await, Vec::new()),
+        };
+        let mut seen: HashSet<String> = items.iter().map(|i| i.item_id.clone()).collect();
+
+        log::info!(
+            "Searching historical listings in {}-day windows...",
+            self.window_length().num_days()
+        );
+
+        while let Some(window) = self.window(&state) {
+            if state.next_page == 1 {
+                log::info!(
+                    "Searching window {}: {} to {}",
+                    window.index + 1,
+                    window.start.format("%Y-%m-%d"),
+                    window.end.format("%Y-%m-%d")
+                );
+            }
+
+            let has_more = self
+                .fetch_page(&window, &mut state, &mut items, &mut seen)
+                .await?;
+            if has_more {
+                state.next_page += 1;
+                self.save(&state)?;
+                tokio::time::sleep(self.config.page_delay).await;
+                continue;
+            }
+
+            if state.window_items > 0 {
+                log::info!(
+                    "Found {} listings in this window (total: {})",
+                    state.window_items,
+                    items.len()
+                );
+                state.empty_windows = 0;
+            } else {
+                log::info!("No listings found in this window");
+                state.empty_windows += 1;
+            }
+
+            state.window += 1;
+            state.next_page = 1;
+            state.window_items = 0;
+            self.save(&state)?;
+
+            if state.floor.is_none() && state.empty_windows >= self.config.max_empty_windows {
+                log::info!("No recent listings found, stopping historical search");
+                break;
+            }
+            if self.window(&state).is_some() {
+                tokio::time::sleep(self.config.window_delay).await;
+            }
+        }
+
+        log::info!("Historical search complete: {} listings", items.len());
+        self.reset()?;
+        Ok(items)
+    }
+
+    /// Windows laid out for a fresh crawl starting at `anchor`.
+    pub fn windows(&self, anchor: DateTime<Utc>, floor: Option<DateTime<Utc>>) -> Vec<Window> {
+        let mut state = CrawlState {
+            version: CHECKPOINT_VERSION,
+            anchor,
+            floor,
+            window: 0,
+            next_page: 1,
+            empty_windows: 0,
+            window_items: 0,
+        };
+        let mut windows = Vec::new();
+        while let Some(window) = self.window(&state) {
+            windows.push(window);
+            state.window += 1;
+        }
+        windows
+    }
+
+    async fn start(&self) -> CrawlState {
+        let floor = match self.config.floor {
+            Some(floor) => Some(floor),
+            None => self.registration_date().await,
+        };
+        CrawlState {
+            version: CHECKPOINT_VERSION,
+            anchor: Utc::now(),
+            floor,
+            window: 0,
+            next_page: 1,
+            empty_windows: 0,
+            window_items: 0,
+        }
+    }
+
+    async fn registration_date(&self) -> Option<DateTime<Utc>> {
+        match self.client.execute(&GetUser::default()).await {
+            Ok(response) => {
+                let date = response.user.and_then(|u| u.registration_date);
+                if let Some(date) = date {
+                    log::info!("Account registered {}", date.format("%Y-%m-%d"));
+                }
+                date
+            }
+            Err(err) => {
+                log::warn!("Could not read account registration date: {err}");
+                None
+            }
+        }
+    }
+
+    fn window_length(&self) -> TimeDelta {
+        let max = TimeDelta::days(MAX_WINDOW_DAYS) - TimeDelta::seconds(1);
+        self.config.window.clamp(TimeDelta::days(1), max)
+    }
+
+    /// The window `state` points at, or `None` once the walk is over.
+    fn window(&self, state: &CrawlState) -> Option<Window> {
+        if state.window >= self.config.max_windows {
+            return None;
+        }
+
+        let length = self.window_length();
+        let end = state.anchor - length * i32::try_from(state.window).ok()?;
+        let mut start = end - length;
+        if let Some(floor) = state.floor {
+            if end <= floor {
+                return None;
+            }
+            start = start.max(floor);
+        }
+
+        Some(Window {
+            index: state.window,
+            start,
+            end,
+        })
+    }
+
+    async fn fetch_page(
+        &self,
+        window: &Window,
+        state: &mut CrawlState,
+        items: &mut Vec<Item>,
+        seen: &mut HashSet<String>,
+    ) -> Result<bool> {
+        let call = GetSellerList {
+            detail_level: Some(DetailLevel::ReturnAll),
+            pagination: Some(Pagination {
+                entries_per_page: self.config.entries_per_page,
+                page_number: state.next_page,
+            }),
+            start_time_from: Some(window.start),
+            start_time_to: Some(window.end),
+            granularity_level: Some(GranularityLevel::Coarse),
+            include_watch_count: Some(true),
+            ..Default::default()
+        };
+
+        let response = self.client.execute(&call).await?;
+        let has_more = response.has_more_items;
+        let page = response.into_items();
+
+        // Counted before duplicates are dropped: a page fetched again after
+        // a resume holds items already seen, and its window is not empty.
+        state.window_items += u32::try_from(page.len()).unwrap_or(u32::MAX);
+        let new_items: Vec<Item> = page
+            .into_iter()
+            .filter(|item| seen.insert(item.item_id.clone()))
+            .collect();
+
+        self.append_items(&new_items)?;
+        items.extend(new_items);
+        Ok(has_more)
+    }
+
+    fn load(&self) -> Result<Option<(CrawlState, Vec<Item>)>> {
+        let Some(path) = &self.checkpoint else {
+            return Ok(None);
+        };
+
+        let state: CrawlState = match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        if state.version != CHECKPOINT_VERSION {
+            return Err(Error::InvalidResponse(format!(
+                "unsupported crawl checkpoint version {}",
+                state.version
+            )));
+        }
+
+        Ok(Some((state, read_items(&items_path(path))?)))
+    }
+
+    fn save(&self, state: &CrawlState) -> Result<()> {
+        match &self.checkpoint {
+            Some(path) => write_atomic(path, &serde_json::to_vec_pretty(state)?),
+            None => Ok(()),
+        }
+    }
+
+    fn append_items(&self, items: &[Item]) -> Result<()> {
+        let Some(path) = &self.checkpoint else {
+            return Ok(());
+        };
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(items_path(path))?;
+        for item in items {
+            serde_json::to_writer(&mut file, item)?;
+            file.write_all(b"\n")?;
+        }
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+fn items_path(checkpoint: &Path) -> PathBuf {
+    let mut name = checkpoint.file_name().unwrap_or_default().to_os_string();
+    name.push(".items.jsonl");
+    checkpoint.with_file_name(name)
+}
+
+/// Items saved so far. Items are appended before the position is saved, so a
+/// page can be on disk twice after a crash; duplicates are dropped here, and
+/// a line cut short by the crash is skipped.
+fn read_items(path: &Path) -> Result<Vec<Item>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut seen = HashSet::new();
+    let mut items = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        match serde_json::from_str::<Item>(&line) {
+            Ok(item) if seen.insert(item.item_id.clone()) => items.push(item),
+            Ok(_) => {}
+            Err(err) => log::warn!("Skipping unreadable checkpoint line: {err}"),
+        }
+    }
+    Ok(items)
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,767 @@
+//! Flat listing rows, one per item, in the exporter's column layout.
+//!
+//! Port of `TradingDataProcessor.processSellerListings` / `processMyeBayData`
+//! from `ebay-listings-exporter.js`. Columns keep the JS names and order so a
+//! row can be written to the same workbook, but values are typed instead of
+//! everything being a string, and two JS slips are fixed:
+//!
+//! - "Picture Count" counts `PictureURL`s (the JS took the length of the
+//!   `PhotoDisplay` string).
+//! - "Last Modified" was `formatDate(item.TimeLeft)`, which is never a date.
+//!   It is left empty here; the warehouse records when each row changed.
+//!
+//! Besides the fixed Brand, Model, Size, Color and Material columns, every
+//! item specific the listing has gets a column of its own ("Specific: Thread
+//! Count" and so on). Categories use different aspect sets, so rows carry only
+//! the specifics their listing has; writers take the union.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::selling_lists::{ListedItem, SellingList};
+use super::trading::types::{Amount, ItemSpecifics};
+use super::trading::Item;
+
+pub const ITEM_ID: &str = "Item ID";
+pub const LISTING_STATUS_CATEGORY: &str = "Listing Status Category";
+
+/// Prefix of the item-specific columns.
+pub const SPECIFIC_PREFIX: &str = "Specific: ";
+/// Between the values of a multi-valued item specific in its column, as
+/// `extractItemSpecific` joins them.
+pub const SPECIFIC_SEPARATOR: &str = ", ";
+
+/// One value in a row. Serialises to plain JSON (`null`, bool, number or
+/// string) so stored rows stay readable.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Cell {
+    #[default]
+    Empty,
+    Bool(bool),
+    Integer(i64),
+    Number(f64),
+    Text(String),
+}
+
+impl Cell {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Cell::Empty => true,
+            Cell::Text(text) => text.is_empty(),
+            _ => false,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Cell::Integer(value) => Some(*value as f64),
+            Cell::Number(value) => Some(*value),
+            Cell::Text(text) => text.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Cell::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    fn text(value: Option<&str>) -> Cell {
+        match value {
+            Some(value) if !value.is_empty() => Cell::Text(value.to_string()),
+            _ => Cell::Empty,
+        }
+    }
+
+    fn integer(value: Option<i64>) -> Cell {
+        value.map_or(Cell::Empty, Cell::Integer)
+    }
+
+    fn amount(value: Option<&Amount>) -> Cell {
+        value.map_or(Cell::Empty, |amount| Cell::Number(amount.value))
+    }
+
+    fn flag(value: Option<bool>) -> Cell {
+        Cell::Bool(value.unwrap_or(false))
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cell::Empty => Ok(()),
+            Cell::Bool(value) => write!(f, "{value}"),
+            Cell::Integer(value) => write!(f, "{value}"),
+            Cell::Number(value) => write!(f, "{value}"),
+            Cell::Text(value) => f.write_str(value),
+        }
+    }
+}
+
+/// How a column's values are typed, for writers that format cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnKind {
+    Text,
+    Integer,
+    Decimal,
+    /// An amount in the row's "Currency".
+    Price,
+    Bool,
+    /// ISO 8601 timestamp.
+    DateTime,
+    /// `YYYY-MM-DD`.
+    Date,
+    /// Fractional days, e.g. "Time Left (Days)".
+    Days,
+}
+
+impl ColumnKind {
+    /// Bring a value read back from a workbook to the type this column is
+    /// built with. The JS exporter wrote every value as a string, so without
+    /// this an old export would never compare equal to a stored row.
+    pub fn coerce(self, cell: Cell) -> Cell {
+        if cell.is_empty() {
+            return Cell::Empty;
+        }
+        match self {
+            ColumnKind::Text | ColumnKind::DateTime | ColumnKind::Date => match cell {
+                Cell::Text(_) => cell,
+                other => Cell::Text(other.to_string()),
+            },
+            ColumnKind::Integer => match &cell {
+                Cell::Number(value) if value.fract() == 0.0 => Cell::Integer(*value as i64),
+                Cell::Text(text) => text.trim().parse().map_or(cell, Cell::Integer),
+                _ => cell,
+            },
+            ColumnKind::Decimal => match &cell {
+                // The buyer analyzer writes percentages as "12.5%".
+                Cell::Text(text) => text
+                    .trim()
+                    .trim_end_matches('%')
+                    .parse()
+                    .map_or(cell, Cell::Number),
+                _ => cell.as_f64().map_or(cell, Cell::Number),
+            },
+            ColumnKind::Price | ColumnKind::Days => cell.as_f64().map_or(cell, Cell::Number),
+            ColumnKind::Bool => match &cell {
+                Cell::Text(text)
+                    if text.eq_ignore_ascii_case("true") || text.eq_ignore_ascii_case("yes") =>
+                {
+                    Cell::Bool(true)
+                }
+                Cell::Text(text)
+                    if text.eq_ignore_ascii_case("false") || text.eq_ignore_ascii_case("no") =>
+                {
+                    Cell::Bool(false)
+                }
+                _ => cell,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnSpec {
+    pub name: &'static str,
+    pub kind: ColumnKind,
+}
+
+pub(crate) const fn col(name: &'static str, kind: ColumnKind) -> ColumnSpec {
+    ColumnSpec { name, kind }
+}
+
+/// Exporter columns in workbook order.
+pub const LISTING_COLUMNS: &[ColumnSpec] = {
+    use ColumnKind::*;
+    &[
+        col(ITEM_ID, Text),
+        col("SKU", Text),
+        col("Title", Text),
+        col("Subtitle", Text),
+        col("Description", Text),
+        col("Category ID", Text),
+        col("Category Name", Text),
+        col("Secondary Category ID", Text),
+        col("Secondary Category Name", Text),
+        col("Condition ID", Text),
+        col("Condition Name", Text),
+        col("Condition Description", Text),
+        col("Listing Type", Text),
+        col("Listing Duration", Text),
+        col("Listing Format", Text),
+        col("Start Price", Price),
+        col("Current Price", Price),
+        col("Buy It Now Price", Price),
+        col("Reserve Price", Price),
+        col("Currency", Text),
+        col("Quantity", Integer),
+        col("Quantity Sold", Integer),
+        col("Quantity Available", Integer),
+        col("Min Qty Per Buyer", Integer),
+        col("Bid Count", Integer),
+        col("High Bidder", Text),
+        col("Best Offer Enabled", Bool),
+        col("Auto Accept Price", Price),
+        col("Min Accept Price", Price),
+        col("Listing Status", Text),
+        col("Time Left", Text),
+        col("Start Time", DateTime),
+        col("End Time", DateTime),
+        col("Time Left (Days)", Days),
+        col("Site", Text),
+        col("Country", Text),
+        col("Location", Text),
+        col("Postal Code", Text),
+        col("Shipping Type", Text),
+        col("Shipping Cost", Price),
+        col("Free Shipping", Bool),
+        col("Fast Handling", Bool),
+        col("Payment Methods", Text),
+        col("PayPal Email", Text),
+        col("Returns Accepted", Text),
+        col("Return Period", Text),
+        col("Return Policy Description", Text),
+        col("Gallery URL", Text),
+        col("Gallery Type", Text),
+        col("Picture Count", Integer),
+        col("Has Pictures", Bool),
+        col("View Item URL", Text),
+        col("View Item URL For Natural Search", Text),
+        col("Watch Count", Integer),
+        col("Hit Count", Integer),
+        col("Question Count", Integer),
+        col("Private Listing", Bool),
+        col("Bold Title", Bool),
+        col("Featured", Bool),
+        col("Highlight", Bool),
+        col("Gallery Plus", Bool),
+        col("Payment Policy ID", Text),
+        col("Shipping Policy ID", Text),
+        col("Return Policy ID", Text),
+        col("Brand", Text),
+        col("Model", Text),
+        col("Size", Text),
+        col("Color", Text),
+        col("Material", Text),
+        col("Seller ID", Text),
+        col("Feedback Score", Integer),
+        col("Positive Feedback %", Decimal),
+        col("Listing Fee", Price),
+        col("Final Value Fee", Price),
+        col("Total Fees", Price),
+        col("Revision", Bool),
+        col("UUID", Text),
+        col("Application Data", Text),
+        col("Created Date", Date),
+        col("End Date", Date),
+        col("Last Modified", Date),
+        col(LISTING_STATUS_CATEGORY, Text),
+    ]
+};
+
+pub fn column(name: &str) -> Option<&'static ColumnSpec> {
+    LISTING_COLUMNS.iter().find(|spec| spec.name == name)
+}
+
+/// One processed listing, keyed by column name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ListingRow {
+    fields: BTreeMap<String, Cell>,
+}
+
+impl ListingRow {
+    pub fn item_id(&self) -> &str {
+        self.get(ITEM_ID).as_str().unwrap_or_default()
+    }
+
+    /// The value in `column`, or [`Cell::Empty`] if the row has none.
+    pub fn get(&self, column: &str) -> &Cell {
+        static EMPTY: Cell = Cell::Empty;
+        self.fields.get(column).unwrap_or(&EMPTY)
+    }
+
+    pub fn set(&mut self, column: impl Into<String>, value: Cell) {
+        self.fields.insert(column.into(), value);
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = (&str, &Cell)> {
+        self.fields.iter().map(|(name, cell)| (name.as_str(), cell))
+    }
+
+    /// Keep only the columns `keep` returns true for.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str, &Cell) -> bool) {
+        self.fields.retain(|name, cell| keep(name, cell));
+    }
+
+    pub fn has_specifics(&self) -> bool {
+        self.fields.keys().any(|name| specific_name(name).is_some())
+    }
+}
+
+/// One item specific with all its values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemSpecific {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// The item specifics of one listing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingSpecifics {
+    pub item_id: String,
+    pub specifics: Vec<ItemSpecific>,
+}
+
+/// The column an item specific is written to.
+pub fn specific_column(name: &str) -> String {
+    format!("{SPECIFIC_PREFIX}{name}")
+}
+
+/// The item specific a column holds, if it is one.
+pub fn specific_name(column: &str) -> Option<&str> {
+    column.strip_prefix(SPECIFIC_PREFIX)
+}
+
+/// `processSellerListings`.
+pub fn process_seller_listings(items: &[Item]) -> Vec<ListingRow> {
+    log::info!("Processing active listings...");
+    items.iter().map(|item| process_item(item, None)).collect()
+}
+
+/// `processMyeBayData`: rows tagged with the list each item came from.
+pub fn process_listed_items(items: &[ListedItem]) -> Vec<ListingRow> {
+    log::info!("Processing My eBay selling data...");
+    items
+        .iter()
+        .map(|listed| process_item(&listed.item, Some(listed.list)))
+        .collect()
+}
+
+pub fn process_item(item: &Item, list: Option<SellingList>) -> ListingRow {
+    let details = item.listing_details.as_ref();
+    let status = item.selling_status.as_ref();
+    let best_offer = item.best_offer_details.as_ref();
+    let shipping = item.shipping_details.as_ref();
+    let first_service = shipping.and_then(|s| s.shipping_service_options.first());
+    let returns = item.return_policy.as_ref();
+    let profiles = item.seller_profiles.as_ref();
+    let seller = item.seller.as_ref();
+    // GetMyeBaySelling puts TimeLeft on the item rather than its status.
+    let time_left = status
+        .and_then(|s| s.time_left.as_deref())
+        .or(item.time_left.as_deref());
+    let enhanced = |name: &str| Cell::Bool(item.listing_enhancements.iter().any(|e| e == name));
+    let specific = |name: &str| {
+        Cell::text(extract_item_specific(item.item_specifics.as_ref(), name).as_deref())
+    };
+
+    let mut row = ListingRow::default();
+    let mut put = |column: &str, value: Cell| row.set(column, value);
+
+    put(ITEM_ID, Cell::Text(item.item_id.clone()));
+    put("SKU", Cell::text(item.sku.as_deref()));
+    put("Title", Cell::text(item.title.as_deref()));
+    put("Subtitle", Cell::text(item.sub_title.as_deref()));
+    put(
+        "Description",
+        Cell::text(clean_description(item.description.as_deref()).as_deref()),
+    );
+
+    let primary = item.primary_category.as_ref();
+    let secondary = item.secondary_category.as_ref();
+    put(
+        "Category ID",
+        Cell::text(primary.and_then(|c| c.category_id.as_deref())),
+    );
+    put(
+        "Category Name",
+        Cell::text(primary.and_then(|c| c.category_name.as_deref())),
+    );
+    put(
+        "Secondary Category ID",
+        Cell::text(secondary.and_then(|c| c.category_id.as_deref())),
+    );
+    put(
+        "Secondary Category Name",
+        Cell::text(secondary.and_then(|c| c.category_name.as_deref())),
+    );
+
+    put("Condition ID", Cell::text(item.condition_id.as_deref()));
+    put(
+        "Condition Name",
+        Cell::text(item.condition_display_name.as_deref()),
+    );
+    put(
+        "Condition Description",
+        Cell::text(item.condition_description.as_deref()),
+    );
+
+    put("Listing Type", Cell::text(item.listing_type.as_deref()));
+    put(
+        "Listing Duration",
+        Cell::text(item.listing_duration.as_deref()),
+    );
+    put(
+        "Listing Format",
+        Cell::text(details.and_then(|d| d.listing_type.as_deref())),
+    );
+
+    let current_price = status.and_then(|s| s.current_price.as_ref());
+    put("Start Price", Cell::amount(item.start_price.as_ref()));
+    put("Current Price", Cell::amount(current_price));
+    put(
+        "Buy It Now Price",
+        Cell::amount(item.buy_it_now_price.as_ref()),
+    );
+    put("Reserve Price", Cell::amount(item.reserve_price.as_ref()));
+    put(
+        "Currency",
+        Cell::text(current_price.and_then(|p| p.currency_id.as_deref())),
+    );
+
+    put("Quantity", Cell::integer(item.quantity));
+    put(
+        "Quantity Sold",
+        Cell::integer(status.and_then(|s| s.quantity_sold)),
+    );
+    put("Quantity Available", Cell::integer(item.quantity_available));
+    put(
+        "Min Qty Per Buyer",
+        Cell::integer(
+            item.quantity_info
+                .as_ref()
+                .and_then(|q| q.minimum_remnant_set),
+        ),
+    );
+
+    put("Bid Count", Cell::integer(status.and_then(|s| s.bid_count)));
+    put(
+        "High Bidder",
+        Cell::text(status.and_then(|s| s.high_bidder.as_ref()?.user_id.as_deref())),
+    );
+    put(
+        "Best Offer Enabled",
+        Cell::flag(best_offer.and_then(|b| b.best_offer_enabled)),
+    );
+    put(
+        "Auto Accept Price",
+        Cell::amount(best_offer.and_then(|b| b.best_offer_auto_accept_price.as_ref())),
+    );
+    put(
+        "Min Accept Price",
+        Cell::amount(best_offer.and_then(|b| b.best_offer_auto_decline_price.as_ref())),
+    );
+
+    put(
+        "Listing Status",
+        Cell::text(status.and_then(|s| s.listing_status.as_deref())),
+    );
+    put("Time Left", Cell::text(time_left));
+    put("Start Time", timestamp(details.and_then(|d| d.start_time)));
+    put("End Time", timestamp(details.and_then(|d| d.end_time)));
+    put("Time Left (Days)", time_left.map_or(Cell::Empty, days_left));
+
+    put("Site", Cell::text(item.site.as_deref()));
+    put("Country", Cell::text(item.country.as_deref()));
+    put("Location", Cell::text(item.location.as_deref()));
+    put("Postal Code", Cell::text(item.postal_code.as_deref()));
+    put(
+        "Shipping Type",
+        Cell::text(shipping.and_then(|s| s.shipping_type.as_deref())),
+    );
+    put(
+        "Shipping Cost",
+        Cell::amount(first_service.and_then(|s| s.shipping_service_cost.as_ref())),
+    );
+    put(
+        "Free Shipping",
+        Cell::flag(first_service.and_then(|s| s.free_shipping)),
+    );
+    put(
+        "Fast Handling",
+        Cell::flag(shipping.and_then(|s| s.fast_and_free)),
+    );
+
+    put(
+        "Payment Methods",
+        Cell::text(Some(&item.payment_methods.join(", "))),
+    );
+    put(
+        "PayPal Email",
+        Cell::text(item.pay_pal_email_address.as_deref()),
+    );
+    put(
+        "Returns Accepted",
+        Cell::text(returns.and_then(|r| r.returns_accepted_option.as_deref())),
+    );
+    put(
+        "Return Period",
+        Cell::text(returns.and_then(|r| r.returns_within_option.as_deref())),
+    );
+    put(
+        "Return Policy Description",
+        Cell::text(returns.and_then(|r| r.description.as_deref())),
+    );
+
+    let pictures = item.picture_details.as_ref();
+    put("Gallery URL", Cell::text(item.gallery_url.as_deref()));
+    put("Gallery Type", Cell::text(item.gallery_type.as_deref()));
+    put(
+        "Picture Count",
+        Cell::Integer(pictures.map_or(0, |p| p.picture_urls.len() as i64)),
+    );
+    put("Has Pictures", Cell::Bool(pictures.is_some()));
+
+    put(
+        "View Item URL",
+        Cell::text(details.and_then(|d| d.view_item_url.as_deref())),
+    );
+    put(
+        "View Item URL For Natural Search",
+        Cell::text(details.and_then(|d| d.view_item_url_for_natural_search.as_deref())),
+    );
+
+    put("Watch Count", Cell::integer(item.watch_count));
+    put("Hit Count", Cell::integer(item.hit_count));
+    put("Question Count", Cell::integer(item.question_count));
+
+    put("Private Listing", Cell::flag(item.private_listing));
+    put("Bold Title", enhanced("Bold"));
+    put("Featured", enhanced("Featured"));
+    put("Highlight", enhanced("Highlight"));
+    put("Gallery Plus", enhanced("GalleryPlus"));
+
+    put(
+        "Payment Policy ID",
+        Cell::text(profiles.and_then(|p| {
+            p.seller_payment_profile
+                .as_ref()?
+                .payment_profile_id
+                .as_deref()
+        })),
+    );
+    put(
+        "Shipping Policy ID",
+        Cell::text(profiles.and_then(|p| {
+            p.seller_shipping_profile
+                .as_ref()?
+                .shipping_profile_id
+                .as_deref()
+        })),
+    );
+    put(
+        "Return Policy ID",
+        Cell::text(profiles.and_then(|p| {
+            p.seller_return_profile
+                .as_ref()?
+                .return_profile_id
+                .as_deref()
+        })),
+    );
+
+    for name in ["Brand", "Model", "Size", "Color", "Material"] {
+        put(name, specific(name));
+    }
+    for specific in item_specifics(item.item_specifics.as_ref()) {
+        put(
+            &specific_column(&specific.name),
+            Cell::Text(specific.values.join(SPECIFIC_SEPARATOR)),
+        );
+    }
+
+    put(
+        "Seller ID",
+        Cell::text(seller.and_then(|s| s.user_id.as_deref())),
+    );
+    put(
+        "Feedback Score",
+        Cell::integer(seller.and_then(|s| s.feedback_score)),
+    );
+    put(
+        "Positive Feedback %",
+        seller
+            .and_then(|s| s.positive_feedback_percent)
+            .map_or(Cell::Empty, Cell::Number),
+    );
+
+    put(
+        "Listing Fee",
+        Cell::amount(details.and_then(|d| d.listing_fee.as_ref())),
+    );
+    put(
+        "Final Value Fee",
+        Cell::amount(status.and_then(|s| s.final_value_fee.as_ref())),
+    );
+    put("Total Fees", total_fees(item));
+
+    put(
+        "Revision",
+        Cell::flag(item.revise_status.as_ref().and_then(|r| r.item_revised)),
+    );
+    put("UUID", Cell::text(item.uuid.as_deref()));
+    put(
+        "Application Data",
+        Cell::text(item.application_data.as_deref()),
+    );
+
+    put("Created Date", date(details.and_then(|d| d.start_time)));
+    put("End Date", date(details.and_then(|d| d.end_time)));
+    put("Last Modified", Cell::Empty);
+
+    if let Some(list) = list {
+        put(
+            LISTING_STATUS_CATEGORY,
+            Cell::Text(list.label().to_string()),
+        );
+    }
+    row
+}
+
+/// `cleanDescription`: tags stripped, trimmed, at most 500 characters.
+pub fn clean_description(description: Option<&str>) -> Option<String> {
+    let description = description?;
+    let mut text = String::with_capacity(description.len());
+    let mut in_tag = false;
+    for c in description.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    Some(text.trim().chars().take(500).collect())
+}
+
+/// `extractItemSpecific`: values of the first name that matches, ignoring case.
+pub fn extract_item_specific(specifics: Option<&ItemSpecifics>, name: &str) -> Option<String> {
+    specifics?
+        .name_value_list
+        .iter()
+        .find(|nv| nv.name.eq_ignore_ascii_case(name))
+        .filter(|nv| !nv.values.is_empty())
+        .map(|nv| nv.values.join(SPECIFIC_SEPARATOR))
+}
+
+/// Every item specific, cleaned up: whitespace in names collapsed, entries
+/// whose names differ only in case merged (the first spelling wins), blank
+/// and repeated values dropped, and specifics left without values skipped.
+pub fn item_specifics(specifics: Option<&ItemSpecifics>) -> Vec<ItemSpecific> {
+    let mut merged: Vec<ItemSpecific> = Vec::new();
+    for entry in specifics.map_or(&[][..], |s| &s.name_value_list) {
+        let name = entry.name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name.is_empty() {
+            continue;
+        }
+        let index = match merged
+            .iter()
+            .position(|s| s.name.eq_ignore_ascii_case(&name))
+        {
+            Some(index) => index,
+            None => {
+                merged.push(ItemSpecific {
+                    name,
+                    values: Vec::new(),
+                });
+                merged.len() - 1
+            }
+        };
+        let values = &mut merged[index].values;
+        for value in entry.values.iter().map(|v| v.trim()) {
+            if !value.is_empty() && !values.iter().any(|v| v == value) {
+                values.push(value.to_string());
+            }
+        }
+    }
+    merged.retain(|specific| !specific.values.is_empty());
+    merged
+}
+
+/// Item specifics per listing, for the items whose response carried an
+/// `ItemSpecifics` element (not every call returns them).
+pub fn listing_specifics<'a>(items: impl IntoIterator<Item = &'a Item>) -> Vec<ListingSpecifics> {
+    items
+        .into_iter()
+        .filter(|item| item.item_specifics.is_some())
+        .map(|item| ListingSpecifics {
+            item_id: item.item_id.clone(),
+            specifics: item_specifics(item.item_specifics.as_ref()),
+        })
+        .collect()
+}
+
+/// `calculateDaysLeft`: whole days plus hours as a tenth of a day, e.g.
+/// `P5DT12H30M` is 5.5. Anything that is not a duration is kept as text.
+pub fn days_left(time_left: &str) -> Cell {
+    match parse_duration(time_left) {
+        Some((days, hours)) => {
+            Cell::Number(days as f64 + (hours as f64 / 24.0 * 10.0).round() / 10.0)
+        }
+        None => Cell::Text(time_left.to_string()),
+    }
+}
+
+/// Days and hours of an ISO 8601 duration such as `P5DT12H30M45S`.
+fn parse_duration(value: &str) -> Option<(u64, u64)> {
+    let rest = value.strip_prefix('P')?;
+    let (mut days, mut hours) = (0, 0);
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => in_time = true,
+            'D' if !in_time => days = number.parse().ok()?,
+            'H' if in_time => hours = number.parse().ok()?,
+            'M' | 'S' if in_time => {}
+            _ => return None,
+        }
+        if c.is_ascii_alphabetic() {
+            number.clear();
+        }
+    }
+    number.is_empty().then_some((days, hours))
+}
+
+/// `calculateTotalFees`: listing fee plus final value fee, empty when zero.
+fn total_fees(item: &Item) -> Cell {
+    let listing_fee = item
+        .listing_details
+        .as_ref()
+        .and_then(|d| d.listing_fee.as_ref())
+        .map_or(0.0, |a| a.value);
+    let final_value_fee = item
+        .selling_status
+        .as_ref()
+        .and_then(|s| s.final_value_fee.as_ref())
+        .map_or(0.0, |a| a.value);
+    let total = listing_fee + final_value_fee;
+    if total > 0.0 {
+        Cell::Number((total * 100.0).round() / 100.0)
+    } else {
+        Cell::Empty
+    }
+}
+
+pub(crate) fn timestamp(value: Option<DateTime<Utc>>) -> Cell {
+    value.map_or(Cell::Empty, |at| {
+        Cell::Text(at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+    })
+}
+
+/// `formatDate`: `YYYY-MM-DD`.
+pub(crate) fn date(value: Option<DateTime<Utc>>) -> Cell {
+    value.map_or(Cell::Empty, |at| {
+        Cell::Text(at.format("%Y-%m-%d").to_string())
+    })
+}
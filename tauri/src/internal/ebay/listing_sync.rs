@@ -0,0 +1,245 @@
+//! Keeps the [`Warehouse`] in step with eBay.
+//!
+//! The first sync for an account (or one after a long gap) is a full sync:
+//! every `GetMyeBaySelling` list, plus the `GetSellerList` history if asked
+//! for. After that only what changed is fetched. `GetSellerList` can filter by
+//! start and end time but not by modification time, so incremental syncs use
+//! `GetSellerEvents` with `ModTimeFrom`/`ModTimeTo`, starting a little before
+//! the end of the last complete sync.
+
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use super::listing_history::{HistoryCrawlConfig, ListingHistoryCrawler};
+use super::listing_rows::{self, ListingRow};
+use super::selling_lists::SellingListsFetcher;
+use super::trading::calls::DetailLevel;
+use super::trading::{GetSellerEvents, TradingClient};
+use super::warehouse::{RowSource, SyncKind, SyncRecord, Warehouse};
+use crate::error::Result;
+
+/// `GetSellerEvents` returns at most this many items; a window that hits it
+/// may have been cut short and is split in two.
+pub const MAX_EVENTS_PER_CALL: usize = 3000;
+
+#[derive(Debug, Clone)]
+pub struct SyncConfig {
+    /// Also walk the listing history on full syncs. Slow; off by default.
+    pub include_history: bool,
+    pub history: HistoryCrawlConfig,
+    /// How far before the last sync's end an incremental sync starts, to
+    /// cover clock skew and late updates.
+    pub overlap: TimeDelta,
+    /// Length of each `GetSellerEvents` window.
+    pub event_window: TimeDelta,
+    /// Windows are not split below this length.
+    pub min_event_window: TimeDelta,
+    /// A last sync older than this triggers a full sync instead.
+    pub max_incremental_age: TimeDelta,
+    pub window_delay: Duration,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            include_history: false,
+            history: HistoryCrawlConfig::default(),
+            overlap: TimeDelta::minutes(10),
+            event_window: TimeDelta::hours(48),
+            min_event_window: TimeDelta::minutes(15),
+            max_incremental_age: TimeDelta::days(30),
+            window_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+pub struct ListingSync<'a> {
+    client: &'a TradingClient,
+    warehouse: &'a Warehouse,
+    account: String,
+    config: SyncConfig,
+}
+
+impl<'a> ListingSync<'a> {
+    pub fn new(
+        client: &'a TradingClient,
+        warehouse: &'a Warehouse,
+        account: impl Into<String>,
+    ) -> Self {
+        Self {
+            client,
+            warehouse,
+            account: account.into(),
+            config: SyncConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: SyncConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Incremental if a recent enough complete sync exists, full otherwise.
+    pub async fn sync(&self) -> Result<SyncRecord> {
+        let since = self
+            .warehouse
+            .last_complete_sync(&self.account)?
+            .and_then(|last| last.mod_time_to)
+            .filter(|to| Utc::now() - *to <= self.config.max_incremental_age);
+
+        match since {
+            Some(since) => self.incremental(since).await,
+            None => self.full().await,
+        }
+    }
+
+    pub async fn full(&self) -> Result<SyncRecord> {
+        log::info!("Starting full listing sync for {}", self.account);
+        let sync =
+            self.warehouse
+                .begin_sync(&self.account, SyncKind::Full, None, Some(Utc::now()))?;
+        let result = self.run_full(sync.id).await;
+        self.close(sync, result)
+    }
+
+    /// Fetch listings modified since `since` (less the configured overlap).
+    pub async fn incremental(&self, since: DateTime<Utc>) -> Result<SyncRecord> {
+        let from = since - self.config.overlap;
+        let to = Utc::now();
+        log::info!(
+            "Starting incremental listing sync for {} from {from} to {to}",
+            self.account
+        );
+        let sync = self.warehouse.begin_sync(
+            &self.account,
+            SyncKind::Incremental,
+            Some(from),
+            Some(to),
+        )?;
+        let result = self.run_incremental(sync.id, from, to).await;
+        self.close(sync, result)
+    }
+
+    fn close(&self, sync: SyncRecord, result: Result<()>) -> Result<SyncRecord> {
+        match result {
+            Ok(()) => {
+                let sync = self.warehouse.finish_sync(sync.id)?;
+                log::info!("Listing sync complete: {} items stored", sync.item_count);
+                Ok(sync)
+            }
+            Err(err) => {
+                log::warn!("Listing sync failed: {err}");
+                self.warehouse.fail_sync(sync.id, &err.to_string())?;
+                Err(err)
+            }
+        }
+    }
+
+    async fn run_full(&self, sync_id: i64) -> Result<()> {
+        let listed = SellingListsFetcher::new(self.client).fetch_all().await?;
+
+        // An item can sit in more than one list (a multi-quantity listing
+        // that is active and has sales); the first list wins.
+        let mut seen = HashSet::new();
+        let rows: Vec<ListingRow> = listing_rows::process_listed_items(&listed)
+            .into_iter()
+            .filter(|row| seen.insert(row.item_id().to_string()))
+            .collect();
+
+        let history = if self.config.include_history {
+            ListingHistoryCrawler::new(self.client)
+                .with_config(self.config.history.clone())
+                .crawl()
+                .await?
+        } else {
+            Vec::new()
+        };
+        let history_rows: Vec<ListingRow> = listing_rows::process_seller_listings(&history)
+            .into_iter()
+            .filter(|row| seen.insert(row.item_id().to_string()))
+            .collect();
+
+        let mut seen = HashSet::new();
+        let specifics: Vec<_> =
+            listing_rows::listing_specifics(listed.iter().map(|l| &l.item).chain(&history))
+                .into_iter()
+                .filter(|listing| seen.insert(listing.item_id.clone()))
+                .collect();
+
+        self.warehouse
+            .record(sync_id, RowSource::SellingLists, &rows)?;
+        self.warehouse
+            .record(sync_id, RowSource::SellerList, &history_rows)?;
+        self.warehouse.record_specifics(&self.account, &specifics)?;
+        Ok(())
+    }
+
+    async fn run_incremental(
+        &self,
+        sync_id: i64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut windows = event_windows(from, to, self.config.event_window);
+        while let Some((start, end)) = windows.pop_front() {
+            let response = self
+                .client
+                .execute(&GetSellerEvents {
+                    detail_level: Some(DetailLevel::ReturnAll),
+                    mod_time_from: Some(start),
+                    mod_time_to: Some(end),
+                    include_watch_count: Some(true),
+                    new_item_filter: None,
+                })
+                .await?;
+            let items = response.into_items();
+
+            if items.len() >= MAX_EVENTS_PER_CALL && end - start > self.config.min_event_window {
+                let middle = start + (end - start) / 2;
+                log::info!(
+                    "{} changes from {start} to {end}; splitting the window",
+                    items.len()
+                );
+                windows.push_front((middle, end));
+                windows.push_front((start, middle));
+                continue;
+            }
+
+            log::info!(
+                "Found {} changed listings from {start} to {end}",
+                items.len()
+            );
+            self.warehouse.record(
+                sync_id,
+                RowSource::SellerEvents,
+                &listing_rows::process_seller_listings(&items),
+            )?;
+            self.warehouse
+                .record_specifics(&self.account, &listing_rows::listing_specifics(&items))?;
+
+            if !windows.is_empty() {
+                tokio::time::sleep(self.config.window_delay).await;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Contiguous windows of at most `length` covering `from..to`, oldest first.
+pub fn event_windows(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    length: TimeDelta,
+) -> VecDeque<(DateTime<Utc>, DateTime<Utc>)> {
+    let length = length.max(TimeDelta::minutes(1));
+    let mut windows = VecDeque::new();
+    let mut start = from;
+    while start < to {
+        let end = (start + length).min(to);
+        windows.push_back((start, end));
+        start = end;
+    }
+    windows
+}
@@ -0,0 +1,160 @@
+//! Audit trail of promotion changes.
+//!
+//! `extendDiscountEndDate` logged its result to the console and forgot it.
+//! Every change the discount tools make, or fail to make, is kept in the
+//! warehouse's `promotion_audit` table so an unattended run can be reviewed
+//! afterwards.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+
+use super::types::{Promotion, PromotionType};
+use crate::error::Result;
+use crate::internal::ebay::warehouse::{from_timestamp, parsed, Warehouse};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditAction {
+    /// The end date was moved out.
+    Extended,
+    /// Any other change.
+    Updated,
+    /// The update was sent and eBay rejected it.
+    Failed,
+}
+
+impl AuditAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Extended => "extended",
+            AuditAction::Updated => "updated",
+            AuditAction::Failed => "failed",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "extended" => Some(AuditAction::Extended),
+            "updated" => Some(AuditAction::Updated),
+            "failed" => Some(AuditAction::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: i64,
+    pub promotion_id: String,
+    pub promotion_name: String,
+    pub promotion_type: PromotionType,
+    /// The renewal rule or other reason behind the change, if any.
+    pub rule: Option<String>,
+    pub action: AuditAction,
+    pub old_end_date: Option<DateTime<Utc>>,
+    pub new_end_date: Option<DateTime<Utc>>,
+    pub recorded_at: DateTime<Utc>,
+    pub error: Option<String>,
+}
+
+/// One account's audit trail.
+#[derive(Debug, Clone)]
+pub struct PromotionAudit {
+    warehouse: Arc<Warehouse>,
+    account: String,
+}
+
+impl PromotionAudit {
+    pub fn new(warehouse: Arc<Warehouse>, account: impl Into<String>) -> Self {
+        Self {
+            warehouse,
+            account: account.into(),
+        }
+    }
+
+    /// Record a change to `promotion` (as it was before the change).
+    pub fn record(
+        &self,
+        promotion_id: &str,
+        promotion: &Promotion,
+        reason: Option<&str>,
+        action: AuditAction,
+        new_end_date: Option<DateTime<Utc>>,
+        error: Option<&str>,
+    ) -> Result<AuditEntry> {
+        let conn = self.warehouse.conn();
+        conn.execute(
+            "INSERT INTO promotion_audit
+                 (account, promotion_id, promotion_name, promotion_type, rule, action,
+                  old_end_date, new_end_date, recorded_at, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                self.account,
+                promotion_id,
+                promotion.name(),
+                promotion.promotion_type().as_str(),
+                reason,
+                action.as_str(),
+                promotion.end_date().timestamp(),
+                new_end_date.map(|at| at.timestamp()),
+                Utc::now().timestamp(),
+                error,
+            ],
+        )?;
+        Ok(conn.query_row(
+            &format!("{AUDIT_SELECT} WHERE id = ?1"),
+            [conn.last_insert_rowid()],
+            entry_from_row,
+        )?)
+    }
+
+    /// The newest `limit` entries, newest first.
+    pub fn entries(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let conn = self.warehouse.conn();
+        let mut stmt = conn.prepare(&format!(
+            "{AUDIT_SELECT} WHERE account = ?1 ORDER BY id DESC LIMIT ?2"
+        ))?;
+        let entries = stmt
+            .query_map(params![self.account, limit as i64], entry_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(entries)
+    }
+
+    /// Every entry for one promotion, oldest first.
+    pub fn history(&self, promotion_id: &str) -> Result<Vec<AuditEntry>> {
+        let conn = self.warehouse.conn();
+        let mut stmt = conn.prepare(&format!(
+            "{AUDIT_SELECT} WHERE account = ?1 AND promotion_id = ?2 ORDER BY id"
+        ))?;
+        let entries = stmt
+            .query_map(params![self.account, promotion_id], entry_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(entries)
+    }
+}
+
+const AUDIT_SELECT: &str = "SELECT id, promotion_id, promotion_name, promotion_type, rule,
+    action, old_end_date, new_end_date, recorded_at, error FROM promotion_audit";
+
+fn entry_from_row(row: &Row<'_>) -> rusqlite::Result<AuditEntry> {
+    Ok(AuditEntry {
+        id: row.get(0)?,
+        promotion_id: row.get(1)?,
+        promotion_name: row.get(2)?,
+        promotion_type: parsed(row, 3, |text| {
+            PromotionType::ALL
+                .into_iter()
+                .find(|kind| kind.as_str() == text)
+        })?,
+        rule: row.get(4)?,
+        action: parsed(row, 5, AuditAction::parse)?,
+        old_end_date: row.get::<_, Option<i64>>(6)?.map(from_timestamp),
+        new_end_date: row.get::<_, Option<i64>>(7)?.map(from_timestamp),
+        recorded_at: from_timestamp(row.get(8)?),
+        error: row.get(9)?,
+    })
+}
@@ -0,0 +1,444 @@
+//! Promotion calendar with overlap and gap detection.
+//!
+//! `filterExpiringDiscounts` only asked which discounts end within
+//! `ALERT_WINDOW_HOURS`. The calendar holds every live promotion with its
+//! dates and the listings it covers, resolved from `inventoryCriterion` and
+//! `selectedInventoryDiscounts` against the seller's catalog:
+//!
+//! - listing IDs and SKUs (`INVENTORY_BY_VALUE`) are matched directly;
+//!   listing IDs that are not in the catalog are left out;
+//! - selection rules (`INVENTORY_BY_RULE`) are checked against each
+//!   listing's Category ID, Brand, Condition ID and Current Price, minus the
+//!   exclusions. A price bound only matches listings in its currency;
+//! - `INVENTORY_ANY` covers every listing.
+//!
+//! From that it finds listings under two markdowns at once, and listings
+//! whose discounts run out. Paused promotions are shown but do not count as
+//! covering anything.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::client::{MarketingClient, PromotionFilter};
+use super::types::{
+    Amount, DiscountBenefit, InventoryCriterion, InventoryCriterionType, Promotion,
+    PromotionStatus, PromotionType, SelectionRule,
+};
+use crate::error::Result;
+use crate::internal::ebay::listing_rows::ListingRow;
+
+/// One promotion on the calendar.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarPromotion {
+    pub promotion_id: String,
+    pub name: String,
+    pub promotion_type: PromotionType,
+    pub status: PromotionStatus,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub priority: Option<String>,
+    /// e.g. "20% off" or "5.00 USD off order".
+    pub discount: Option<String>,
+    /// Covered listings, by Item ID.
+    pub item_ids: BTreeSet<String>,
+}
+
+impl CalendarPromotion {
+    /// Whether the promotion discounts anything between `from` and `to`.
+    fn covers_during(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+        self.status != PromotionStatus::Paused && self.start_date < to && from < self.end_date
+    }
+}
+
+/// Listings two markdowns discount at the same time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkdownOverlap {
+    pub promotion_ids: [String; 2],
+    pub names: [String; 2],
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub item_ids: Vec<String>,
+}
+
+/// A listing without any discount from `from` until `until` (open-ended if
+/// no later promotion picks it up).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageGap {
+    pub item_id: String,
+    pub sku: String,
+    pub title: String,
+    pub from: DateTime<Utc>,
+    pub until: Option<DateTime<Utc>>,
+    /// The promotion whose end opens the gap, if one does.
+    pub last_promotion_id: Option<String>,
+}
+
+/// What the front end draws for a date range.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarView {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub promotions: Vec<CalendarPromotion>,
+    pub overlaps: Vec<MarkdownOverlap>,
+    pub gaps: Vec<CoverageGap>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PromotionCalendar {
+    promotions: Vec<CalendarPromotion>,
+    /// Item ID to (SKU, Title) for every catalog listing.
+    listings: BTreeMap<String, (String, String)>,
+}
+
+impl PromotionCalendar {
+    /// Build the calendar from promotions (with their IDs) and the listings
+    /// to check coverage for, e.g. the active ones in the warehouse.
+    pub fn new(promotions: Vec<(String, Promotion)>, listings: &[ListingRow]) -> Self {
+        let catalog: Vec<CatalogListing<'_>> = listings
+            .iter()
+            .filter(|row| !row.item_id().is_empty())
+            .map(CatalogListing::new)
+            .collect();
+
+        let mut promotions: Vec<CalendarPromotion> = promotions
+            .into_iter()
+            .map(|(promotion_id, promotion)| CalendarPromotion {
+                item_ids: covered_items(&promotion, &catalog),
+                discount: discount_label(&promotion),
+                priority: match &promotion {
+                    Promotion::Markdown(p) => p.priority.clone(),
+                    Promotion::Item(p) => p.priority.clone(),
+                },
+                name: promotion.name().to_string(),
+                promotion_type: promotion.promotion_type(),
+                status: promotion.status(),
+                start_date: promotion.start_date(),
+                end_date: promotion.end_date(),
+                promotion_id,
+            })
+            .collect();
+        promotions.sort_by(|a, b| {
+            (a.start_date, a.end_date, &a.promotion_id).cmp(&(
+                b.start_date,
+                b.end_date,
+                &b.promotion_id,
+            ))
+        });
+
+        Self {
+            promotions,
+            listings: catalog
+                .iter()
+                .map(|listing| {
+                    (
+                        listing.item_id.to_string(),
+                        (listing.sku.to_string(), listing.title.to_string()),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Read every scheduled, running and paused promotion in full and build
+    /// the calendar. A promotion that cannot be read is left out.
+    pub async fn load(client: &MarketingClient, listings: &[ListingRow]) -> Result<Self> {
+        let mut promotions = Vec::new();
+        for detail in client.promotions(&PromotionFilter::default()).await? {
+            if !matches!(
+                detail.promotion_status,
+                PromotionStatus::Running | PromotionStatus::Scheduled | PromotionStatus::Paused
+            ) {
+                continue;
+            }
+            match client
+                .get(&detail.promotion_id, detail.promotion_type)
+                .await
+            {
+                Ok(promotion) => promotions.push((detail.promotion_id, promotion)),
+                Err(err) => log::warn!("Could not read promotion {}: {err}", detail.promotion_id),
+            }
+        }
+        log::info!("Loaded {} promotions for the calendar", promotions.len());
+        Ok(Self::new(promotions, listings))
+    }
+
+    /// Promotions ordered by start date.
+    pub fn promotions(&self) -> &[CalendarPromotion] {
+        &self.promotions
+    }
+
+    /// Promotions covering `item_id`, ordered by start date.
+    pub fn promotions_for(&self, item_id: &str) -> Vec<&CalendarPromotion> {
+        self.promotions
+            .iter()
+            .filter(|promotion| promotion.item_ids.contains(item_id))
+            .collect()
+    }
+
+    /// Pairs of markdowns that discount the same listings at the same time,
+    /// limited to those overlapping `from..to`.
+    pub fn overlaps(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<MarkdownOverlap> {
+        let markdowns: Vec<&CalendarPromotion> = self
+            .promotions
+            .iter()
+            .filter(|p| p.promotion_type == PromotionType::MarkdownSale)
+            .filter(|p| p.covers_during(from, to))
+            .collect();
+
+        let mut overlaps = Vec::new();
+        for (i, a) in markdowns.iter().enumerate() {
+            for b in &markdowns[i + 1..] {
+                let start = a.start_date.max(b.start_date).max(from);
+                let end = a.end_date.min(b.end_date).min(to);
+                if start >= end {
+                    continue;
+                }
+                let item_ids: Vec<String> = a.item_ids.intersection(&b.item_ids).cloned().collect();
+                if item_ids.is_empty() {
+                    continue;
+                }
+                overlaps.push(MarkdownOverlap {
+                    promotion_ids: [a.promotion_id.clone(), b.promotion_id.clone()],
+                    names: [a.name.clone(), b.name.clone()],
+                    from: start,
+                    to: end,
+                    item_ids,
+                });
+            }
+        }
+        overlaps
+    }
+
+    /// For every catalog listing, the first stretch from `after` on with no
+    /// discount running. A listing whose promotions end before `after`, or
+    /// that has none, is uncovered from `after`; a gap with no `until` is
+    /// open-ended.
+    pub fn gaps_after(&self, after: DateTime<Utc>) -> Vec<CoverageGap> {
+        let mut spans: HashMap<&str, Vec<Span<'_>>> = HashMap::new();
+        for promotion in &self.promotions {
+            if promotion.status == PromotionStatus::Paused || promotion.end_date <= after {
+                continue;
+            }
+            for item_id in &promotion.item_ids {
+                spans.entry(item_id.as_str()).or_default().push((
+                    promotion.start_date,
+                    promotion.end_date,
+                    &promotion.promotion_id,
+                ));
+            }
+        }
+
+        let mut gaps = Vec::new();
+        for (item_id, (sku, title)) in &self.listings {
+            let mut spans = spans.remove(item_id.as_str()).unwrap_or_default();
+            spans.sort();
+
+            // Walk forward from `after` through spans that keep the listing
+            // covered; the first hole is the gap.
+            let mut covered_until = after;
+            let mut last_promotion = None;
+            let mut until = None;
+            for (start, end, promotion_id) in spans {
+                if start > covered_until {
+                    until = Some(start);
+                    break;
+                }
+                if end > covered_until {
+                    covered_until = end;
+                    last_promotion = Some(promotion_id.to_string());
+                }
+            }
+            gaps.push(CoverageGap {
+                item_id: item_id.clone(),
+                sku: sku.clone(),
+                title: title.clone(),
+                from: covered_until,
+                until,
+                last_promotion_id: last_promotion,
+            });
+        }
+        gaps.sort_by(|a, b| (a.from, &a.item_id).cmp(&(b.from, &b.item_id)));
+        gaps
+    }
+
+    /// Listings with no discount at all once `date` has passed.
+    pub fn uncovered_after(&self, date: DateTime<Utc>) -> Vec<CoverageGap> {
+        self.gaps_after(date)
+            .into_iter()
+            .filter(|gap| gap.until.is_none())
+            .collect()
+    }
+
+    /// Promotions running at some point in `from..to`, with the overlaps in
+    /// that range and the gaps that open before `to`.
+    pub fn view(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> CalendarView {
+        CalendarView {
+            from,
+            to,
+            promotions: self
+                .promotions
+                .iter()
+                .filter(|p| p.start_date < to && from < p.end_date)
+                .cloned()
+                .collect(),
+            overlaps: self.overlaps(from, to),
+            gaps: self
+                .gaps_after(from)
+                .into_iter()
+                .filter(|gap| gap.from < to)
+                .collect(),
+        }
+    }
+}
+
+/// When one promotion covers a listing: start, end and promotion ID.
+type Span<'a> = (DateTime<Utc>, DateTime<Utc>, &'a str);
+
+/// The columns of a listing that inventory criteria look at.
+struct CatalogListing<'a> {
+    item_id: &'a str,
+    sku: String,
+    title: String,
+    category_ids: [String; 2],
+    brand: String,
+    condition_id: String,
+    price: Option<f64>,
+    currency: String,
+}
+
+impl<'a> CatalogListing<'a> {
+    fn new(row: &'a ListingRow) -> Self {
+        let text = |column: &str| row.get(column).to_string();
+        Self {
+            item_id: row.item_id(),
+            sku: text("SKU"),
+            title: text("Title"),
+            category_ids: [text("Category ID"), text("Secondary Category ID")],
+            brand: text("Brand"),
+            condition_id: text("Condition ID"),
+            price: row.get("Current Price").as_f64(),
+            currency: text("Currency"),
+        }
+    }
+
+    fn matches_rule(&self, rule: &SelectionRule) -> bool {
+        (rule.category_ids.is_empty()
+            || self
+                .category_ids
+                .iter()
+                .any(|id| !id.is_empty() && rule.category_ids.contains(id)))
+            && (rule.brands.is_empty()
+                || rule
+                    .brands
+                    .iter()
+                    .any(|brand| brand.eq_ignore_ascii_case(&self.brand)))
+            && (rule.listing_condition_ids.is_empty()
+                || rule.listing_condition_ids.contains(&self.condition_id))
+            && self.within(rule.min_price.as_ref(), |price, min| price >= min)
+            && self.within(rule.max_price.as_ref(), |price, max| price <= max)
+    }
+
+    /// Whether the price passes `bound`. A price in another currency cannot
+    /// be compared, so it does not; a bound that is not a number is ignored.
+    fn within(&self, bound: Option<&Amount>, check: impl Fn(f64, f64) -> bool) -> bool {
+        let Some(amount) = bound else {
+            return true;
+        };
+        let Ok(limit) = amount.value.parse::<f64>() else {
+            return true;
+        };
+        amount.currency.eq_ignore_ascii_case(&self.currency)
+            && self.price.is_some_and(|price| check(price, limit))
+    }
+}
+
+fn covered_items(promotion: &Promotion, catalog: &[CatalogListing<'_>]) -> BTreeSet<String> {
+    let mut items = BTreeSet::new();
+    for criterion in promotion.inventory_criteria() {
+        items.extend(criterion_items(criterion, catalog));
+    }
+    items
+}
+
+fn criterion_items(criterion: &InventoryCriterion, catalog: &[CatalogListing<'_>]) -> Vec<String> {
+    let skus: BTreeSet<&str> = criterion
+        .inventory_items
+        .iter()
+        .map(|item| item.inventory_reference_id.as_str())
+        .collect();
+
+    match criterion.inventory_criterion_type {
+        InventoryCriterionType::InventoryAny => catalog
+            .iter()
+            .map(|listing| listing.item_id.to_string())
+            .collect(),
+        InventoryCriterionType::InventoryByValue => catalog
+            .iter()
+            .filter(|listing| {
+                criterion.listing_ids.iter().any(|id| id == listing.item_id)
+                    || (!listing.sku.is_empty() && skus.contains(listing.sku.as_str()))
+            })
+            .map(|listing| listing.item_id.to_string())
+            .collect(),
+        InventoryCriterionType::InventoryByRule => {
+            let Some(rules) = &criterion.rule_criteria else {
+                return Vec::new();
+            };
+            let excluded_skus: BTreeSet<&str> = rules
+                .exclude_inventory_items
+                .iter()
+                .map(|item| item.inventory_reference_id.as_str())
+                .collect();
+            catalog
+                .iter()
+                .filter(|listing| {
+                    rules
+                        .selection_rules
+                        .iter()
+                        .any(|rule| listing.matches_rule(rule))
+                })
+                .filter(|listing| {
+                    !rules
+                        .exclude_listing_ids
+                        .iter()
+                        .any(|id| id == listing.item_id)
+                        && !excluded_skus.contains(listing.sku.as_str())
+                })
+                .map(|listing| listing.item_id.to_string())
+                .collect()
+        }
+    }
+}
+
+/// Short description of the first discount tier.
+fn discount_label(promotion: &Promotion) -> Option<String> {
+    let benefit = match promotion {
+        Promotion::Markdown(p) => p
+            .selected_inventory_discounts
+            .first()
+            .map(|d| &d.discount_benefit),
+        Promotion::Item(p) => p.discount_rules.first().map(|r| &r.discount_benefit),
+    }?;
+    benefit_label(benefit)
+}
+
+fn benefit_label(benefit: &DiscountBenefit) -> Option<String> {
+    if let Some(percent) = &benefit.percentage_off_item {
+        Some(format!("{percent}% off"))
+    } else if let Some(percent) = &benefit.percentage_off_order {
+        Some(format!("{percent}% off order"))
+    } else if let Some(amount) = &benefit.amount_off_item {
+        Some(format!("{} {} off", amount.value, amount.currency))
+    } else {
+        benefit
+            .amount_off_order
+            .as_ref()
+            .map(|amount| format!("{} {} off order", amount.value, amount.currency))
+    }
+}
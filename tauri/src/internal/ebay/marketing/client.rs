@@ -0,0 +1,471 @@
+//! Marketing API (REST) client.
+//!
+//! Port of `MarketingApiClient` from `discount-manager.js`, extended from
+//! get/update of `item_promotion` and `item_price_markdown` to the full
+//! promotion lifecycle: create, get, update and delete for every
+//! [`PromotionType`], and pause and resume for the threshold promotions that
+//! support them. `getPromotions` follows the `next` link eBay
+//! returns instead of guessing from `discounts.length === limit` the way
+//! `fetchAllDiscounts` does. The promotion reports are read the same way.
+
+use std::sync::Arc;
+
+use reqwest::header::LOCATION;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use url::Url;
+
+use super::types::{
+    ErrorResponse, ItemPriceMarkdown, ItemPromotion, Promotion, PromotionDetail,
+    PromotionReportDetail, PromotionReportsPage, PromotionStatus, PromotionType, PromotionsPage,
+    SummaryReport,
+};
+use crate::accounts::Account;
+use crate::adapters::ebay::oauth::{MARKETING_READ_SCOPE, MARKETING_SCOPE};
+use crate::adapters::ebay::rate_limit::{Api, Throttle};
+use crate::adapters::ebay::retry::{self, Retrier};
+use crate::adapters::ebay::token_provider::{send_authorized, TokenSource};
+use crate::environment::Environment;
+use crate::error::{Error, Result};
+
+pub const API_PATH: &str = "/sell/marketing/v1/";
+
+/// Largest page `getPromotions` returns.
+pub const PAGE_LIMIT: u32 = 200;
+
+/// Circuit name for the retrier.
+const ENDPOINT: &str = "sell.marketing";
+
+/// Narrows `getPromotions`; every field is optional.
+#[derive(Debug, Clone, Default)]
+pub struct PromotionFilter {
+    pub status: Option<PromotionStatus>,
+    pub promotion_type: Option<PromotionType>,
+    /// Matches promotion names.
+    pub q: Option<String>,
+}
+
+/// A response body and the `Location` header, which is all `create` gets
+/// back.
+struct Reply {
+    location: Option<String>,
+    body: String,
+}
+
+#[derive(Clone)]
+pub struct MarketingClient {
+    http: reqwest::Client,
+    api_url: Url,
+    marketplace_id: String,
+    tokens: Arc<dyn TokenSource>,
+    throttle: Option<Throttle>,
+    retrier: Option<Arc<Retrier>>,
+    /// Only `sell.marketing.readonly` was granted: changes are refused.
+    read_only: bool,
+}
+
+impl std::fmt::Debug for MarketingClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MarketingClient")
+            .field("api_url", &self.api_url.as_str())
+            .field("marketplace_id", &self.marketplace_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MarketingClient {
+    pub fn new(
+        environment: Environment,
+        marketplace_id: impl Into<String>,
+        tokens: Arc<dyn TokenSource>,
+    ) -> Self {
+        let api_url = environment
+            .api_base_url()
+            .join(API_PATH)
+            .expect("static URL");
+
+        Self {
+            http: reqwest::Client::new(),
+            api_url,
+            marketplace_id: marketplace_id.into(),
+            tokens,
+            throttle: None,
+            retrier: None,
+            read_only: false,
+        }
+    }
+
+    /// Client using the account's user token. Fails if the account's grant
+    /// has neither Marketing scope, which would otherwise surface as a 403
+    /// on the first call; with only the read-only one, changes are refused.
+    pub fn for_account(account: &Account) -> Result<Self> {
+        let tokens = account.stored_tokens()?.ok_or_else(|| {
+            Error::NotAuthorized(format!(
+                "{} has no tokens stored, run the authorization flow first",
+                account.name()
+            ))
+        })?;
+        // The code grant does not always echo scopes back; the ones asked
+        // for were granted then.
+        let granted = if tokens.scopes.is_empty() {
+            &account.config().scopes
+        } else {
+            &tokens.scopes
+        };
+        let has = |scope: &str| granted.iter().any(|granted| granted == scope);
+        let read_only = if has(MARKETING_SCOPE) {
+            false
+        } else if has(MARKETING_READ_SCOPE) {
+            true
+        } else {
+            return Err(Error::NotAuthorized(format!(
+                "{} was not granted {MARKETING_SCOPE}, re-authorize the account to manage promotions",
+                account.name()
+            )));
+        };
+
+        let mut client = Self::new(
+            account.environment(),
+            account.marketplace_id(),
+            account.user_tokens(),
+        )
+        .with_throttle(account.user_throttle(Api::Marketing))
+        .with_retrier(account.retrier());
+        client.read_only = read_only;
+        Ok(client)
+    }
+
+    /// Override the API host (used by tests).
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.api_url = base_url.join(API_PATH).expect("static path");
+        self
+    }
+
+    /// Wait on `throttle` before every request.
+    pub fn with_throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Retry failed calls under `retrier`'s policy. Creates are never
+    /// retried, so a timeout cannot leave a duplicate promotion behind.
+    pub fn with_retrier(mut self, retrier: Arc<Retrier>) -> Self {
+        self.retrier = Some(retrier);
+        self
+    }
+
+    /// Marketplace promotions are listed for, e.g. `EBAY_US`.
+    pub fn marketplace_id(&self) -> &str {
+        &self.marketplace_id
+    }
+
+    /// `createItemPromotion` / `createItemPriceMarkdownPromotion`. Returns
+    /// the new promotion's ID.
+    pub async fn create(&self, promotion: &Promotion) -> Result<String> {
+        let url = self.url(&[promotion.promotion_type().resource()]);
+        let body = serde_json::to_string(&promotion.writable())?;
+        log::info!(
+            "Creating {} promotion {:?}",
+            promotion.promotion_type(),
+            promotion.name()
+        );
+
+        let reply = self.send_once(Method::POST, &url, Some(&body)).await?;
+        created_id(&reply).ok_or_else(|| {
+            Error::InvalidResponse(format!(
+                "Promotion {:?} was created but eBay did not return its ID",
+                promotion.name()
+            ))
+        })
+    }
+
+    /// Read a promotion of a known type from its resource.
+    pub async fn get(&self, promotion_id: &str, kind: PromotionType) -> Result<Promotion> {
+        match kind {
+            PromotionType::MarkdownSale => Ok(self.item_price_markdown(promotion_id).await?.into()),
+            _ => Ok(self.item_promotion(promotion_id).await?.into()),
+        }
+    }
+
+    /// `getItemPromotion`.
+    pub async fn item_promotion(&self, promotion_id: &str) -> Result<ItemPromotion> {
+        let url = self.url(&["item_promotion", promotion_id]);
+        self.get_json(&url).await
+    }
+
+    /// `getItemPriceMarkdownPromotion`.
+    pub async fn item_price_markdown(&self, promotion_id: &str) -> Result<ItemPriceMarkdown> {
+        let url = self.url(&["item_price_markdown", promotion_id]);
+        self.get_json(&url).await
+    }
+
+    /// `updateItemPromotion` / `updateItemPriceMarkdownPromotion`: replace
+    /// the promotion with `promotion`. Only the fields set on `promotion`
+    /// are sent, so read it first and change what you need.
+    pub async fn update(&self, promotion_id: &str, promotion: &Promotion) -> Result<()> {
+        let body = serde_json::to_string(&promotion.writable())?;
+        self.put(promotion_id, promotion.promotion_type(), &body)
+            .await
+    }
+
+    /// Send `body` as the update of a promotion unchanged, e.g. the body
+    /// stored in a [`super::plan::PlannedChange`].
+    pub async fn update_with_body(
+        &self,
+        promotion_id: &str,
+        kind: PromotionType,
+        body: &serde_json::Value,
+    ) -> Result<()> {
+        self.put(promotion_id, kind, &body.to_string()).await
+    }
+
+    /// `deleteItemPromotion` / `deleteItemPriceMarkdownPromotion`.
+    pub async fn delete(&self, promotion_id: &str, kind: PromotionType) -> Result<()> {
+        let url = self.url(&[kind.resource(), promotion_id]);
+        log::info!("Deleting promotion {promotion_id}");
+        self.send(Method::DELETE, &url, None).await?;
+        Ok(())
+    }
+
+    /// `pausePromotion`: RUNNING to PAUSED. eBay only pauses threshold
+    /// promotions, so a markdown sale is refused without a call.
+    pub async fn pause(&self, promotion_id: &str, kind: PromotionType) -> Result<()> {
+        ensure_pausable(promotion_id, kind)?;
+        let url = self.url(&["promotion", promotion_id, "pause"]);
+        log::info!("Pausing promotion {promotion_id}");
+        self.send(Method::POST, &url, None).await?;
+        Ok(())
+    }
+
+    /// `resumePromotion`: PAUSED back to RUNNING. Like [`Self::pause`], only
+    /// for threshold promotions.
+    pub async fn resume(&self, promotion_id: &str, kind: PromotionType) -> Result<()> {
+        ensure_pausable(promotion_id, kind)?;
+        let url = self.url(&["promotion", promotion_id, "resume"]);
+        log::info!("Resuming promotion {promotion_id}");
+        self.send(Method::POST, &url, None).await?;
+        Ok(())
+    }
+
+    /// Every promotion on the marketplace that matches `filter`, following
+    /// `next` until eBay stops returning one.
+    pub async fn promotions(&self, filter: &PromotionFilter) -> Result<Vec<PromotionDetail>> {
+        log::info!("Fetching all discounts...");
+        let mut all = Vec::new();
+        let mut next = Some(self.promotions_url(filter));
+
+        while let Some(url) = next.take() {
+            let page = self.promotion_page(&url).await?;
+            log::info!(
+                "Fetched {} discounts (offset {})",
+                page.promotions.len(),
+                page.offset.unwrap_or(0)
+            );
+            next = next_page(&url, page.next, page.promotions.is_empty())?;
+            all.extend(page.promotions);
+        }
+
+        Ok(all)
+    }
+
+    /// One page of `getPromotions`, e.g. a `next` link from the previous one.
+    pub async fn promotion_page(&self, url: &Url) -> Result<PromotionsPage> {
+        self.get_json(url).await
+    }
+
+    /// `getPromotionReports`: sales to date of every promotion that matches
+    /// `filter`, following `next` like [`Self::promotions`].
+    pub async fn promotion_reports(
+        &self,
+        filter: &PromotionFilter,
+    ) -> Result<Vec<PromotionReportDetail>> {
+        log::info!("Fetching promotion reports...");
+        let mut all = Vec::new();
+        let mut next = Some(self.filtered_url("promotion_report", filter));
+
+        while let Some(url) = next.take() {
+            let page: PromotionReportsPage = self.get_json(&url).await?;
+            log::info!(
+                "Fetched {} promotion reports (offset {})",
+                page.promotion_reports.len(),
+                page.offset.unwrap_or(0)
+            );
+            next = next_page(&url, page.next, page.promotion_reports.is_empty())?;
+            all.extend(page.promotion_reports);
+        }
+
+        Ok(all)
+    }
+
+    /// `getPromotionSummaryReport`: sales to date of all promotions on the
+    /// marketplace.
+    pub async fn promotion_summary_report(&self) -> Result<SummaryReport> {
+        let mut url = self.url(&["promotion_summary_report"]);
+        url.query_pairs_mut()
+            .append_pair("marketplace_id", &self.marketplace_id);
+        self.get_json(&url).await
+    }
+
+    fn promotions_url(&self, filter: &PromotionFilter) -> Url {
+        self.filtered_url("promotion", filter)
+    }
+
+    fn filtered_url(&self, resource: &str, filter: &PromotionFilter) -> Url {
+        let mut url = self.url(&[resource]);
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("marketplace_id", &self.marketplace_id)
+                .append_pair("limit", &PAGE_LIMIT.to_string());
+            if let Some(status) = filter.status {
+                query.append_pair("promotion_status", status.as_str());
+            }
+            if let Some(kind) = filter.promotion_type {
+                query.append_pair("promotion_type", kind.as_str());
+            }
+            if let Some(q) = &filter.q {
+                query.append_pair("q", q);
+            }
+        }
+        url
+    }
+
+    async fn put(&self, promotion_id: &str, kind: PromotionType, body: &str) -> Result<()> {
+        let url = self.url(&[kind.resource(), promotion_id]);
+        log::info!("Updating promotion {promotion_id}");
+        self.send(Method::PUT, &url, Some(body)).await?;
+        Ok(())
+    }
+
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.api_url.clone();
+        url.path_segments_mut()
+            .expect("API URL has a path")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &Url) -> Result<T> {
+        let reply = self.send(Method::GET, url, None).await?;
+        Ok(serde_json::from_str(&reply.body)?)
+    }
+
+    async fn send(&self, method: Method, url: &Url, body: Option<&str>) -> Result<Reply> {
+        match &self.retrier {
+            Some(retrier) => {
+                retrier
+                    .run(ENDPOINT, || self.send_once(method.clone(), url, body))
+                    .await
+            }
+            None => self.send_once(method, url, body).await,
+        }
+    }
+
+    async fn send_once(&self, method: Method, url: &Url, body: Option<&str>) -> Result<Reply> {
+        if self.read_only && method != Method::GET {
+            return Err(Error::NotAuthorized(format!(
+                "only {MARKETING_READ_SCOPE} was granted, re-authorize the account to change promotions"
+            )));
+        }
+        if let Some(throttle) = &self.throttle {
+            throttle.acquire().await?;
+        }
+
+        let response = send_authorized(self.tokens.as_ref(), |token| {
+            let request = self
+                .http
+                .request(method.clone(), url.clone())
+                .bearer_auth(token)
+                .header("Accept", "application/json")
+                .header("X-EBAY-C-MARKETPLACE-ID", &self.marketplace_id);
+            match body {
+                Some(body) => request
+                    .header("Content-Type", "application/json")
+                    .body(body.to_string()),
+                None => request,
+            }
+        })
+        .await?;
+
+        let status = response.status();
+        let retry_after = retry::retry_after(response.headers());
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(Error::Api {
+                status: status.as_u16(),
+                message: error_message(&body),
+                retry_after,
+            });
+        }
+        Ok(Reply { location, body })
+    }
+}
+
+/// Markdown sales cannot be paused; end or delete them instead.
+fn ensure_pausable(promotion_id: &str, kind: PromotionType) -> Result<()> {
+    if kind == PromotionType::MarkdownSale {
+        return Err(Error::Promotion(format!(
+            "promotion {promotion_id} is a markdown sale, which eBay cannot pause or resume"
+        )));
+    }
+    Ok(())
+}
+
+/// The page after `url`, or `None` once eBay stops returning a `next` link,
+/// returns an empty page or links back to the same page.
+fn next_page(url: &Url, next: Option<String>, empty: bool) -> Result<Option<Url>> {
+    match next {
+        Some(href) if !empty => {
+            let href = url.join(&href)?;
+            Ok((href != *url).then_some(href))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// `errors[].message` joined with "; ", or the raw body if it has none.
+fn error_message(body: &str) -> String {
+    let errors = serde_json::from_str::<ErrorResponse>(body)
+        .map(|response| response.errors)
+        .unwrap_or_default();
+    let messages: Vec<&str> = errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .filter(|message| !message.is_empty())
+        .collect();
+    if messages.is_empty() {
+        body.to_string()
+    } else {
+        messages.join("; ")
+    }
+}
+
+/// ID of a created promotion: the last segment of the `Location` header, or
+/// of `href` / `promotionId` in the body when the header is missing.
+fn created_id(reply: &Reply) -> Option<String> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Created {
+        promotion_id: Option<String>,
+        href: Option<String>,
+    }
+
+    let created: Option<Created> = serde_json::from_str(&reply.body).ok();
+    let href = reply
+        .location
+        .clone()
+        .or_else(|| created.as_ref().and_then(|c| c.href.clone()));
+    href.as_deref()
+        .and_then(|href| href.trim_end_matches('/').rsplit('/').next())
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .or_else(|| created.and_then(|c| c.promotion_id))
+}
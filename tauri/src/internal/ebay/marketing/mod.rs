@@ -0,0 +1,25 @@
+//! Typed Marketing API client for seller promotions, and the tools built on
+//! it: reviewable change plans, rule-driven renewal with an audit trail, a
+//! calendar of overlapping markdowns and coverage gaps, and sales reports per
+//! promotion.
+
+pub mod audit;
+pub mod calendar;
+pub mod client;
+pub mod plan;
+pub mod renewal;
+pub mod report;
+pub mod types;
+
+pub use audit::{AuditEntry, PromotionAudit};
+pub use calendar::{
+    CalendarPromotion, CalendarView, CoverageGap, MarkdownOverlap, PromotionCalendar,
+};
+pub use client::{MarketingClient, PromotionFilter};
+pub use plan::{PlannedChange, PromotionPlan};
+pub use renewal::{RenewalAction, RenewalEngine, RenewalRule, RenewalRules};
+pub use report::{PromotionPerformance, PromotionReports, SummaryTotals};
+pub use types::{
+    ItemPriceMarkdown, ItemPromotion, Promotion, PromotionDetail, PromotionReportDetail,
+    PromotionStatus, PromotionType, SummaryReport,
+};
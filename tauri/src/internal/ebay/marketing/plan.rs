@@ -0,0 +1,319 @@
+//! Reviewable plans of promotion changes.
+//!
+//! `extendDiscountEndDate` sent its PUT straight away, with
+//! `promotionStatus: 'SCHEDULED'` forced in, which can quietly reset a
+//! running sale. Changes are planned first instead: each [`PlannedChange`]
+//! holds the exact body that will be sent, the promotion as it was when the
+//! plan was made, and a field-by-field diff between the two. A plan is plain
+//! JSON, so it can be saved, shown in the UI and applied later.
+//!
+//! [`PromotionPlan::apply`] reads every promotion again first. If any of them
+//! no longer matches what the plan was made from, nothing is sent.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::audit::{AuditAction, PromotionAudit};
+use super::client::{MarketingClient, API_PATH};
+use super::types::{Promotion, PromotionStatus, PromotionType};
+use crate::adapters::ebay::vault::write_atomic;
+use crate::error::{Error, Result};
+
+/// One changed field. `field` is a path into the promotion JSON, e.g.
+/// `selectedInventoryDiscounts[0].discountBenefit.percentageOffItem`; a
+/// missing side means the field is absent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// The update of one promotion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedChange {
+    pub promotion_id: String,
+    pub promotion_type: PromotionType,
+    pub promotion_name: String,
+    /// Why the change is planned, e.g. the renewal rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub method: String,
+    pub path: String,
+    /// Request body, sent as is.
+    pub body: Value,
+    /// The promotion's writable fields when the plan was made.
+    pub current: Value,
+    pub diff: Vec<FieldChange>,
+    /// Side effects worth a second look, e.g. a status change.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl PlannedChange {
+    pub fn new(
+        promotion_id: impl Into<String>,
+        current: &Promotion,
+        updated: &Promotion,
+        reason: Option<String>,
+    ) -> Result<Self> {
+        let promotion_id = promotion_id.into();
+        let promotion_type = current.promotion_type();
+        if updated.promotion_type() != promotion_type {
+            return Err(Error::Plan(format!(
+                "promotion {promotion_id} cannot change from {promotion_type} to {}",
+                updated.promotion_type()
+            )));
+        }
+
+        let before = serde_json::to_value(current.writable())?;
+        let body = serde_json::to_value(updated.writable())?;
+        let diff = diff(&before, &body);
+
+        let mut warnings = Vec::new();
+        if current.status() != updated.status() {
+            warnings.push(format!(
+                "changes status from {} to {}",
+                current.status(),
+                updated.status()
+            ));
+        }
+        if current.status() == PromotionStatus::Running
+            && current.start_date() != updated.start_date()
+        {
+            warnings.push("moves the start date of a running promotion".to_string());
+        }
+
+        Ok(Self {
+            path: format!("{API_PATH}{}/{promotion_id}", promotion_type.resource()),
+            promotion_id,
+            promotion_type,
+            promotion_name: current.name().to_string(),
+            reason,
+            method: "PUT".to_string(),
+            body,
+            current: before,
+            diff,
+            warnings,
+        })
+    }
+
+    /// Whether the change only moves the end date later.
+    fn is_extension(&self) -> bool {
+        match self.diff.as_slice() {
+            [change] if change.field == "endDate" => end_date(&self.body) > end_date(&self.current),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromotionPlan {
+    pub created_at: DateTime<Utc>,
+    pub marketplace_id: String,
+    pub changes: Vec<PlannedChange>,
+}
+
+impl PromotionPlan {
+    pub fn new(marketplace_id: impl Into<String>) -> Self {
+        Self {
+            created_at: Utc::now(),
+            marketplace_id: marketplace_id.into(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Plan replacing `current` with `updated`. Returns false, and plans
+    /// nothing, if the two are the same.
+    pub fn add(
+        &mut self,
+        promotion_id: &str,
+        current: &Promotion,
+        updated: &Promotion,
+        reason: Option<String>,
+    ) -> Result<bool> {
+        let change = PlannedChange::new(promotion_id, current, updated, reason)?;
+        if change.diff.is_empty() {
+            return Ok(false);
+        }
+        self.changes
+            .retain(|planned| planned.promotion_id != change.promotion_id);
+        self.changes.push(change);
+        Ok(true)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_atomic(path.as_ref(), self.to_json()?.as_bytes())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Check every promotion against the plan, then send the changes in
+    /// order. Fails without sending anything if a promotion changed since
+    /// the plan was made; a change eBay rejects is reported and the rest are
+    /// still sent. Every change sent is written to `audit`.
+    pub async fn apply(
+        &self,
+        client: &MarketingClient,
+        audit: &PromotionAudit,
+    ) -> Result<ApplyReport> {
+        if client.marketplace_id() != self.marketplace_id {
+            return Err(Error::Plan(format!(
+                "plan is for {}, client is for {}",
+                self.marketplace_id,
+                client.marketplace_id()
+            )));
+        }
+
+        let mut promotions = Vec::with_capacity(self.changes.len());
+        let mut conflicts = Vec::new();
+        for change in &self.changes {
+            let promotion = client
+                .get(&change.promotion_id, change.promotion_type)
+                .await?;
+            let now = serde_json::to_value(promotion.writable())?;
+            if now != change.current {
+                let fields: Vec<String> = diff(&change.current, &now)
+                    .into_iter()
+                    .map(|field| field.field)
+                    .collect();
+                conflicts.push(format!("{} ({})", change.promotion_id, fields.join(", ")));
+            }
+            promotions.push(promotion);
+        }
+        if !conflicts.is_empty() {
+            return Err(Error::Plan(format!(
+                "promotions changed since the plan was made: {}",
+                conflicts.join("; ")
+            )));
+        }
+
+        let mut report = ApplyReport {
+            applied_at: Utc::now(),
+            results: Vec::with_capacity(self.changes.len()),
+        };
+        for (change, promotion) in self.changes.iter().zip(&promotions) {
+            let id = change.promotion_id.as_str();
+            let sent = client
+                .update_with_body(id, change.promotion_type, &change.body)
+                .await;
+            let action = match (&sent, change.is_extension()) {
+                (Err(_), _) => AuditAction::Failed,
+                (Ok(()), true) => AuditAction::Extended,
+                (Ok(()), false) => AuditAction::Updated,
+            };
+            let error = sent.err().map(|err| err.to_string());
+            match &error {
+                Some(error) => log::error!("Failed to update promotion {id}: {error}"),
+                None => log::info!("Applied planned change to promotion {id}"),
+            }
+            audit.record(
+                id,
+                promotion,
+                change.reason.as_deref(),
+                action,
+                end_date(&change.body),
+                error.as_deref(),
+            )?;
+            report.results.push(AppliedChange {
+                promotion_id: id.to_string(),
+                action,
+                error,
+            });
+        }
+        Ok(report)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedChange {
+    pub promotion_id: String,
+    pub action: AuditAction,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyReport {
+    pub applied_at: DateTime<Utc>,
+    pub results: Vec<AppliedChange>,
+}
+
+impl ApplyReport {
+    pub fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.error.is_some())
+            .count()
+    }
+}
+
+/// Every leaf that differs between two JSON values. Object keys are walked
+/// in alphabetical order and array elements by index.
+pub fn diff(before: &Value, after: &Value) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    diff_into(String::new(), Some(before), Some(after), &mut changes);
+    changes
+}
+
+fn diff_into(
+    path: String,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    changes: &mut Vec<FieldChange>,
+) {
+    match (before, after) {
+        (Some(Value::Object(a)), Some(Value::Object(b))) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff_into(path, a.get(key), b.get(key), changes);
+            }
+        }
+        (Some(Value::Array(a)), Some(Value::Array(b))) => {
+            for i in 0..a.len().max(b.len()) {
+                diff_into(format!("{path}[{i}]"), a.get(i), b.get(i), changes);
+            }
+        }
+        (a, b) if a != b => changes.push(FieldChange {
+            field: path,
+            before: a.cloned(),
+            after: b.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+fn end_date(body: &Value) -> Option<DateTime<Utc>> {
+    body.get("endDate")
+        .and_then(Value::as_str)
+        .and_then(|text| DateTime::parse_from_rfc3339(text).ok())
+        .map(|at| at.with_timezone(&Utc))
+}
@@ -0,0 +1,463 @@
+//! Rule-driven renewal of expiring promotions.
+//!
+//! `DiscountManager.run` listed what ended within `ALERT_WINDOW_HOURS`, asked
+//! y/n on the terminal, and `extendDiscountEndDate` pushed every end date out
+//! by 14 days. Here the decision comes from rules, checked in order; the
+//! first rule whose filters match a promotion decides what happens to it:
+//!
+//! ```json
+//! {
+//!   "rules": [
+//!     {
+//!       "name": "coupons lapse",
+//!       "promotionTypes": ["CODED_COUPON"],
+//!       "action": { "type": "lapse" }
+//!     },
+//!     {
+//!       "name": "shirt markdowns",
+//!       "promotionTypes": ["MARKDOWN_SALE"],
+//!       "sku": "SHIRT-*",
+//!       "withinHours": 24,
+//!       "action": { "type": "extend", "days": 7, "maxTotalDays": 90 }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `sku` and `namePattern` are patterns where `*` matches any run of
+//! characters and `?` any one, ignoring case; `sku` matches if any SKU the
+//! promotion names does. A rule acts once fewer than `withinHours` (default 48) remain.
+//! `maxTotalDays` caps the promotion's whole run, start to end.
+//!
+//! Unlike `extendDiscountEndDate`, the update keeps the promotion's status
+//! and description as they are. [`RenewalEngine::plan`] only builds a
+//! [`PromotionPlan`] for review; [`RenewalEngine::run`] applies it straight
+//! away. Every update sent, and every one eBay rejects, is written to the
+//! [`PromotionAudit`].
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use super::audit::PromotionAudit;
+use super::client::{MarketingClient, PromotionFilter};
+use super::plan::PromotionPlan;
+use super::types::{Promotion, PromotionDetail, PromotionStatus, PromotionType};
+use crate::error::{Error, Result};
+
+/// `ALERT_WINDOW_HOURS` from the discount manager.
+pub const DEFAULT_WINDOW_HOURS: u32 = 48;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenewalRule {
+    pub name: String,
+    /// Types the rule applies to; empty means every type.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub promotion_types: Vec<PromotionType>,
+    /// Pattern at least one of the promotion's SKUs must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    /// Pattern the promotion name must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_pattern: Option<String>,
+    /// The rule acts once fewer than this many hours remain.
+    #[serde(default = "default_window_hours")]
+    pub within_hours: u32,
+    pub action: RenewalAction,
+}
+
+fn default_window_hours() -> u32 {
+    DEFAULT_WINDOW_HOURS
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RenewalAction {
+    /// Move the end date out by `days`, keeping the run from start to end
+    /// within `max_total_days`.
+    #[serde(rename_all = "camelCase")]
+    Extend {
+        days: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_total_days: Option<u32>,
+    },
+    /// Let the promotion end.
+    Lapse,
+}
+
+impl RenewalRule {
+    pub fn new(name: impl Into<String>, action: RenewalAction) -> Self {
+        Self {
+            name: name.into(),
+            promotion_types: Vec::new(),
+            sku: None,
+            name_pattern: None,
+            within_hours: DEFAULT_WINDOW_HOURS,
+            action,
+        }
+    }
+
+    pub fn with_types(mut self, types: impl IntoIterator<Item = PromotionType>) -> Self {
+        self.promotion_types = types.into_iter().collect();
+        self
+    }
+
+    pub fn with_sku(mut self, pattern: impl Into<String>) -> Self {
+        self.sku = Some(pattern.into());
+        self
+    }
+
+    pub fn with_name_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.name_pattern = Some(pattern.into());
+        self
+    }
+
+    pub fn with_window_hours(mut self, hours: u32) -> Self {
+        self.within_hours = hours;
+        self
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::Rule("rule name is empty".to_string()));
+        }
+        if let RenewalAction::Extend {
+            days,
+            max_total_days,
+        } = self.action
+        {
+            if days == 0 {
+                return Err(Error::Rule(format!(
+                    "{:?}: extend needs at least one day",
+                    self.name
+                )));
+            }
+            if max_total_days == Some(0) {
+                return Err(Error::Rule(format!(
+                    "{:?}: maxTotalDays must be at least one day",
+                    self.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the rule's type and name filters admit the promotion. These
+    /// are known from the `getPromotions` summary.
+    fn admits(&self, promotion_type: PromotionType, name: &str) -> bool {
+        (self.promotion_types.is_empty() || self.promotion_types.contains(&promotion_type))
+            && self
+                .name_pattern
+                .as_deref()
+                .is_none_or(|pattern| pattern_matches(pattern, name))
+    }
+
+    /// Whether the rule applies to the full promotion.
+    pub fn matches(&self, promotion: &Promotion) -> bool {
+        self.admits(promotion.promotion_type(), promotion.name())
+            && self.sku.as_deref().is_none_or(|pattern| {
+                promotion
+                    .skus()
+                    .into_iter()
+                    .any(|sku| pattern_matches(pattern, sku))
+            })
+    }
+
+    /// Whether fewer than `within_hours` remain before `end_date`.
+    pub fn is_due(&self, end_date: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        end_date - now < TimeDelta::hours(i64::from(self.within_hours))
+    }
+}
+
+/// Rules in the order they are checked.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RenewalRules {
+    pub rules: Vec<RenewalRule>,
+}
+
+impl RenewalRules {
+    pub fn new(rules: Vec<RenewalRule>) -> Result<Self> {
+        rules.iter().try_for_each(RenewalRule::validate)?;
+        Ok(Self { rules })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let rules: Self = serde_json::from_str(json)?;
+        rules.rules.iter().try_for_each(RenewalRule::validate)?;
+        Ok(rules)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// The first rule that applies to `promotion`.
+    pub fn rule_for(&self, promotion: &Promotion) -> Option<&RenewalRule> {
+        self.rules.iter().find(|rule| rule.matches(promotion))
+    }
+
+    /// Whether any rule could act on the promotion `detail` describes. Only
+    /// those are read in full.
+    fn may_act(&self, detail: &PromotionDetail, now: DateTime<Utc>) -> bool {
+        let Some(end_date) = detail.end_date else {
+            return false;
+        };
+        self.rules.iter().any(|rule| {
+            rule.admits(detail.promotion_type, &detail.name) && rule.is_due(end_date, now)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RenewalOutcome {
+    /// The extension is in the plan and has not been sent.
+    Planned,
+    Extended,
+    /// The rule says to let it end.
+    Lapsed,
+    /// Extending would run past `maxTotalDays`.
+    Capped,
+    Failed,
+}
+
+/// What the engine did with one promotion that a rule acted on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenewalDecision {
+    pub promotion_id: String,
+    pub promotion_name: String,
+    pub promotion_type: PromotionType,
+    pub rule: String,
+    pub outcome: RenewalOutcome,
+    pub end_date: DateTime<Utc>,
+    pub new_end_date: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenewalReport {
+    pub ran_at: DateTime<Utc>,
+    /// Live promotions looked at.
+    pub checked: usize,
+    pub decisions: Vec<RenewalDecision>,
+    /// The updates behind the `Planned` decisions.
+    pub plan: PromotionPlan,
+}
+
+impl RenewalReport {
+    pub fn count(&self, outcome: RenewalOutcome) -> usize {
+        self.decisions
+            .iter()
+            .filter(|decision| decision.outcome == outcome)
+            .count()
+    }
+}
+
+/// The end date `action` moves a promotion to, or `None` if it stays put.
+pub fn renewed_end_date(
+    action: RenewalAction,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let RenewalAction::Extend {
+        days,
+        max_total_days,
+    } = action
+    else {
+        return None;
+    };
+    let mut new_end = end_date + TimeDelta::days(i64::from(days));
+    if let Some(max_days) = max_total_days {
+        new_end = new_end.min(start_date + TimeDelta::days(i64::from(max_days)));
+    }
+    (new_end > end_date).then_some(new_end)
+}
+
+#[derive(Debug)]
+pub struct RenewalEngine {
+    client: MarketingClient,
+    rules: RenewalRules,
+    audit: PromotionAudit,
+}
+
+impl RenewalEngine {
+    pub fn new(client: MarketingClient, rules: RenewalRules, audit: PromotionAudit) -> Self {
+        Self {
+            client,
+            rules,
+            audit,
+        }
+    }
+
+    pub fn rules(&self) -> &RenewalRules {
+        &self.rules
+    }
+
+    /// Decide what the rules do with every live promotion, without sending
+    /// anything. Extensions come back as `Planned` decisions and as changes
+    /// in the report's plan. A promotion that cannot be read is skipped.
+    pub async fn plan(&self, now: DateTime<Utc>) -> Result<RenewalReport> {
+        let live: Vec<PromotionDetail> = self
+            .client
+            .promotions(&PromotionFilter::default())
+            .await?
+            .into_iter()
+            .filter(|detail| {
+                matches!(
+                    detail.promotion_status,
+                    PromotionStatus::Running | PromotionStatus::Scheduled | PromotionStatus::Paused
+                )
+            })
+            .collect();
+
+        let mut report = RenewalReport {
+            ran_at: now,
+            checked: live.len(),
+            decisions: Vec::new(),
+            plan: PromotionPlan::new(self.client.marketplace_id()),
+        };
+        for detail in live.iter().filter(|detail| self.rules.may_act(detail, now)) {
+            self.decide(detail, now, &mut report).await?;
+        }
+        Ok(report)
+    }
+
+    /// Plan and apply at once, for unattended runs. A promotion eBay
+    /// rejects is reported and the run moves on; only failing to list
+    /// promotions, or to write the audit trail, ends the run.
+    pub async fn run(&self, now: DateTime<Utc>) -> Result<RenewalReport> {
+        let mut report = self.plan(now).await?;
+        if !report.plan.is_empty() {
+            let applied = report.plan.apply(&self.client, &self.audit).await?;
+            for result in applied.results {
+                let Some(decision) = report
+                    .decisions
+                    .iter_mut()
+                    .find(|decision| decision.promotion_id == result.promotion_id)
+                else {
+                    continue;
+                };
+                decision.outcome = match result.error {
+                    None => RenewalOutcome::Extended,
+                    Some(_) => RenewalOutcome::Failed,
+                };
+                decision.error = result.error;
+            }
+        }
+
+        log::info!(
+            "Renewal finished: {} checked, {} extended, {} lapsed, {} capped, {} failed",
+            report.checked,
+            report.count(RenewalOutcome::Extended),
+            report.count(RenewalOutcome::Lapsed),
+            report.count(RenewalOutcome::Capped),
+            report.count(RenewalOutcome::Failed)
+        );
+        Ok(report)
+    }
+
+    /// Run now and then every `interval` until the handle is aborted.
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(err) = self.run(Utc::now()).await {
+                    log::warn!("Promotion renewal failed: {err}");
+                }
+            }
+        })
+    }
+
+    async fn decide(
+        &self,
+        detail: &PromotionDetail,
+        now: DateTime<Utc>,
+        report: &mut RenewalReport,
+    ) -> Result<()> {
+        let id = detail.promotion_id.as_str();
+        let promotion = match self.client.get(id, detail.promotion_type).await {
+            Ok(promotion) => promotion,
+            Err(err) => {
+                log::warn!("Could not read promotion {id}: {err}");
+                return Ok(());
+            }
+        };
+        let Some(rule) = self.rules.rule_for(&promotion) else {
+            return Ok(());
+        };
+        let end_date = promotion.end_date();
+        if !rule.is_due(end_date, now) {
+            return Ok(());
+        }
+
+        let mut decision = RenewalDecision {
+            promotion_id: id.to_string(),
+            promotion_name: promotion.name().to_string(),
+            promotion_type: promotion.promotion_type(),
+            rule: rule.name.clone(),
+            outcome: RenewalOutcome::Lapsed,
+            end_date,
+            new_end_date: None,
+            error: None,
+        };
+        if rule.action == RenewalAction::Lapse {
+            log::info!("Letting promotion {id} lapse ({:?})", rule.name);
+        } else if let Some(new_end) =
+            renewed_end_date(rule.action, promotion.start_date(), end_date)
+        {
+            let mut updated = promotion.clone();
+            updated.set_end_date(new_end);
+            report
+                .plan
+                .add(id, &promotion, &updated, Some(rule.name.clone()))?;
+            decision.outcome = RenewalOutcome::Planned;
+            decision.new_end_date = Some(new_end);
+        } else {
+            log::info!(
+                "Promotion {id} has reached its maximum length ({:?})",
+                rule.name
+            );
+            decision.outcome = RenewalOutcome::Capped;
+        }
+        report.decisions.push(decision);
+        Ok(())
+    }
+}
+
+/// `*` matches any run of characters, `?` any one; case is ignored.
+pub fn pattern_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
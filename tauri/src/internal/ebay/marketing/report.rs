@@ -0,0 +1,585 @@
+//! Promotion performance from the Marketing API's sales reports.
+//!
+//! The discount manager showed promotion names and end dates and nothing
+//! about what a sale brought in. `promotion_report` gives, per promotion, the
+//! base sale (revenue at full price), the promotion sale (revenue with the
+//! discount), the discount given and the units and orders sold on it;
+//! `promotion_summary_report` gives the same across the marketplace.
+//!
+//! eBay's figures are totals to date. Each [`PromotionReports::refresh`]
+//! stores them with the time they were read, next to the latest copy of each
+//! promotion, and [`PromotionReports::performance`] turns successive reads
+//! into per-day, week or month amounts: a period gets the difference between
+//! its last read and the last read before it. Sales from before the first
+//! read count towards the first period.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{params, Row};
+use rust_xlsxwriter::Workbook;
+use serde::Serialize;
+
+use super::client::{MarketingClient, PromotionFilter};
+use super::types::{
+    Amount, PromotionDetail, PromotionReportDetail, PromotionStatus, PromotionType, SummaryReport,
+};
+use crate::error::{Error, Result};
+use crate::internal::ebay::fees::Period;
+use crate::internal::ebay::listing_rows::{Cell, ColumnKind, ListingRow};
+use crate::internal::ebay::warehouse::{from_timestamp, parsed_optional, Warehouse};
+use crate::internal::ebay::workbook::{
+    column_widths, finish_data_sheet, round2, save_export, write_header, write_listing,
+    write_metrics, CellFormats, Column, ExportResult, SUMMARY_SHEET,
+};
+
+pub const PERFORMANCE_SHEET: &str = "Promotion Performance";
+
+/// Label of the single period [`PromotionReports::performance`] returns
+/// without a [`Period`].
+pub const TO_DATE: &str = "To date";
+
+/// Sales of one promotion in one period, joined to the stored promotion.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromotionPerformance {
+    pub promotion_id: String,
+    /// Empty, and type and status unknown, for a promotion that was never
+    /// stored (e.g. deleted before the first refresh).
+    pub name: String,
+    pub promotion_type: Option<PromotionType>,
+    pub status: Option<PromotionStatus>,
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+    /// e.g. `2024-03`, or [`TO_DATE`].
+    pub period: String,
+    pub period_start: Option<NaiveDate>,
+    pub currency: Option<String>,
+    pub base_sale: f64,
+    pub promotion_sale: f64,
+    pub total_sale: f64,
+    pub total_discount: f64,
+    /// Units sold with the discount applied; eBay does not count base sale
+    /// units separately.
+    pub items_sold: i64,
+    pub orders_sold: i64,
+    /// eBay's sales lift as of the end of the period.
+    pub sales_lift: Option<f64>,
+}
+
+/// The latest `promotion_summary_report`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryTotals {
+    pub recorded_at: DateTime<Utc>,
+    pub currency: Option<String>,
+    pub base_sale: f64,
+    pub promotion_sale: f64,
+    pub total_sale: f64,
+    pub sales_lift: Option<f64>,
+}
+
+/// What one refresh stored.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshResult {
+    pub recorded_at: DateTime<Utc>,
+    pub promotions: usize,
+    pub reports: usize,
+}
+
+/// One account's promotions and sales reports in the warehouse.
+#[derive(Debug, Clone)]
+pub struct PromotionReports {
+    warehouse: Arc<Warehouse>,
+    account: String,
+}
+
+impl PromotionReports {
+    pub fn new(warehouse: Arc<Warehouse>, account: impl Into<String>) -> Self {
+        Self {
+            warehouse,
+            account: account.into(),
+        }
+    }
+
+    /// Read every promotion, its report and the summary report, and store
+    /// them as of `now`.
+    pub async fn refresh(
+        &self,
+        client: &MarketingClient,
+        now: DateTime<Utc>,
+    ) -> Result<RefreshResult> {
+        let filter = PromotionFilter::default();
+        let promotions = client.promotions(&filter).await?;
+        let reports = client.promotion_reports(&filter).await?;
+        let summary = client.promotion_summary_report().await?;
+
+        let stored = self.store_promotions(&promotions, now)?;
+        let recorded = self.record(&reports, Some(&summary), now)?;
+        log::info!("Stored {recorded} promotion reports for {stored} promotions");
+        Ok(RefreshResult {
+            recorded_at: now,
+            promotions: stored,
+            reports: recorded,
+        })
+    }
+
+    /// Keep the latest copy of each promotion. Returns the number stored.
+    pub fn store_promotions(
+        &self,
+        promotions: &[PromotionDetail],
+        now: DateTime<Utc>,
+    ) -> Result<usize> {
+        let mut conn = self.warehouse.conn();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO promotions
+                     (account, promotion_id, name, promotion_type, status, start_date,
+                      end_date, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (account, promotion_id) DO UPDATE SET
+                     name = excluded.name,
+                     promotion_type = excluded.promotion_type,
+                     status = excluded.status,
+                     start_date = excluded.start_date,
+                     end_date = excluded.end_date,
+                     updated_at = excluded.updated_at",
+            )?;
+            for promotion in promotions {
+                stmt.execute(params![
+                    self.account,
+                    promotion.promotion_id,
+                    promotion.name,
+                    promotion.promotion_type.as_str(),
+                    promotion.promotion_status.as_str(),
+                    promotion.start_date.map(|at| at.timestamp()),
+                    promotion.end_date.map(|at| at.timestamp()),
+                    now.timestamp(),
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(promotions.len())
+    }
+
+    /// Store `reports` (and `summary`) as read at `recorded_at`. Returns the
+    /// number of promotion reports stored.
+    pub fn record(
+        &self,
+        reports: &[PromotionReportDetail],
+        summary: Option<&SummaryReport>,
+        recorded_at: DateTime<Utc>,
+    ) -> Result<usize> {
+        let at = recorded_at.timestamp();
+        let mut conn = self.warehouse.conn();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO promotion_reports
+                     (account, promotion_id, recorded_at, currency, base_sale, promotion_sale,
+                      total_sale, total_discount, items_sold, orders_sold, sales_lift)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            )?;
+            for report in reports {
+                stmt.execute(params![
+                    self.account,
+                    report.promotion_id,
+                    at,
+                    currency([
+                        &report.base_sale,
+                        &report.promotion_sale,
+                        &report.total_sale,
+                        &report.total_discount,
+                    ]),
+                    amount(&report.base_sale),
+                    amount(&report.promotion_sale),
+                    amount(&report.total_sale),
+                    amount(&report.total_discount),
+                    report.items_sold_quantity,
+                    report.number_of_orders_sold,
+                    percentage(&report.percentage_sales_lift),
+                ])?;
+            }
+        }
+        if let Some(summary) = summary {
+            tx.execute(
+                "INSERT OR REPLACE INTO promotion_summary_reports
+                     (account, recorded_at, currency, base_sale, promotion_sale, total_sale,
+                      sales_lift)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    self.account,
+                    at,
+                    currency([
+                        &summary.base_sale,
+                        &summary.promotion_sale,
+                        &summary.total_sale,
+                    ]),
+                    amount(&summary.base_sale),
+                    amount(&summary.promotion_sale),
+                    amount(&summary.total_sale),
+                    percentage(&summary.percentage_sales_lift),
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(reports.len())
+    }
+
+    /// Sales per promotion and period, oldest period first, or one
+    /// [`TO_DATE`] row per promotion when `period` is `None`.
+    pub fn performance(&self, period: Option<Period>) -> Result<Vec<PromotionPerformance>> {
+        let conn = self.warehouse.conn();
+        let mut stmt = conn.prepare(
+            "SELECT r.promotion_id, r.recorded_at, r.currency, r.base_sale, r.promotion_sale,
+                    r.total_sale, r.total_discount, r.items_sold, r.orders_sold, r.sales_lift,
+                    p.name, p.promotion_type, p.status, p.start_date, p.end_date
+             FROM promotion_reports r
+             LEFT JOIN promotions p
+                 ON p.account = r.account AND p.promotion_id = r.promotion_id
+             WHERE r.account = ?1
+             ORDER BY r.promotion_id, r.recorded_at",
+        )?;
+        let reads = stmt
+            .query_map([&self.account], read_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // Last read per promotion and period; the BTreeMap keeps both in
+        // order.
+        let mut last: BTreeMap<(String, Option<NaiveDate>), StoredRead> = BTreeMap::new();
+        for read in reads {
+            let start = period.map(|period| period.start_of(read.recorded_at.date_naive()));
+            last.insert((read.performance.promotion_id.clone(), start), read);
+        }
+
+        let mut rows = Vec::with_capacity(last.len());
+        let mut previous: Option<&StoredRead> = None;
+        for ((promotion_id, start), read) in &last {
+            let before = previous.filter(|p| &p.performance.promotion_id == promotion_id);
+            let mut row = read.performance.clone();
+            if let Some(before) = before {
+                row.base_sale -= before.performance.base_sale;
+                row.promotion_sale -= before.performance.promotion_sale;
+                row.total_sale -= before.performance.total_sale;
+                row.total_discount -= before.performance.total_discount;
+                row.items_sold -= before.performance.items_sold;
+                row.orders_sold -= before.performance.orders_sold;
+            }
+            row.base_sale = round2(row.base_sale);
+            row.promotion_sale = round2(row.promotion_sale);
+            row.total_sale = round2(row.total_sale);
+            row.total_discount = round2(row.total_discount);
+            row.period_start = *start;
+            row.period = match (period, start) {
+                (Some(period), Some(start)) => period.label(*start),
+                _ => TO_DATE.to_string(),
+            };
+            rows.push(row);
+            previous = Some(read);
+        }
+        rows.sort_by(|a, b| {
+            (a.period_start, &a.promotion_id).cmp(&(b.period_start, &b.promotion_id))
+        });
+        Ok(rows)
+    }
+
+    /// The latest summary report, if one was ever stored.
+    pub fn summary(&self) -> Result<Option<SummaryTotals>> {
+        let conn = self.warehouse.conn();
+        let mut stmt = conn.prepare(
+            "SELECT recorded_at, currency, base_sale, promotion_sale, total_sale, sales_lift
+             FROM promotion_summary_reports
+             WHERE account = ?1
+             ORDER BY recorded_at DESC
+             LIMIT 1",
+        )?;
+        let mut rows = stmt.query_map([&self.account], |row| {
+            Ok(SummaryTotals {
+                recorded_at: from_timestamp(row.get(0)?),
+                currency: row.get(1)?,
+                base_sale: row.get::<_, Option<f64>>(2)?.unwrap_or_default(),
+                promotion_sale: row.get::<_, Option<f64>>(3)?.unwrap_or_default(),
+                total_sale: row.get::<_, Option<f64>>(4)?.unwrap_or_default(),
+                sales_lift: row.get(5)?,
+            })
+        })?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Write [`Self::performance`] for `period` and the latest summary to an
+    /// XLSX workbook at `path`.
+    pub fn export(&self, period: Option<Period>, path: impl AsRef<Path>) -> Result<ExportResult> {
+        export_performance(&self.performance(period)?, self.summary()?.as_ref(), path)
+    }
+}
+
+/// Write performance rows to a "Promotion Performance" sheet, with totals on
+/// a "Summary" sheet laid out like the listings export's.
+pub fn export_performance(
+    rows: &[PromotionPerformance],
+    summary: Option<&SummaryTotals>,
+    path: impl AsRef<Path>,
+) -> Result<ExportResult> {
+    let path = path.as_ref();
+    log::info!("Creating Excel file: {}", path.display());
+    save_export(performance_workbook(rows, summary)?, path, rows.len())
+}
+
+/// The performance workbook, not yet saved.
+pub fn performance_workbook(
+    rows: &[PromotionPerformance],
+    summary: Option<&SummaryTotals>,
+) -> Result<Workbook> {
+    if rows.is_empty() {
+        return Err(Error::Spreadsheet("No data to export".to_string()));
+    }
+
+    let columns = performance_columns();
+    let shaped: Vec<ListingRow> = rows.iter().map(performance_row).collect();
+    let mut formats = CellFormats::new();
+    let mut workbook = Workbook::new();
+
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(PERFORMANCE_SHEET)?;
+    write_header(sheet, &columns, &formats)?;
+    for (width, col) in column_widths(&columns, &shaped).into_iter().zip(0u16..) {
+        sheet.set_column_width(col, width)?;
+    }
+    for (row, shaped) in (1u32..).zip(&shaped) {
+        write_listing(sheet, row, &columns, shaped, &mut formats)?;
+    }
+    finish_data_sheet(sheet, &columns, shaped.len() as u32, 2)?;
+
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(SUMMARY_SHEET)?;
+    write_metrics(sheet, summary_entries(rows, summary))?;
+
+    Ok(workbook)
+}
+
+pub fn performance_columns() -> Vec<Column> {
+    [
+        ("Promotion ID", ColumnKind::Text),
+        ("Promotion Name", ColumnKind::Text),
+        ("Promotion Type", ColumnKind::Text),
+        ("Status", ColumnKind::Text),
+        ("Start Date", ColumnKind::DateTime),
+        ("End Date", ColumnKind::DateTime),
+        ("Period", ColumnKind::Text),
+        ("Currency", ColumnKind::Text),
+        ("Base Sale", ColumnKind::Price),
+        ("Promotion Sale", ColumnKind::Price),
+        ("Total Sale", ColumnKind::Price),
+        ("Total Discount", ColumnKind::Price),
+        ("Items Sold", ColumnKind::Integer),
+        ("Orders", ColumnKind::Integer),
+        ("Sales Lift (%)", ColumnKind::Decimal),
+    ]
+    .into_iter()
+    .map(|(name, kind)| Column::new(name, kind))
+    .collect()
+}
+
+fn performance_row(performance: &PromotionPerformance) -> ListingRow {
+    let text = |value: Option<&str>| value.map_or(Cell::Empty, |v| Cell::Text(v.to_string()));
+    let date =
+        |value: Option<DateTime<Utc>>| value.map_or(Cell::Empty, |at| Cell::Text(at.to_rfc3339()));
+
+    let mut row = ListingRow::default();
+    row.set("Promotion ID", Cell::Text(performance.promotion_id.clone()));
+    row.set("Promotion Name", Cell::Text(performance.name.clone()));
+    row.set(
+        "Promotion Type",
+        text(performance.promotion_type.map(PromotionType::as_str)),
+    );
+    row.set(
+        "Status",
+        text(performance.status.map(PromotionStatus::as_str)),
+    );
+    row.set("Start Date", date(performance.start_date));
+    row.set("End Date", date(performance.end_date));
+    row.set("Period", Cell::Text(performance.period.clone()));
+    row.set("Currency", text(performance.currency.as_deref()));
+    row.set("Base Sale", Cell::Number(performance.base_sale));
+    row.set("Promotion Sale", Cell::Number(performance.promotion_sale));
+    row.set("Total Sale", Cell::Number(performance.total_sale));
+    row.set("Total Discount", Cell::Number(performance.total_discount));
+    row.set("Items Sold", Cell::Integer(performance.items_sold));
+    row.set("Orders", Cell::Integer(performance.orders_sold));
+    row.set(
+        "Sales Lift (%)",
+        performance.sales_lift.map_or(Cell::Empty, Cell::Number),
+    );
+    row
+}
+
+/// Metric / Value rows: totals over `rows`, eBay's summary report, then the
+/// promotions that brought in the most. Amounts are totalled per currency,
+/// as [`FeeLedger::by_period`] does, and labelled with it.
+///
+/// [`FeeLedger::by_period`]: crate::internal::ebay::fees::FeeLedger::by_period
+fn summary_entries(
+    rows: &[PromotionPerformance],
+    summary: Option<&SummaryTotals>,
+) -> Vec<(String, Cell)> {
+    let blank = || (String::new(), Cell::Empty);
+    let heading = |text: &str| (text.to_string(), Cell::Empty);
+    let money = |label: &str, currency: &str, value: f64| {
+        let label = match currency {
+            "" => label.to_string(),
+            code => format!("{label} ({code})"),
+        };
+        (label, Cell::Number(round2(value)))
+    };
+
+    // Base, promotion and total sale and discount, by currency.
+    let mut by_currency: BTreeMap<&str, [f64; 4]> = BTreeMap::new();
+    // Promotion sale by currency, then promotion.
+    let mut by_promotion: BTreeMap<(&str, &str), (&str, f64)> = BTreeMap::new();
+    for row in rows {
+        let currency = row.currency.as_deref().unwrap_or_default();
+        let totals = by_currency.entry(currency).or_default();
+        for (total, value) in totals.iter_mut().zip([
+            row.base_sale,
+            row.promotion_sale,
+            row.total_sale,
+            row.total_discount,
+        ]) {
+            *total += value;
+        }
+        by_promotion
+            .entry((currency, row.promotion_id.as_str()))
+            .or_insert((row.name.as_str(), 0.0))
+            .1 += row.promotion_sale;
+    }
+    let promotions: BTreeSet<&str> = rows.iter().map(|r| r.promotion_id.as_str()).collect();
+
+    let mut entries = vec![
+        heading("PROMOTION PERFORMANCE SUMMARY"),
+        blank(),
+        (
+            "Promotions Reported".to_string(),
+            Cell::Integer(promotions.len() as i64),
+        ),
+    ];
+    for (currency, [base, promotion, total, discount]) in &by_currency {
+        entries.push(money("Base Sale Revenue", currency, *base));
+        entries.push(money("Promotion Sale Revenue", currency, *promotion));
+        entries.push(money("Total Sale Revenue", currency, *total));
+        entries.push(money("Total Discount", currency, *discount));
+    }
+    entries.push((
+        "Items Sold on Promotion".to_string(),
+        Cell::Integer(rows.iter().map(|r| r.items_sold).sum()),
+    ));
+    entries.push((
+        "Orders".to_string(),
+        Cell::Integer(rows.iter().map(|r| r.orders_sold).sum()),
+    ));
+
+    if let Some(summary) = summary {
+        let currency = summary.currency.as_deref().unwrap_or_default();
+        entries.push(blank());
+        entries.push(heading("ALL PROMOTIONS (EBAY SUMMARY):"));
+        entries.push(money("  Base Sale Revenue", currency, summary.base_sale));
+        entries.push(money(
+            "  Promotion Sale Revenue",
+            currency,
+            summary.promotion_sale,
+        ));
+        entries.push(money("  Total Sale Revenue", currency, summary.total_sale));
+        if let Some(lift) = summary.sales_lift {
+            entries.push(("  Sales Lift (%)".to_string(), Cell::Number(lift)));
+        }
+        entries.push((
+            "  As Of".to_string(),
+            Cell::Text(summary.recorded_at.format("%Y-%m-%d %H:%M").to_string()),
+        ));
+    }
+
+    entries.push(blank());
+    entries.push(heading("TOP PROMOTIONS BY PROMOTION SALE:"));
+    for currency in by_currency.keys() {
+        let mut top: Vec<(&str, &str, f64)> = by_promotion
+            .iter()
+            .filter(|((code, _), _)| code == currency)
+            .map(|((_, id), (name, sale))| (*id, *name, *sale))
+            .collect();
+        top.sort_by(|a, b| b.2.total_cmp(&a.2));
+        entries.extend(top.into_iter().take(10).map(|(id, name, sale)| {
+            let label = if name.is_empty() { id } else { name };
+            money(&format!("  {label}"), currency, sale)
+        }));
+    }
+    entries
+}
+
+/// One stored read, with the to-date figures in `performance`.
+struct StoredRead {
+    recorded_at: DateTime<Utc>,
+    performance: PromotionPerformance,
+}
+
+fn read_from_row(row: &Row<'_>) -> rusqlite::Result<StoredRead> {
+    let value = |i: usize| -> rusqlite::Result<f64> {
+        Ok(row.get::<_, Option<f64>>(i)?.unwrap_or_default())
+    };
+    let count = |i: usize| -> rusqlite::Result<i64> {
+        Ok(row.get::<_, Option<i64>>(i)?.unwrap_or_default())
+    };
+    Ok(StoredRead {
+        recorded_at: from_timestamp(row.get(1)?),
+        performance: PromotionPerformance {
+            promotion_id: row.get(0)?,
+            name: row.get::<_, Option<String>>(10)?.unwrap_or_default(),
+            // NULL for a promotion that was never stored; anything else
+            // must be a known value.
+            promotion_type: parsed_optional(row, 11, |text| {
+                PromotionType::ALL
+                    .into_iter()
+                    .find(|kind| kind.as_str() == text)
+            })?,
+            status: parsed_optional(row, 12, |text| {
+                PromotionStatus::ALL
+                    .into_iter()
+                    .find(|status| status.as_str() == text)
+            })?,
+            start_date: row.get::<_, Option<i64>>(13)?.map(from_timestamp),
+            end_date: row.get::<_, Option<i64>>(14)?.map(from_timestamp),
+            period: TO_DATE.to_string(),
+            period_start: None,
+            currency: row.get(2)?,
+            base_sale: value(3)?,
+            promotion_sale: value(4)?,
+            total_sale: value(5)?,
+            total_discount: value(6)?,
+            items_sold: count(7)?,
+            orders_sold: count(8)?,
+            sales_lift: row.get(9)?,
+        },
+    })
+}
+
+fn amount(value: &Option<Amount>) -> Option<f64> {
+    value.as_ref().and_then(|amount| amount.value.parse().ok())
+}
+
+/// The first currency among `amounts`.
+fn currency<const N: usize>(amounts: [&Option<Amount>; N]) -> Option<String> {
+    amounts
+        .into_iter()
+        .flatten()
+        .map(|amount| amount.currency.clone())
+        .find(|currency| !currency.is_empty())
+}
+
+/// eBay sends percentages as strings, sometimes with a `%`.
+fn percentage(value: &Option<String>) -> Option<f64> {
+    value
+        .as_deref()
+        .and_then(|text| text.trim().trim_end_matches('%').parse().ok())
+}
@@ -0,0 +1,555 @@
+//! Promotion resources of the Marketing API (`sell/marketing/v1`).
+//!
+//! Field names follow eBay's JSON (camelCase). `discount-manager.js` passes
+//! these objects around untyped and copies the writable fields by hand in
+//! `extendDiscountEndDate`; here every field it touches is modelled, and
+//! empty ones are left out of request bodies.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Amount {
+    pub value: String,
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PromotionType {
+    OrderDiscount,
+    VolumeDiscount,
+    MarkdownSale,
+    CodedCoupon,
+}
+
+impl PromotionType {
+    pub const ALL: [PromotionType; 4] = [
+        PromotionType::OrderDiscount,
+        PromotionType::VolumeDiscount,
+        PromotionType::MarkdownSale,
+        PromotionType::CodedCoupon,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PromotionType::OrderDiscount => "ORDER_DISCOUNT",
+            PromotionType::VolumeDiscount => "VOLUME_DISCOUNT",
+            PromotionType::MarkdownSale => "MARKDOWN_SALE",
+            PromotionType::CodedCoupon => "CODED_COUPON",
+        }
+    }
+
+    /// Resource the promotion is read and written through: markdowns live
+    /// under `item_price_markdown`, everything else under `item_promotion`.
+    pub fn resource(self) -> &'static str {
+        match self {
+            PromotionType::MarkdownSale => "item_price_markdown",
+            _ => "item_promotion",
+        }
+    }
+}
+
+impl fmt::Display for PromotionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PromotionStatus {
+    Draft,
+    Scheduled,
+    Running,
+    Paused,
+    Ended,
+}
+
+impl PromotionStatus {
+    pub const ALL: [PromotionStatus; 5] = [
+        PromotionStatus::Draft,
+        PromotionStatus::Scheduled,
+        PromotionStatus::Running,
+        PromotionStatus::Paused,
+        PromotionStatus::Ended,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PromotionStatus::Draft => "DRAFT",
+            PromotionStatus::Scheduled => "SCHEDULED",
+            PromotionStatus::Running => "RUNNING",
+            PromotionStatus::Paused => "PAUSED",
+            PromotionStatus::Ended => "ENDED",
+        }
+    }
+}
+
+impl fmt::Display for PromotionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InventoryCriterionType {
+    InventoryAny,
+    InventoryByRule,
+    InventoryByValue,
+}
+
+/// Which listings a promotion (or one markdown tier) applies to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryCriterion {
+    pub inventory_criterion_type: InventoryCriterionType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inventory_items: Vec<InventoryItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub listing_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_criteria: Option<RuleCriteria>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryItem {
+    /// The item's SKU.
+    pub inventory_reference_id: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleCriteria {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_inventory_items: Vec<InventoryItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_listing_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markup_inventory_items: Vec<InventoryItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markup_listing_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub selection_rules: Vec<SelectionRule>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionRule {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub brands: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub category_ids: Vec<String>,
+    /// `MARKETPLACE` or `STORE`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category_scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub listing_condition_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<Amount>,
+}
+
+/// What the buyer gets. Percentages are strings, as eBay sends them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscountBenefit {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_off_item: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_off_order: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentage_off_item: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentage_off_order: Option<String>,
+}
+
+/// When the benefit applies, e.g. "buy 2 or more".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscountSpecification {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub for_each_amount: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub for_each_quantity: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_amount: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_quantity: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number_of_discounted_items: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscountRule {
+    #[serde(default)]
+    pub discount_benefit: DiscountBenefit,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discount_specification: Option<DiscountSpecification>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_discount_amount: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_order: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CouponConfiguration {
+    pub coupon_code: String,
+    /// `PRIVATE_SINGLE_SELLER_COUPON` or `PUBLIC_SINGLE_SELLER_COUPON`.
+    pub coupon_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_coupon_redemption_per_user: Option<u32>,
+}
+
+/// An `item_promotion`: ORDER_DISCOUNT, VOLUME_DISCOUNT or CODED_COUPON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemPromotion {
+    /// Read-only; left out of create and update bodies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promotion_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promotion_href: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub marketplace_id: String,
+    pub promotion_type: PromotionType,
+    pub promotion_status: PromotionStatus,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    /// `PRIORITY_1` (highest) to `PRIORITY_4`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promotion_image_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub discount_rules: Vec<DiscountRule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inventory_criterion: Option<InventoryCriterion>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub apply_discount_to_single_item_only: Option<bool>,
+    /// CODED_COUPON only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coupon_configuration: Option<CouponConfiguration>,
+    /// CODED_COUPON only: total discount the seller is willing to give.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<Amount>,
+}
+
+/// One markdown tier: a discount and the listings it applies to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectedInventoryDiscount {
+    pub discount_benefit: DiscountBenefit,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discount_id: Option<String>,
+    pub inventory_criterion: InventoryCriterion,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_order: Option<u32>,
+}
+
+/// An `item_price_markdown` (MARKDOWN_SALE).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemPriceMarkdown {
+    /// Read-only; left out of create and update bodies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promotion_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promotion_href: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub marketplace_id: String,
+    pub promotion_status: PromotionStatus,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promotion_image_url: Option<String>,
+    pub selected_inventory_discounts: Vec<SelectedInventoryDiscount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub apply_free_shipping: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_select_future_inventory: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_price_increase_in_item_revision: Option<bool>,
+}
+
+/// Any promotion, as read from or written to its resource.
+///
+/// Deserializing tries the markdown shape first; it is the only one with
+/// `selectedInventoryDiscounts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Promotion {
+    Markdown(Box<ItemPriceMarkdown>),
+    Item(Box<ItemPromotion>),
+}
+
+impl Promotion {
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Promotion::Markdown(p) => p.promotion_id.as_deref(),
+            Promotion::Item(p) => p.promotion_id.as_deref(),
+        }
+    }
+
+    pub fn promotion_type(&self) -> PromotionType {
+        match self {
+            Promotion::Markdown(_) => PromotionType::MarkdownSale,
+            Promotion::Item(p) => p.promotion_type,
+        }
+    }
+
+    pub fn status(&self) -> PromotionStatus {
+        match self {
+            Promotion::Markdown(p) => p.promotion_status,
+            Promotion::Item(p) => p.promotion_status,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Promotion::Markdown(p) => &p.name,
+            Promotion::Item(p) => &p.name,
+        }
+    }
+
+    pub fn marketplace_id(&self) -> &str {
+        match self {
+            Promotion::Markdown(p) => &p.marketplace_id,
+            Promotion::Item(p) => &p.marketplace_id,
+        }
+    }
+
+    pub fn start_date(&self) -> DateTime<Utc> {
+        match self {
+            Promotion::Markdown(p) => p.start_date,
+            Promotion::Item(p) => p.start_date,
+        }
+    }
+
+    pub fn end_date(&self) -> DateTime<Utc> {
+        match self {
+            Promotion::Markdown(p) => p.end_date,
+            Promotion::Item(p) => p.end_date,
+        }
+    }
+
+    pub fn set_end_date(&mut self, end_date: DateTime<Utc>) {
+        match self {
+            Promotion::Markdown(p) => p.end_date = end_date,
+            Promotion::Item(p) => p.end_date = end_date,
+        }
+    }
+
+    /// Every inventory criterion of the promotion: one per markdown tier,
+    /// at most one otherwise.
+    pub fn inventory_criteria(&self) -> Vec<&InventoryCriterion> {
+        match self {
+            Promotion::Markdown(p) => p
+                .selected_inventory_discounts
+                .iter()
+                .map(|discount| &discount.inventory_criterion)
+                .collect(),
+            Promotion::Item(p) => p.inventory_criterion.iter().collect(),
+        }
+    }
+
+    /// SKUs the promotion names explicitly (`inventoryItems`).
+    pub fn skus(&self) -> Vec<&str> {
+        self.inventory_criteria()
+            .into_iter()
+            .flat_map(|criterion| &criterion.inventory_items)
+            .map(|item| item.inventory_reference_id.as_str())
+            .collect()
+    }
+
+    /// Listing IDs the promotion names explicitly (`listingIds`).
+    pub fn listing_ids(&self) -> Vec<&str> {
+        self.inventory_criteria()
+            .into_iter()
+            .flat_map(|criterion| &criterion.listing_ids)
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The same promotion without its read-only fields, as sent in a create
+    /// or update body.
+    pub fn writable(&self) -> Promotion {
+        let mut body = self.clone();
+        match &mut body {
+            Promotion::Markdown(p) => {
+                p.promotion_id = None;
+                p.promotion_href = None;
+            }
+            Promotion::Item(p) => {
+                p.promotion_id = None;
+                p.promotion_href = None;
+            }
+        }
+        body
+    }
+}
+
+impl From<ItemPromotion> for Promotion {
+    fn from(promotion: ItemPromotion) -> Self {
+        Promotion::Item(Box::new(promotion))
+    }
+}
+
+impl From<ItemPriceMarkdown> for Promotion {
+    fn from(markdown: ItemPriceMarkdown) -> Self {
+        Promotion::Markdown(Box::new(markdown))
+    }
+}
+
+/// One entry of `getPromotions`: the summary fields shared by every type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromotionDetail {
+    pub promotion_id: String,
+    #[serde(default)]
+    pub promotion_href: Option<String>,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub marketplace_id: String,
+    pub promotion_type: PromotionType,
+    pub promotion_status: PromotionStatus,
+    #[serde(default)]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub promotion_image_url: Option<String>,
+}
+
+/// A page of `getPromotions`. `next` is absent on the last page.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromotionsPage {
+    #[serde(default)]
+    pub href: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: Option<u32>,
+    #[serde(default)]
+    pub total: Option<u32>,
+    #[serde(default)]
+    pub next: Option<String>,
+    #[serde(default)]
+    pub prev: Option<String>,
+    #[serde(default)]
+    pub promotions: Vec<PromotionDetail>,
+}
+
+/// One entry of `getPromotionReports`: what a promotion has sold since it
+/// started. Amounts are to date, not per period.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromotionReportDetail {
+    pub promotion_id: String,
+    #[serde(default)]
+    pub promotion_report_id: Option<String>,
+    #[serde(default)]
+    pub promotion_href: Option<String>,
+    /// Sales of the promoted items without a discount applied.
+    #[serde(default)]
+    pub base_sale: Option<Amount>,
+    /// Sales with the promotion's discount applied.
+    #[serde(default)]
+    pub promotion_sale: Option<Amount>,
+    #[serde(default)]
+    pub total_sale: Option<Amount>,
+    #[serde(default)]
+    pub total_discount: Option<Amount>,
+    /// Units sold with the discount applied.
+    #[serde(default)]
+    pub items_sold_quantity: Option<i64>,
+    #[serde(default)]
+    pub number_of_orders_sold: Option<i64>,
+    #[serde(default)]
+    pub percentage_sales_lift: Option<String>,
+    #[serde(default)]
+    pub average_item_discount: Option<Amount>,
+    #[serde(default)]
+    pub average_item_revenue: Option<Amount>,
+    #[serde(default)]
+    pub average_order_discount: Option<Amount>,
+    #[serde(default)]
+    pub average_order_revenue: Option<Amount>,
+    #[serde(default)]
+    pub average_order_size: Option<String>,
+}
+
+/// A page of `getPromotionReports`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromotionReportsPage {
+    #[serde(default)]
+    pub href: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: Option<u32>,
+    #[serde(default)]
+    pub total: Option<u32>,
+    #[serde(default)]
+    pub next: Option<String>,
+    #[serde(default)]
+    pub prev: Option<String>,
+    #[serde(default)]
+    pub promotion_reports: Vec<PromotionReportDetail>,
+}
+
+/// `getPromotionSummaryReport`: every promotion on the marketplace together.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryReport {
+    #[serde(default)]
+    pub base_sale: Option<Amount>,
+    #[serde(default)]
+    pub promotion_sale: Option<Amount>,
+    #[serde(default)]
+    pub total_sale: Option<Amount>,
+    #[serde(default)]
+    pub percentage_sales_lift: Option<String>,
+}
+
+/// Error body of the REST APIs.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorResponse {
+    #[serde(default)]
+    pub errors: Vec<ErrorDetail>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorDetail {
+    #[serde(default)]
+    pub error_id: Option<u64>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub long_message: Option<String>,
+}
@@ -0,0 +1,16 @@
+//! eBay seller (private) API logic.
+
+pub mod export_profile;
+pub mod fees;
+pub mod listing_diff;
+pub mod listing_history;
+pub mod listing_rows;
+pub mod listing_sync;
+pub mod marketing;
+pub mod parquet_export;
+pub mod selling_lists;
+pub mod timeline;
+pub mod trading;
+pub mod warehouse;
+pub mod workbook;
+pub mod workbook_stream;
@@ -0,0 +1,296 @@
+//! Parquet export with a fixed schema.
+//!
+//! The CSVs from `IncrementalCsvExporter` carry every value as a string, so
+//! notebooks guess the types: Item IDs turn into floats and lose digits,
+//! prices pick up binary noise. Here each column's [`ColumnKind`] decides the
+//! Arrow type up front:
+//!
+//! | kind                 | Arrow type                    |
+//! |----------------------|-------------------------------|
+//! | Text                 | Utf8 (Item IDs included)      |
+//! | Integer              | Int64                         |
+//! | Decimal, Days        | Float64                       |
+//! | Price                | Decimal128(18, 2)             |
+//! | Bool                 | Boolean (not "YES"/"NO")      |
+//! | DateTime             | Timestamp(ms, UTC)            |
+//! | Date                 | Date32                        |
+//!
+//! Values are coerced as in [`ColumnKind::coerce`]; one that still does not
+//! fit its column is an error rather than a silent null. Columns outside the
+//! schema are left out.
+//!
+//! [`export_listings_parquet`] writes every exporter column, then the item
+//! specifics and any other columns the rows carry (as Utf8), named as in the
+//! XLSX export.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    ArrayBuilder, BooleanBuilder, Date32Builder, Decimal128Builder, Float64Builder, Int64Builder,
+    StringBuilder, TimestampMillisecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use super::listing_rows::{Cell, ColumnKind, ListingRow};
+use super::workbook::{format_file_size, parse_datetime, Column, ExportResult, SeenColumns};
+use crate::error::{Error, Result};
+
+/// Rows buffered before they are written out as one row group.
+pub const ROW_GROUP_SIZE: usize = 10_000;
+
+const PRICE_PRECISION: u8 = 18;
+const PRICE_SCALE: i8 = 2;
+
+/// The Arrow schema `columns` are written with. Every field is nullable; an
+/// empty cell is a null.
+pub fn schema(columns: &[Column]) -> Schema {
+    Schema::new(
+        columns
+            .iter()
+            .map(|column| Field::new(column.name.as_str(), data_type(column.kind), true))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn data_type(kind: ColumnKind) -> DataType {
+    match kind {
+        ColumnKind::Text => DataType::Utf8,
+        ColumnKind::Integer => DataType::Int64,
+        ColumnKind::Decimal | ColumnKind::Days => DataType::Float64,
+        ColumnKind::Price => DataType::Decimal128(PRICE_PRECISION, PRICE_SCALE),
+        ColumnKind::Bool => DataType::Boolean,
+        ColumnKind::DateTime => DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+        ColumnKind::Date => DataType::Date32,
+    }
+}
+
+/// Writes rows to a Parquet file one row group at a time.
+///
+/// After an error the file is incomplete; drop the writer and start over.
+pub struct ParquetWriter {
+    writer: ArrowWriter<File>,
+    path: PathBuf,
+    columns: Vec<Column>,
+    schema: SchemaRef,
+    builders: Vec<ColumnBuilder>,
+    total: usize,
+}
+
+impl ParquetWriter {
+    pub fn create(path: impl Into<PathBuf>, columns: &[Column]) -> Result<Self> {
+        let path = path.into();
+        let schema = Arc::new(schema(columns));
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(ROW_GROUP_SIZE)
+            .build();
+        let writer = ArrowWriter::try_new(File::create(&path)?, schema.clone(), Some(properties))?;
+        Ok(Self {
+            writer,
+            path,
+            columns: columns.to_vec(),
+            schema,
+            builders: columns
+                .iter()
+                .map(|column| ColumnBuilder::new(column.kind))
+                .collect::<Result<_>>()?,
+            total: 0,
+        })
+    }
+
+    pub fn append(&mut self, row: &ListingRow) -> Result<()> {
+        for (column, builder) in self.columns.iter().zip(&mut self.builders) {
+            let cell = column.kind.coerce(row.get(&column.name).clone());
+            if !builder.append(&cell) {
+                let item_id = row.item_id();
+                let at = if item_id.is_empty() {
+                    format!("row {}", self.total + 1)
+                } else {
+                    format!("item {item_id}")
+                };
+                return Err(Error::Parquet(format!(
+                    "{at}: {:?} value {cell:?} is not a valid {:?}",
+                    column.name, column.kind
+                )));
+            }
+        }
+        self.total += 1;
+        if self
+            .builders
+            .first()
+            .is_some_and(|b| b.len() >= ROW_GROUP_SIZE)
+        {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Rows appended so far.
+    pub fn rows_written(&self) -> usize {
+        self.total
+    }
+
+    pub fn finish(mut self) -> Result<ExportResult> {
+        self.flush()?;
+        self.writer.close()?;
+        let file_size = fs::metadata(&self.path)?.len();
+        log::info!(
+            "Parquet file created: {} ({}, {} rows)",
+            self.path.display(),
+            format_file_size(file_size),
+            self.total
+        );
+        Ok(ExportResult {
+            filename: self.path,
+            record_count: self.total,
+            file_size,
+        })
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.builders.first().is_none_or(|b| b.len() == 0) {
+            return Ok(());
+        }
+        let arrays: Vec<ArrayRef> = self
+            .builders
+            .iter_mut()
+            .map(ColumnBuilder::finish)
+            .collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+        self.writer.write(&batch)?;
+        Ok(())
+    }
+}
+
+enum ColumnBuilder {
+    Text(StringBuilder),
+    Integer(Int64Builder),
+    Float(Float64Builder),
+    Price(Decimal128Builder),
+    Bool(BooleanBuilder),
+    Timestamp(TimestampMillisecondBuilder),
+    Date(Date32Builder),
+}
+
+impl ColumnBuilder {
+    fn new(kind: ColumnKind) -> Result<Self> {
+        Ok(match kind {
+            ColumnKind::Text => ColumnBuilder::Text(StringBuilder::new()),
+            ColumnKind::Integer => ColumnBuilder::Integer(Int64Builder::new()),
+            ColumnKind::Decimal | ColumnKind::Days => ColumnBuilder::Float(Float64Builder::new()),
+            ColumnKind::Price => ColumnBuilder::Price(
+                Decimal128Builder::new().with_precision_and_scale(PRICE_PRECISION, PRICE_SCALE)?,
+            ),
+            ColumnKind::Bool => ColumnBuilder::Bool(BooleanBuilder::new()),
+            ColumnKind::DateTime => {
+                ColumnBuilder::Timestamp(TimestampMillisecondBuilder::new().with_timezone("UTC"))
+            }
+            ColumnKind::Date => ColumnBuilder::Date(Date32Builder::new()),
+        })
+    }
+
+    /// Append a coerced cell; false if it does not fit the column.
+    fn append(&mut self, cell: &Cell) -> bool {
+        if cell.is_empty() {
+            self.append_null();
+            return true;
+        }
+        match (self, cell) {
+            (ColumnBuilder::Text(b), Cell::Text(text)) => b.append_value(text),
+            (ColumnBuilder::Text(b), other) => b.append_value(other.to_string()),
+            (ColumnBuilder::Integer(b), Cell::Integer(value)) => b.append_value(*value),
+            (ColumnBuilder::Float(b), Cell::Number(value)) => b.append_value(*value),
+            (ColumnBuilder::Float(b), Cell::Integer(value)) => b.append_value(*value as f64),
+            (ColumnBuilder::Price(b), Cell::Number(value)) => {
+                b.append_value((value * 100.0).round() as i128)
+            }
+            (ColumnBuilder::Price(b), Cell::Integer(value)) => b.append_value(*value as i128 * 100),
+            (ColumnBuilder::Bool(b), Cell::Bool(value)) => b.append_value(*value),
+            (ColumnBuilder::Timestamp(b), Cell::Text(text)) => match parse_datetime(text) {
+                Some(at) => b.append_value(at.and_utc().timestamp_millis()),
+                None => return false,
+            },
+            (ColumnBuilder::Date(b), Cell::Text(text)) => match parse_date(text) {
+                Some(date) => b.append_value(days_since_epoch(date)),
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn append_null(&mut self) {
+        match self {
+            ColumnBuilder::Text(b) => b.append_null(),
+            ColumnBuilder::Integer(b) => b.append_null(),
+            ColumnBuilder::Float(b) => b.append_null(),
+            ColumnBuilder::Price(b) => b.append_null(),
+            ColumnBuilder::Bool(b) => b.append_null(),
+            ColumnBuilder::Timestamp(b) => b.append_null(),
+            ColumnBuilder::Date(b) => b.append_null(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            ColumnBuilder::Text(b) => b.len(),
+            ColumnBuilder::Integer(b) => b.len(),
+            ColumnBuilder::Float(b) => b.len(),
+            ColumnBuilder::Price(b) => b.len(),
+            ColumnBuilder::Bool(b) => b.len(),
+            ColumnBuilder::Timestamp(b) => b.len(),
+            ColumnBuilder::Date(b) => b.len(),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Text(b) => Arc::new(b.finish()),
+            ColumnBuilder::Integer(b) => Arc::new(b.finish()),
+            ColumnBuilder::Float(b) => Arc::new(b.finish()),
+            ColumnBuilder::Price(b) => Arc::new(b.finish()),
+            ColumnBuilder::Bool(b) => Arc::new(b.finish()),
+            ColumnBuilder::Timestamp(b) => Arc::new(b.finish()),
+            ColumnBuilder::Date(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .or_else(|| parse_datetime(text).as_ref().map(NaiveDateTime::date))
+}
+
+fn days_since_epoch(date: NaiveDate) -> i32 {
+    (date - DateTime::UNIX_EPOCH.date_naive()).num_days() as i32
+}
+
+/// Write listing rows to `path`: every [`LISTING_COLUMNS`] column, then the
+/// item specifics and other columns the rows carry. `rows` is read twice,
+/// first for the column names.
+///
+/// [`LISTING_COLUMNS`]: super::listing_rows::LISTING_COLUMNS
+pub fn export_listings_parquet<I>(rows: I, path: impl AsRef<Path>) -> Result<ExportResult>
+where
+    I: IntoIterator<Item = ListingRow> + Clone,
+{
+    let path = path.as_ref();
+    log::info!("Creating Parquet file: {}", path.display());
+
+    let mut seen = SeenColumns::with_every_listing_column();
+    rows.clone().into_iter().for_each(|row| seen.add(&row));
+
+    let mut writer = ParquetWriter::create(path, &seen.columns())?;
+    for row in rows {
+        writer.append(&seen.normalize(row))?;
+    }
+    writer.finish()
+}
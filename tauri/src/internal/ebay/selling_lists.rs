@@ -0,0 +1,259 @@
+//! My eBay selling lists via `GetMyeBaySelling`.
+//!
+//! Port of `fetchMyeBaySellingData` / `fetchMyeBayCategory` from
+//! `ebay-listings-exporter.js`. The JS declared active, sold and unsold arrays
+//! but only ever requested `ActiveList`; this fetches every list, pages each
+//! one by its `PaginationResult`, and tags every item with the list it came
+//! from so an export can show where each listing is in its lifecycle.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::trading::calls::{
+    GetMyeBaySellingResponse, ItemListCustomization, PaginatedItemArray,
+    PaginatedOrderTransactionArray, Transaction,
+};
+use super::trading::types::{Pagination, PaginationResult};
+use super::trading::{GetMyeBaySelling, Item, TradingClient};
+use crate::error::Result;
+
+/// Largest `EntriesPerPage` eBay accepts for these lists.
+pub const MAX_ENTRIES_PER_PAGE: u32 = 200;
+
+/// Largest `DurationInDays` for the sold, unsold and deleted lists.
+pub const MAX_DURATION_IN_DAYS: u32 = 60;
+
+/// One of the `GetMyeBaySelling` containers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SellingList {
+    Active,
+    Scheduled,
+    Sold,
+    Unsold,
+    DeletedFromSold,
+}
+
+impl SellingList {
+    pub const ALL: [SellingList; 5] = [
+        SellingList::Active,
+        SellingList::Scheduled,
+        SellingList::Sold,
+        SellingList::Unsold,
+        SellingList::DeletedFromSold,
+    ];
+
+    /// Request/response element name, e.g. `ActiveList`.
+    pub fn element_name(self) -> &'static str {
+        match self {
+            SellingList::Active => "ActiveList",
+            SellingList::Scheduled => "ScheduledList",
+            SellingList::Sold => "SoldList",
+            SellingList::Unsold => "UnsoldList",
+            SellingList::DeletedFromSold => "DeletedFromSoldList",
+        }
+    }
+
+    /// Value for the exporter's "Listing Status Category" column.
+    pub fn label(self) -> &'static str {
+        match self {
+            SellingList::Active => "Active",
+            SellingList::Scheduled => "Scheduled",
+            SellingList::Sold => "Sold",
+            SellingList::Unsold => "Unsold",
+            SellingList::DeletedFromSold => "Deleted From Sold",
+        }
+    }
+
+    fn has_duration(self) -> bool {
+        matches!(
+            self,
+            SellingList::Sold | SellingList::Unsold | SellingList::DeletedFromSold
+        )
+    }
+}
+
+/// An item together with the list it was found in. Items from the sold lists
+/// also carry their transaction (with `item` moved out into [`Self::item`]).
+#[derive(Debug, Clone)]
+pub struct ListedItem {
+    pub list: SellingList,
+    pub item: Item,
+    pub transaction: Option<Transaction>,
+}
+
+pub struct SellingListsFetcher<'a> {
+    client: &'a TradingClient,
+    entries_per_page: u32,
+    duration_in_days: u32,
+    page_delay: Duration,
+}
+
+impl<'a> SellingListsFetcher<'a> {
+    pub fn new(client: &'a TradingClient) -> Self {
+        Self {
+            client,
+            entries_per_page: MAX_ENTRIES_PER_PAGE,
+            duration_in_days: MAX_DURATION_IN_DAYS,
+            page_delay: Duration::from_millis(500),
+        }
+    }
+
+    pub fn with_entries_per_page(mut self, entries_per_page: u32) -> Self {
+        self.entries_per_page = entries_per_page.clamp(1, MAX_ENTRIES_PER_PAGE);
+        self
+    }
+
+    /// How far back the sold, unsold and deleted lists go.
+    pub fn with_duration_in_days(mut self, days: u32) -> Self {
+        self.duration_in_days = days.clamp(1, MAX_DURATION_IN_DAYS);
+        self
+    }
+
+    pub fn with_page_delay(mut self, delay: Duration) -> Self {
+        self.page_delay = delay;
+        self
+    }
+
+    /// Every list, in [`SellingList::ALL`] order.
+    pub async fn fetch_all(&self) -> Result<Vec<ListedItem>> {
+        let mut all = Vec::new();
+        for list in SellingList::ALL {
+            all.extend(self.fetch_list(list).await?);
+        }
+
+        log::info!(
+            "My eBay data: {} active, {} scheduled, {} sold, {} unsold, {} deleted from sold",
+            count(&all, SellingList::Active),
+            count(&all, SellingList::Scheduled),
+            count(&all, SellingList::Sold),
+            count(&all, SellingList::Unsold),
+            count(&all, SellingList::DeletedFromSold)
+        );
+        Ok(all)
+    }
+
+    /// One list, following `TotalNumberOfPages` to the last page.
+    pub async fn fetch_list(&self, list: SellingList) -> Result<Vec<ListedItem>> {
+        let description = list.label().to_lowercase();
+        log::info!("Fetching {description} listings...");
+
+        let mut all = Vec::new();
+        let mut page_number = 1;
+        loop {
+            let response = self
+                .client
+                .execute(&self.request(list, page_number))
+                .await?;
+            let (items, pagination) = take_list(response, list);
+
+            if items.is_empty() {
+                log::info!("No more {description} listings found");
+                break;
+            }
+            let found = items.len();
+            all.extend(items);
+
+            let total_pages = pagination.map_or(1, |p| p.total_number_of_pages);
+            log::info!(
+                "{description} listings page {page_number}/{total_pages}: {found} items (total: {})",
+                all.len()
+            );
+            if page_number >= total_pages {
+                break;
+            }
+
+            page_number += 1;
+            tokio::time::sleep(self.page_delay).await;
+        }
+
+        Ok(all)
+    }
+
+    fn request(&self, list: SellingList, page_number: u32) -> GetMyeBaySelling {
+        let customization = ItemListCustomization {
+            include: true,
+            sort: (list == SellingList::Active).then(|| "TimeLeft".to_string()),
+            duration_in_days: list.has_duration().then_some(self.duration_in_days),
+            pagination: Some(Pagination {
+                entries_per_page: self.entries_per_page,
+                page_number,
+            }),
+        };
+
+        let mut call = GetMyeBaySelling::default();
+        let slot = match list {
+            SellingList::Active => &mut call.active_list,
+            SellingList::Scheduled => &mut call.scheduled_list,
+            SellingList::Sold => &mut call.sold_list,
+            SellingList::Unsold => &mut call.unsold_list,
+            SellingList::DeletedFromSold => &mut call.deleted_from_sold_list,
+        };
+        *slot = Some(customization);
+        call
+    }
+}
+
+fn count(items: &[ListedItem], list: SellingList) -> usize {
+    items.iter().filter(|i| i.list == list).count()
+}
+
+/// Pull the requested container out of a response and tag its items.
+fn take_list(
+    response: GetMyeBaySellingResponse,
+    list: SellingList,
+) -> (Vec<ListedItem>, Option<PaginationResult>) {
+    match list {
+        SellingList::Active => from_items(response.active_list, list),
+        SellingList::Scheduled => from_items(response.scheduled_list, list),
+        SellingList::Unsold => from_items(response.unsold_list, list),
+        SellingList::Sold => from_transactions(response.sold_list, list),
+        SellingList::DeletedFromSold => from_transactions(response.deleted_from_sold_list, list),
+    }
+}
+
+fn from_items(
+    container: Option<PaginatedItemArray>,
+    list: SellingList,
+) -> (Vec<ListedItem>, Option<PaginationResult>) {
+    let Some(container) = container else {
+        return (Vec::new(), None);
+    };
+    let items = container
+        .item_array
+        .map(|a| a.items)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|item| ListedItem {
+            list,
+            item,
+            transaction: None,
+        })
+        .collect();
+    (items, container.pagination_result)
+}
+
+fn from_transactions(
+    container: Option<PaginatedOrderTransactionArray>,
+    list: SellingList,
+) -> (Vec<ListedItem>, Option<PaginationResult>) {
+    let Some(container) = container else {
+        return (Vec::new(), None);
+    };
+    let items = container
+        .order_transaction_array
+        .map(|a| a.order_transactions)
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|ot| ot.into_transactions())
+        .filter_map(|mut transaction| {
+            let item = transaction.item.take()?;
+            Some(ListedItem {
+                list,
+                item,
+                transaction: Some(transaction),
+            })
+        })
+        .collect();
+    (items, container.pagination_result)
+}
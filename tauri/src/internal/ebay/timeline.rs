@@ -0,0 +1,305 @@
+//! Price, quantity and engagement history per listing.
+//!
+//! `processSellerListings` captured Current Price, Quantity Available, Watch
+//! Count and Hit Count as they were at one moment. The [`Warehouse`] keeps
+//! them from every sync that fetched an item; this reads them back as series,
+//! answers questions such as "price changes in the last 30 days" or "listings
+//! whose watchers doubled this week", and shapes them for the front end's
+//! line charts.
+//!
+//! A point exists only for syncs that fetched the item. An incremental sync
+//! fetches what eBay reports as modified, so between two points the value is
+//! taken to be unchanged.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::warehouse::{from_timestamp, Warehouse};
+use crate::error::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Metric {
+    Price,
+    QuantityAvailable,
+    WatchCount,
+    HitCount,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 4] = [
+        Metric::Price,
+        Metric::QuantityAvailable,
+        Metric::WatchCount,
+        Metric::HitCount,
+    ];
+
+    /// The exporter column the metric comes from.
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::Price => "Current Price",
+            Metric::QuantityAvailable => "Quantity Available",
+            Metric::WatchCount => "Watch Count",
+            Metric::HitCount => "Hit Count",
+        }
+    }
+}
+
+/// The tracked values of one listing after one sync.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricPoint {
+    pub sync_id: i64,
+    pub recorded_at: DateTime<Utc>,
+    pub price: Option<f64>,
+    pub currency: Option<String>,
+    pub quantity_available: Option<i64>,
+    pub watch_count: Option<i64>,
+    pub hit_count: Option<i64>,
+}
+
+impl MetricPoint {
+    pub fn value(&self, metric: Metric) -> Option<f64> {
+        match metric {
+            Metric::Price => self.price,
+            Metric::QuantityAvailable => self.quantity_available.map(|v| v as f64),
+            Metric::WatchCount => self.watch_count.map(|v| v as f64),
+            Metric::HitCount => self.hit_count.map(|v| v as f64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceChange {
+    pub item_id: String,
+    pub title: Option<String>,
+    pub currency: Option<String>,
+    pub before: f64,
+    pub after: f64,
+    /// When the sync that saw the new price ran.
+    pub changed_at: DateTime<Utc>,
+    pub sync_id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricGrowth {
+    pub item_id: String,
+    pub title: Option<String>,
+    pub metric: Metric,
+    pub before: f64,
+    pub after: f64,
+    /// `after / before`.
+    pub ratio: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartPoint {
+    pub at: DateTime<Utc>,
+    pub value: f64,
+}
+
+/// One line of a chart: a metric over time for one listing.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartSeries {
+    pub item_id: String,
+    /// The listing title, or the Item ID when there is none.
+    pub label: String,
+    pub metric: Metric,
+    pub points: Vec<ChartPoint>,
+}
+
+pub struct Timeline<'a> {
+    warehouse: &'a Warehouse,
+    account: String,
+}
+
+impl<'a> Timeline<'a> {
+    pub fn new(warehouse: &'a Warehouse, account: impl Into<String>) -> Self {
+        Self {
+            warehouse,
+            account: account.into(),
+        }
+    }
+
+    /// Every recorded point for one listing, oldest first.
+    pub fn points(&self, item_id: &str) -> Result<Vec<MetricPoint>> {
+        Ok(self
+            .load(Some(item_id))?
+            .remove(item_id)
+            .unwrap_or_default())
+    }
+
+    /// Price changes seen by syncs at or after `since`, newest first.
+    pub fn price_changes(&self, since: DateTime<Utc>) -> Result<Vec<PriceChange>> {
+        let titles = self.titles()?;
+        let mut changes = Vec::new();
+        for (item_id, points) in self.load(None)? {
+            for pair in points.windows(2) {
+                let (previous, current) = (&pair[0], &pair[1]);
+                let (Some(before), Some(after)) = (previous.price, current.price) else {
+                    continue;
+                };
+                if current.recorded_at < since || before == after {
+                    continue;
+                }
+                changes.push(PriceChange {
+                    item_id: item_id.clone(),
+                    title: titles.get(&item_id).cloned(),
+                    currency: current.currency.clone(),
+                    before,
+                    after,
+                    changed_at: current.recorded_at,
+                    sync_id: current.sync_id,
+                });
+            }
+        }
+        changes.sort_by_key(|change| std::cmp::Reverse(change.changed_at));
+        Ok(changes)
+    }
+
+    /// Listings whose `metric` grew by at least `factor` since `since`, e.g.
+    /// `growth(Metric::WatchCount, now - 7 days, 2.0)` for "watchers doubled
+    /// this week". The baseline is the last value at or before `since` (the
+    /// first one after it for listings first seen later); listings with a
+    /// zero baseline are left out. Largest growth first.
+    pub fn growth(
+        &self,
+        metric: Metric,
+        since: DateTime<Utc>,
+        factor: f64,
+    ) -> Result<Vec<MetricGrowth>> {
+        let titles = self.titles()?;
+        let mut grown = Vec::new();
+        for (item_id, points) in self.load(None)? {
+            let values: Vec<(DateTime<Utc>, f64)> = points
+                .iter()
+                .filter_map(|p| Some((p.recorded_at, p.value(metric)?)))
+                .collect();
+            let baseline = values
+                .iter()
+                .rev()
+                .find(|(at, _)| *at <= since)
+                .or_else(|| values.first());
+            let (Some(&(_, before)), Some(&(_, after))) = (baseline, values.last()) else {
+                continue;
+            };
+            if before <= 0.0 || after < before * factor {
+                continue;
+            }
+            grown.push(MetricGrowth {
+                title: titles.get(&item_id).cloned(),
+                item_id,
+                metric,
+                before,
+                after,
+                ratio: after / before,
+            });
+        }
+        grown.sort_by(|a, b| b.ratio.total_cmp(&a.ratio));
+        Ok(grown)
+    }
+
+    /// Line-chart data for `item_ids`, from `since` onward when given. The
+    /// last value before `since` is carried to `since` so lines start at the
+    /// left edge of the chart.
+    pub fn chart(
+        &self,
+        item_ids: &[&str],
+        metric: Metric,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ChartSeries>> {
+        let titles = self.titles()?;
+        let mut series = Vec::with_capacity(item_ids.len());
+        for &item_id in item_ids {
+            let mut points = Vec::new();
+            let mut carried = None;
+            for point in self.points(item_id)? {
+                let Some(value) = point.value(metric) else {
+                    continue;
+                };
+                match since {
+                    Some(since) if point.recorded_at < since => carried = Some(value),
+                    _ => points.push(ChartPoint {
+                        at: point.recorded_at,
+                        value,
+                    }),
+                }
+            }
+            if let (Some(since), Some(value)) = (since, carried) {
+                if points.first().is_none_or(|first| first.at > since) {
+                    points.insert(0, ChartPoint { at: since, value });
+                }
+            }
+
+            series.push(ChartSeries {
+                item_id: item_id.to_string(),
+                label: titles
+                    .get(item_id)
+                    .cloned()
+                    .unwrap_or_else(|| item_id.to_string()),
+                metric,
+                points,
+            });
+        }
+        Ok(series)
+    }
+
+    /// Points per Item ID, each list oldest first.
+    fn load(&self, item_id: Option<&str>) -> Result<BTreeMap<String, Vec<MetricPoint>>> {
+        let conn = self.warehouse.conn();
+        let mut stmt = conn.prepare(
+            "SELECT item_id, sync_id, recorded_at, price, currency,
+                    quantity_available, watch_count, hit_count
+             FROM listing_metrics
+             WHERE account = ?1 AND (?2 IS NULL OR item_id = ?2)
+             ORDER BY item_id, recorded_at, sync_id",
+        )?;
+        let rows = stmt.query_map(params![self.account, item_id], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                MetricPoint {
+                    sync_id: r.get(1)?,
+                    recorded_at: from_timestamp(r.get(2)?),
+                    price: r.get(3)?,
+                    currency: r.get(4)?,
+                    quantity_available: r.get(5)?,
+                    watch_count: r.get(6)?,
+                    hit_count: r.get(7)?,
+                },
+            ))
+        })?;
+
+        let mut points: BTreeMap<String, Vec<MetricPoint>> = BTreeMap::new();
+        for row in rows {
+            let (item_id, point) = row?;
+            points.entry(item_id).or_default().push(point);
+        }
+        Ok(points)
+    }
+
+    fn titles(&self) -> Result<HashMap<String, String>> {
+        let conn = self.warehouse.conn();
+        let mut stmt = conn.prepare(
+            "SELECT item_id, json_extract(row_json, '$.Title') FROM listings
+             WHERE account = ?1",
+        )?;
+        let titles = stmt
+            .query_map([&self.account], |r| {
+                Ok((r.get::<_, String>(0)?, r.get::<_, Option<String>>(1)?))
+            })?
+            .filter_map(|row| match row {
+                Ok((item_id, Some(title))) => Some(Ok((item_id, title))),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(titles)
+    }
+}
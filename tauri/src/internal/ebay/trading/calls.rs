@@ -0,0 +1,426 @@
+//! Request and response structs for the Trading API calls we make.
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::types::{Amount, Item, ItemArray, Pagination, PaginationResult, User};
+
+/// A Trading API call: the request body plus the response it produces.
+pub trait TradingCall: Serialize {
+    /// Value of `X-EBAY-API-CALL-NAME`; also names the request and response
+    /// root elements (`<{CALL_NAME}Request>` / `<{CALL_NAME}Response>`).
+    const CALL_NAME: &'static str;
+
+    type Response: DeserializeOwned;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DetailLevel {
+    ReturnAll,
+    ItemReturnAttributes,
+    ItemReturnDescription,
+    ReturnHeaders,
+    ReturnSummary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GranularityLevel {
+    Coarse,
+    Medium,
+    Fine,
+}
+
+// ==================== GetSellerList ====================
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetSellerList {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail_level: Option<DetailLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time_from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time_to: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time_from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time_to: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granularity_level: Option<GranularityLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_watch_count: Option<bool>,
+    #[serde(rename = "OutputSelector", skip_serializing_if = "Vec::is_empty")]
+    pub output_selectors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetSellerListResponse {
+    #[serde(default)]
+    pub has_more_items: bool,
+    #[serde(default)]
+    pub item_array: Option<ItemArray>,
+    #[serde(default)]
+    pub pagination_result: Option<PaginationResult>,
+    #[serde(default)]
+    pub page_number: Option<u32>,
+    #[serde(default)]
+    pub returned_item_count_actual: Option<u32>,
+}
+
+impl GetSellerListResponse {
+    pub fn into_items(self) -> Vec<Item> {
+        self.item_array.map(|a| a.items).unwrap_or_default()
+    }
+}
+
+impl TradingCall for GetSellerList {
+    const CALL_NAME: &'static str = "GetSellerList";
+    type Response = GetSellerListResponse;
+}
+
+// ==================== GetSellerEvents ====================
+
+/// Listings created, revised or ended in a time range. `GetSellerList` has no
+/// modification-time filter, so incremental syncs use this call.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetSellerEvents {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail_level: Option<DetailLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mod_time_from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mod_time_to: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_watch_count: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_item_filter: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetSellerEventsResponse {
+    #[serde(default)]
+    pub time_to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub item_array: Option<ItemArray>,
+    #[serde(default)]
+    pub returned_item_count_actual: Option<u32>,
+}
+
+impl GetSellerEventsResponse {
+    pub fn into_items(self) -> Vec<Item> {
+        self.item_array.map(|a| a.items).unwrap_or_default()
+    }
+}
+
+impl TradingCall for GetSellerEvents {
+    const CALL_NAME: &'static str = "GetSellerEvents";
+    type Response = GetSellerEventsResponse;
+}
+
+// ==================== GetItem ====================
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetItem {
+    #[serde(rename = "ItemID")]
+    pub item_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail_level: Option<DetailLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_item_specifics: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_watch_count: Option<bool>,
+}
+
+impl GetItem {
+    pub fn new(item_id: impl Into<String>) -> Self {
+        Self {
+            item_id: item_id.into(),
+            detail_level: Some(DetailLevel::ReturnAll),
+            include_item_specifics: Some(true),
+            include_watch_count: Some(true),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetItemResponse {
+    #[serde(default)]
+    pub item: Option<Item>,
+}
+
+impl TradingCall for GetItem {
+    const CALL_NAME: &'static str = "GetItem";
+    type Response = GetItemResponse;
+}
+
+// ==================== GetMyeBaySelling ====================
+
+/// `ItemListCustomizationType`: selects one list and how to page it.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ItemListCustomization {
+    pub include: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_in_days: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetMyeBaySelling {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_list: Option<ItemListCustomization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_list: Option<ItemListCustomization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sold_list: Option<ItemListCustomization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsold_list: Option<ItemListCustomization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_from_sold_list: Option<ItemListCustomization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_from_unsold_list: Option<ItemListCustomization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail_level: Option<DetailLevel>,
+}
+
+/// `PaginatedItemArrayType`: ActiveList, ScheduledList, UnsoldList.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PaginatedItemArray {
+    #[serde(default)]
+    pub item_array: Option<ItemArray>,
+    #[serde(default)]
+    pub pagination_result: Option<PaginationResult>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Buyer {
+    #[serde(rename = "UserID", default)]
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Transaction {
+    #[serde(rename = "TransactionID", default)]
+    pub transaction_id: Option<String>,
+    #[serde(default)]
+    pub item: Option<Item>,
+    #[serde(default)]
+    pub quantity_purchased: Option<i64>,
+    #[serde(default)]
+    pub created_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub paid_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub shipped_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub buyer: Option<Buyer>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TransactionArray {
+    #[serde(rename = "Transaction", default)]
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Order {
+    #[serde(rename = "OrderID", default)]
+    pub order_id: Option<String>,
+    #[serde(default)]
+    pub transaction_array: Option<TransactionArray>,
+}
+
+/// Either a single-line-item transaction or a multi-line-item order.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct OrderTransaction {
+    #[serde(default)]
+    pub transaction: Option<Transaction>,
+    #[serde(default)]
+    pub order: Option<Order>,
+}
+
+impl OrderTransaction {
+    /// All transactions, whether they came back bare or wrapped in an order.
+    pub fn into_transactions(self) -> Vec<Transaction> {
+        let mut transactions: Vec<Transaction> = self.transaction.into_iter().collect();
+        if let Some(array) = self.order.and_then(|o| o.transaction_array) {
+            transactions.extend(array.transactions);
+        }
+        transactions
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OrderTransactionArray {
+    #[serde(rename = "OrderTransaction", default)]
+    pub order_transactions: Vec<OrderTransaction>,
+}
+
+/// `PaginatedOrderTransactionArrayType`: SoldList, DeletedFromSoldList.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PaginatedOrderTransactionArray {
+    #[serde(default)]
+    pub order_transaction_array: Option<OrderTransactionArray>,
+    #[serde(default)]
+    pub pagination_result: Option<PaginationResult>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetMyeBaySellingResponse {
+    #[serde(default)]
+    pub active_list: Option<PaginatedItemArray>,
+    #[serde(default)]
+    pub scheduled_list: Option<PaginatedItemArray>,
+    #[serde(default)]
+    pub sold_list: Option<PaginatedOrderTransactionArray>,
+    #[serde(default)]
+    pub unsold_list: Option<PaginatedItemArray>,
+    #[serde(default)]
+    pub deleted_from_sold_list: Option<PaginatedOrderTransactionArray>,
+    #[serde(default)]
+    pub deleted_from_unsold_list: Option<PaginatedItemArray>,
+}
+
+impl TradingCall for GetMyeBaySelling {
+    const CALL_NAME: &'static str = "GetMyeBaySelling";
+    type Response = GetMyeBaySellingResponse;
+}
+
+// ==================== GetUser ====================
+
+/// Returns the authenticated user (the seller) when `UserID` is omitted.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetUser {
+    #[serde(rename = "UserID", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail_level: Option<DetailLevel>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetUserResponse {
+    #[serde(default)]
+    pub user: Option<User>,
+}
+
+impl TradingCall for GetUser {
+    const CALL_NAME: &'static str = "GetUser";
+    type Response = GetUserResponse;
+}
+
+// ==================== GetAccount ====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountHistorySelection {
+    LastInvoice,
+    SpecifiedInvoice,
+    BetweenSpecifiedDates,
+}
+
+/// Seller account activity: fees, credits and payments.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetAccount {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_history_selection: Option<AccountHistorySelection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub begin_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<DateTime<Utc>>,
+    #[serde(rename = "ItemID", skip_serializing_if = "Option::is_none")]
+    pub item_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_balance: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_summary: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+}
+
+/// One line of account activity.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AccountEntry {
+    /// `AccountDetailEntryCodeType`, e.g. `FeeInsertion` or
+    /// `CreditFinalValue`.
+    #[serde(default)]
+    pub account_details_entry_type: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub gross_detail_amount: Option<Amount>,
+    #[serde(default)]
+    pub net_detail_amount: Option<Amount>,
+    #[serde(rename = "ItemID", default)]
+    pub item_id: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub ref_number: Option<String>,
+    #[serde(rename = "OrderLineItemID", default)]
+    pub order_line_item_id: Option<String>,
+    #[serde(rename = "TransactionID", default)]
+    pub transaction_id: Option<String>,
+    #[serde(rename = "VATPercent", default)]
+    pub vat_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct AccountEntries {
+    #[serde(rename = "AccountEntry", default)]
+    pub entries: Vec<AccountEntry>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetAccountResponse {
+    #[serde(rename = "AccountID", default)]
+    pub account_id: Option<String>,
+    /// Currency of the account; entries without a `currencyID` are in it.
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub account_entries: Option<AccountEntries>,
+    #[serde(default)]
+    pub pagination_result: Option<PaginationResult>,
+    #[serde(default)]
+    pub has_more_entries: bool,
+    #[serde(default)]
+    pub page_number: Option<u32>,
+}
+
+impl GetAccountResponse {
+    pub fn into_entries(self) -> Vec<AccountEntry> {
+        self.account_entries.map(|a| a.entries).unwrap_or_default()
+    }
+}
+
+impl TradingCall for GetAccount {
+    const CALL_NAME: &'static str = "GetAccount";
+    type Response = GetAccountResponse;
+}
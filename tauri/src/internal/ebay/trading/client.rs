@@ -0,0 +1,241 @@
+//! Trading API (XML) client.
+//!
+//! Port of `TradingApiClient` from `ebay-listings-exporter.js`: same endpoint
+//! and `X-EBAY-API-*` headers, OAuth via `X-EBAY-API-IAF-TOKEN`, but requests
+//! and responses are serde structs and `<Errors>` come back as
+//! [`TradingErrors`] instead of a joined message string.
+
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use url::Url;
+
+use super::calls::TradingCall;
+use super::types::{Ack, ApiError, SeverityCode, TradingErrors, XMLNS};
+use crate::accounts::Account;
+use crate::adapters::ebay::rate_limit::{Api, Throttle};
+use crate::adapters::ebay::retry::{self, Retrier};
+use crate::adapters::ebay::TokenSource;
+use crate::environment::Environment;
+use crate::error::{Error, Result};
+
+pub const ENDPOINT_PATH: &str = "/ws/api.dll";
+
+/// Circuit name for the retrier, the same whatever the endpoint URL.
+const CIRCUIT: &str = "trading";
+
+/// Trading API schema version sent as `<Version>` and compatibility level.
+pub const VERSION: &str = "1291";
+
+/// Error codes eBay returns when the IAF (OAuth) token is rejected.
+pub(crate) const INVALID_TOKEN_CODES: &[&str] = &["931", "932", "21916984", "21917053"];
+
+/// Just the common response fields, read before the call-specific struct.
+#[derive(Debug, Deserialize)]
+struct ResponseEnvelope {
+    #[serde(rename = "Ack")]
+    ack: Ack,
+    #[serde(rename = "Errors", default)]
+    errors: Vec<ApiError>,
+}
+
+#[derive(Clone)]
+pub struct TradingClient {
+    http: reqwest::Client,
+    endpoint: Url,
+    app_id: String,
+    site_id: u32,
+    tokens: Arc<dyn TokenSource>,
+    throttle: Option<Throttle>,
+    retrier: Option<Arc<Retrier>>,
+}
+
+impl std::fmt::Debug for TradingClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TradingClient")
+            .field("endpoint", &self.endpoint.as_str())
+            .field("app_id", &self.app_id)
+            .field("site_id", &self.site_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TradingClient {
+    pub fn new(
+        environment: Environment,
+        app_id: impl Into<String>,
+        site_id: u32,
+        tokens: Arc<dyn TokenSource>,
+    ) -> Self {
+        let endpoint = environment
+            .api_base_url()
+            .join(ENDPOINT_PATH)
+            .expect("static URL");
+
+        Self {
+            http: reqwest::Client::new(),
+            endpoint,
+            app_id: app_id.into(),
+            site_id,
+            tokens,
+            throttle: None,
+            retrier: None,
+        }
+    }
+
+    pub fn for_account(account: &Account) -> Self {
+        Self::new(
+            account.environment(),
+            account.config().client_id.clone(),
+            account.site_id(),
+            account.user_tokens(),
+        )
+        .with_throttle(account.user_throttle(Api::Trading))
+        .with_retrier(account.retrier())
+    }
+
+    /// Override the endpoint URL (used by tests).
+    pub fn with_endpoint(mut self, endpoint: Url) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// Wait on `throttle` before every request.
+    pub fn with_throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Retry failed calls under `retrier`'s policy.
+    pub fn with_retrier(mut self, retrier: Arc<Retrier>) -> Self {
+        self.retrier = Some(retrier);
+        self
+    }
+
+    /// Send `call` and parse its response. If eBay rejects the token, the
+    /// call is retried once with a fresh one; other failures are retried if
+    /// a retrier is set.
+    pub async fn execute<C: TradingCall>(&self, call: &C) -> Result<C::Response> {
+        let xml = build_xml_request(call)?;
+        match &self.retrier {
+            Some(retrier) => retrier.run(CIRCUIT, || self.execute_once::<C>(&xml)).await,
+            None => self.execute_once::<C>(&xml).await,
+        }
+    }
+
+    async fn execute_once<C: TradingCall>(&self, xml: &str) -> Result<C::Response> {
+        let token = self.tokens.bearer_token().await?;
+        match self.send::<C::Response>(C::CALL_NAME, xml, &token).await {
+            Err(Error::Trading(errors)) if is_token_error(&errors) => {
+                log::warn!(
+                    "{} rejected the access token, retrying with a fresh one",
+                    C::CALL_NAME
+                );
+                self.tokens.invalidate(&token).await?;
+                let token = self.tokens.bearer_token().await?;
+                self.send::<C::Response>(C::CALL_NAME, xml, &token).await
+            }
+            other => other,
+        }
+    }
+
+    async fn send<R: DeserializeOwned>(
+        &self,
+        call_name: &str,
+        xml: &str,
+        token: &str,
+    ) -> Result<R> {
+        if let Some(throttle) = &self.throttle {
+            throttle.acquire().await?;
+        }
+
+        let response = self
+            .http
+            .post(self.endpoint.clone())
+            .header("Content-Type", "text/xml")
+            .header("X-EBAY-API-CALL-NAME", call_name)
+            .header("X-EBAY-API-SITEID", self.site_id.to_string())
+            .header("X-EBAY-API-APP-NAME", &self.app_id)
+            .header("X-EBAY-API-VERSION", VERSION)
+            .header("X-EBAY-API-COMPATIBILITY-LEVEL", VERSION)
+            .header("X-EBAY-API-REQUEST-ENCODING", "XML")
+            .header("X-EBAY-API-IAF-TOKEN", token)
+            .body(xml.to_string())
+            .send()
+            .await?;
+
+        let status = response.status();
+        let retry_after = retry::retry_after(response.headers());
+        let body = response.text().await?;
+
+        if !status.is_success() && !body.contains("Response") {
+            return Err(Error::Api {
+                status: status.as_u16(),
+                message: body,
+                retry_after,
+            });
+        }
+
+        parse_response(call_name, &body)
+    }
+}
+
+/// Wrap the serialized call in `<{CallName}Request>` with the namespace and
+/// `<Version>`, like `buildXmlRequest` does.
+pub fn build_xml_request<C: TradingCall>(call: &C) -> Result<String> {
+    let body = quick_xml::se::to_string_with_root("Body", call)?;
+    let inner = body
+        .strip_prefix("<Body>")
+        .and_then(|b| b.strip_suffix("</Body>"))
+        .unwrap_or("");
+
+    Ok(format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><{name}Request xmlns="{XMLNS}"><Version>{VERSION}</Version>{inner}</{name}Request>"#,
+        name = C::CALL_NAME
+    ))
+}
+
+/// Check `Ack` and `<Errors>`, then parse the call-specific response.
+pub fn parse_response<R: DeserializeOwned>(call_name: &str, body: &str) -> Result<R> {
+    let root = format!("<{call_name}Response");
+    if !body.contains(&root) {
+        return Err(Error::InvalidResponse(format!(
+            "Invalid response format for {call_name}"
+        )));
+    }
+
+    let envelope: ResponseEnvelope = quick_xml::de::from_str(body)?;
+
+    match envelope.ack {
+        Ack::Failure => {
+            return Err(Error::Trading(TradingErrors {
+                call_name: call_name.to_string(),
+                ack: envelope.ack,
+                errors: envelope.errors,
+            }));
+        }
+        // Part of the request failed but the rest of the response (e.g. a
+        // `GetSellerList` page) is still good; keep it and log what failed.
+        Ack::PartialFailure => {
+            for error in &envelope.errors {
+                log::warn!("{call_name} partly failed: {error}");
+            }
+        }
+        _ => {
+            for warning in envelope
+                .errors
+                .iter()
+                .filter(|e| e.severity_code == SeverityCode::Warning)
+            {
+                log::warn!("{call_name} warning {warning}");
+            }
+        }
+    }
+
+    Ok(quick_xml::de::from_str(body)?)
+}
+
+fn is_token_error(errors: &TradingErrors) -> bool {
+    INVALID_TOKEN_CODES.iter().any(|code| errors.has_code(code))
+}
@@ -0,0 +1,11 @@
+//! Typed Trading API client.
+
+pub mod calls;
+pub mod client;
+pub mod types;
+
+pub use calls::{
+    GetAccount, GetItem, GetMyeBaySelling, GetSellerEvents, GetSellerList, GetUser, TradingCall,
+};
+pub use client::TradingClient;
+pub use types::{ApiError, Item, TradingErrors};
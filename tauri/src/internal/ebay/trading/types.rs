@@ -0,0 +1,381 @@
+//! Types shared across Trading API calls (eBLBaseComponents).
+//!
+//! Field names follow the XML element names so the structs can be read
+//! side by side with eBay's schema. Only the elements the exporter uses are
+//! modelled; anything else in a response is ignored.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const XMLNS: &str = "urn:ebay:apis:eBLBaseComponents";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Ack {
+    Success,
+    Warning,
+    Failure,
+    PartialFailure,
+    CustomCode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeverityCode {
+    Error,
+    Warning,
+    CustomCode,
+}
+
+/// One `<Errors>` block from a Trading API response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiError {
+    pub short_message: String,
+    #[serde(default)]
+    pub long_message: Option<String>,
+    pub error_code: String,
+    pub severity_code: SeverityCode,
+    #[serde(default)]
+    pub error_classification: Option<String>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = self.long_message.as_deref().unwrap_or(&self.short_message);
+        write!(f, "{}: {}", self.error_code, message)
+    }
+}
+
+/// The `<Errors>` a failed call returned, formatted like the Node.js client.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TradingErrors {
+    pub call_name: String,
+    pub ack: Ack,
+    pub errors: Vec<ApiError>,
+}
+
+impl TradingErrors {
+    pub fn has_code(&self, code: &str) -> bool {
+        self.errors.iter().any(|e| e.error_code == code)
+    }
+}
+
+impl fmt::Display for TradingErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "eBay API Error ({}): ", self.call_name)?;
+        let messages: Vec<String> = self.errors.iter().map(ToString::to_string).collect();
+        f.write_str(&messages.join("; "))
+    }
+}
+
+/// Monetary value such as `<CurrentPrice currencyID="USD">9.99</CurrentPrice>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Amount {
+    #[serde(rename = "@currencyID", default)]
+    pub currency_id: Option<String>,
+    #[serde(rename = "$text")]
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Pagination {
+    pub entries_per_page: u32,
+    pub page_number: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PaginationResult {
+    #[serde(default)]
+    pub total_number_of_pages: u32,
+    #[serde(default)]
+    pub total_number_of_entries: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemArray {
+    #[serde(rename = "Item", default)]
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Category {
+    #[serde(rename = "CategoryID", default)]
+    pub category_id: Option<String>,
+    #[serde(default)]
+    pub category_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct User {
+    #[serde(rename = "UserID", default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub feedback_score: Option<i64>,
+    #[serde(default)]
+    pub positive_feedback_percent: Option<f64>,
+    #[serde(default)]
+    pub registration_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListingDetails {
+    #[serde(default)]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end_time: Option<DateTime<Utc>>,
+    #[serde(rename = "ViewItemURL", default)]
+    pub view_item_url: Option<String>,
+    #[serde(rename = "ViewItemURLForNaturalSearch", default)]
+    pub view_item_url_for_natural_search: Option<String>,
+    #[serde(default)]
+    pub listing_type: Option<String>,
+    #[serde(default)]
+    pub listing_fee: Option<Amount>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SellingStatus {
+    #[serde(default)]
+    pub current_price: Option<Amount>,
+    #[serde(default)]
+    pub quantity_sold: Option<i64>,
+    #[serde(default)]
+    pub bid_count: Option<i64>,
+    #[serde(default)]
+    pub high_bidder: Option<User>,
+    #[serde(default)]
+    pub listing_status: Option<String>,
+    #[serde(default)]
+    pub time_left: Option<String>,
+    #[serde(default)]
+    pub final_value_fee: Option<Amount>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct QuantityInfo {
+    #[serde(default)]
+    pub minimum_remnant_set: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BestOfferDetails {
+    #[serde(default)]
+    pub best_offer_enabled: Option<bool>,
+    #[serde(default)]
+    pub best_offer_auto_accept_price: Option<Amount>,
+    #[serde(default)]
+    pub best_offer_auto_decline_price: Option<Amount>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ShippingServiceOptions {
+    #[serde(default)]
+    pub shipping_service: Option<String>,
+    #[serde(default)]
+    pub shipping_service_cost: Option<Amount>,
+    #[serde(default)]
+    pub free_shipping: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ShippingDetails {
+    #[serde(default)]
+    pub shipping_type: Option<String>,
+    #[serde(rename = "ShippingServiceOptions", default)]
+    pub shipping_service_options: Vec<ShippingServiceOptions>,
+    #[serde(default)]
+    pub fast_and_free: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ReturnPolicy {
+    #[serde(default)]
+    pub returns_accepted_option: Option<String>,
+    #[serde(default)]
+    pub returns_within_option: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PictureDetails {
+    #[serde(rename = "GalleryURL", default)]
+    pub gallery_url: Option<String>,
+    #[serde(default)]
+    pub gallery_type: Option<String>,
+    #[serde(default)]
+    pub photo_display: Option<String>,
+    #[serde(rename = "PictureURL", default)]
+    pub picture_urls: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SellerProfiles {
+    #[serde(default)]
+    pub seller_payment_profile: Option<SellerPaymentProfile>,
+    #[serde(default)]
+    pub seller_shipping_profile: Option<SellerShippingProfile>,
+    #[serde(default)]
+    pub seller_return_profile: Option<SellerReturnProfile>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SellerPaymentProfile {
+    #[serde(rename = "PaymentProfileID", default)]
+    pub payment_profile_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SellerShippingProfile {
+    #[serde(rename = "ShippingProfileID", default)]
+    pub shipping_profile_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SellerReturnProfile {
+    #[serde(rename = "ReturnProfileID", default)]
+    pub return_profile_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemSpecifics {
+    #[serde(rename = "NameValueList", default)]
+    pub name_value_list: Vec<NameValueList>,
+}
+
+/// One item specific (aspect). `Value` repeats for multi-valued aspects.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NameValueList {
+    #[serde(rename = "Name", default)]
+    pub name: String,
+    #[serde(rename = "Value", default)]
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ReviseStatus {
+    #[serde(default)]
+    pub item_revised: Option<bool>,
+}
+
+/// `ItemType`, trimmed to the elements `processSellerListings` reads.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Item {
+    #[serde(rename = "ItemID", default)]
+    pub item_id: String,
+    #[serde(rename = "SKU", default)]
+    pub sku: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub sub_title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+
+    #[serde(default)]
+    pub primary_category: Option<Category>,
+    #[serde(default)]
+    pub secondary_category: Option<Category>,
+
+    #[serde(rename = "ConditionID", default)]
+    pub condition_id: Option<String>,
+    #[serde(default)]
+    pub condition_display_name: Option<String>,
+    #[serde(default)]
+    pub condition_description: Option<String>,
+
+    #[serde(default)]
+    pub listing_type: Option<String>,
+    #[serde(default)]
+    pub listing_duration: Option<String>,
+    #[serde(default)]
+    pub listing_details: Option<ListingDetails>,
+
+    #[serde(default)]
+    pub start_price: Option<Amount>,
+    #[serde(default)]
+    pub buy_it_now_price: Option<Amount>,
+    #[serde(default)]
+    pub reserve_price: Option<Amount>,
+    #[serde(default)]
+    pub selling_status: Option<SellingStatus>,
+
+    #[serde(default)]
+    pub quantity: Option<i64>,
+    #[serde(default)]
+    pub quantity_available: Option<i64>,
+    #[serde(default)]
+    pub quantity_info: Option<QuantityInfo>,
+    #[serde(default)]
+    pub best_offer_details: Option<BestOfferDetails>,
+
+    /// Item-level time left, returned by GetMyeBaySelling.
+    #[serde(default)]
+    pub time_left: Option<String>,
+
+    #[serde(default)]
+    pub site: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub postal_code: Option<String>,
+    #[serde(default)]
+    pub shipping_details: Option<ShippingDetails>,
+
+    #[serde(rename = "PaymentMethods", default)]
+    pub payment_methods: Vec<String>,
+    #[serde(default)]
+    pub pay_pal_email_address: Option<String>,
+    #[serde(default)]
+    pub return_policy: Option<ReturnPolicy>,
+
+    #[serde(rename = "GalleryURL", default)]
+    pub gallery_url: Option<String>,
+    #[serde(default)]
+    pub gallery_type: Option<String>,
+    #[serde(default)]
+    pub picture_details: Option<PictureDetails>,
+
+    #[serde(default)]
+    pub watch_count: Option<i64>,
+    #[serde(default)]
+    pub hit_count: Option<i64>,
+    #[serde(default)]
+    pub question_count: Option<i64>,
+
+    #[serde(default)]
+    pub private_listing: Option<bool>,
+    #[serde(rename = "ListingEnhancement", default)]
+    pub listing_enhancements: Vec<String>,
+    #[serde(default)]
+    pub seller_profiles: Option<SellerProfiles>,
+    #[serde(default)]
+    pub item_specifics: Option<ItemSpecifics>,
+    #[serde(default)]
+    pub seller: Option<User>,
+
+    #[serde(default)]
+    pub revise_status: Option<ReviseStatus>,
+    #[serde(rename = "UUID", default)]
+    pub uuid: Option<String>,
+    #[serde(default)]
+    pub application_data: Option<String>,
+}
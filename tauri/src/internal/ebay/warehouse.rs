@@ -0,0 +1,765 @@
+//! Local SQLite store of processed listings.
+//!
+//! The exporter fetched the whole catalog on every run and kept nothing but
+//! the workbook. Here every sync is recorded: `listings` holds the latest row
+//! per account and Item ID, and `listing_snapshots` holds the row each sync
+//! saw, so the catalog as of any earlier sync can be rebuilt. An export is a
+//! query over `listings`. `listing_metrics` pulls price, quantity, watch and
+//! hit counts out of each snapshot, dated when its sync started, for the
+//! [`super::timeline`] queries.
+//! `listing_specifics` holds each listing's latest item specifics as one row
+//! per value, for queries across categories with different aspect sets.
+//! `promotion_audit` records every change the discount tools made to a
+//! promotion. `promotions` holds the latest copy of each promotion, and
+//! `promotion_reports` / `promotion_summary_reports` every sales report read
+//! for them.
+
+use std::path::Path;
+use std::sync::Mutex as StdMutex;
+
+use chrono::{DateTime, Utc};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::listing_rows::{self, Cell, ItemSpecific, ListingRow, ListingSpecifics};
+use crate::error::{Error, Result};
+
+const SCHEMA_VERSION: i64 = 5;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS syncs (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    account       TEXT NOT NULL,
+    kind          TEXT NOT NULL,
+    status        TEXT NOT NULL,
+    started_at    INTEGER NOT NULL,
+    finished_at   INTEGER,
+    mod_time_from INTEGER,
+    mod_time_to   INTEGER,
+    item_count    INTEGER NOT NULL DEFAULT 0,
+    error         TEXT
+);
+CREATE INDEX IF NOT EXISTS syncs_account ON syncs (account, id);
+
+CREATE TABLE IF NOT EXISTS listings (
+    account         TEXT NOT NULL,
+    item_id         TEXT NOT NULL,
+    row_json        TEXT NOT NULL,
+    first_seen_sync INTEGER NOT NULL REFERENCES syncs (id),
+    last_sync       INTEGER NOT NULL REFERENCES syncs (id),
+    updated_at      INTEGER NOT NULL,
+    PRIMARY KEY (account, item_id)
+);
+
+CREATE TABLE IF NOT EXISTS listing_snapshots (
+    sync_id  INTEGER NOT NULL REFERENCES syncs (id),
+    account  TEXT NOT NULL,
+    item_id  TEXT NOT NULL,
+    row_json TEXT NOT NULL,
+    PRIMARY KEY (sync_id, item_id)
+);
+CREATE INDEX IF NOT EXISTS listing_snapshots_item
+    ON listing_snapshots (account, item_id, sync_id);
+
+CREATE TABLE IF NOT EXISTS listing_metrics (
+    sync_id            INTEGER NOT NULL REFERENCES syncs (id),
+    account            TEXT NOT NULL,
+    item_id            TEXT NOT NULL,
+    recorded_at        INTEGER NOT NULL,
+    price              REAL,
+    currency           TEXT,
+    quantity_available INTEGER,
+    watch_count        INTEGER,
+    hit_count          INTEGER,
+    PRIMARY KEY (sync_id, item_id)
+);
+CREATE INDEX IF NOT EXISTS listing_metrics_item
+    ON listing_metrics (account, item_id, recorded_at);
+
+CREATE TABLE IF NOT EXISTS listing_specifics (
+    account  TEXT NOT NULL,
+    item_id  TEXT NOT NULL,
+    name     TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    value    TEXT NOT NULL,
+    PRIMARY KEY (account, item_id, name, position)
+);
+CREATE INDEX IF NOT EXISTS listing_specifics_name
+    ON listing_specifics (account, name, value);
+
+CREATE TABLE IF NOT EXISTS promotion_audit (
+    id             INTEGER PRIMARY KEY AUTOINCREMENT,
+    account        TEXT NOT NULL,
+    promotion_id   TEXT NOT NULL,
+    promotion_name TEXT NOT NULL,
+    promotion_type TEXT NOT NULL,
+    rule           TEXT,
+    action         TEXT NOT NULL,
+    old_end_date   INTEGER,
+    new_end_date   INTEGER,
+    recorded_at    INTEGER NOT NULL,
+    error          TEXT
+);
+CREATE INDEX IF NOT EXISTS promotion_audit_promotion
+    ON promotion_audit (account, promotion_id, id);
+
+CREATE TABLE IF NOT EXISTS promotions (
+    account        TEXT NOT NULL,
+    promotion_id   TEXT NOT NULL,
+    name           TEXT NOT NULL,
+    promotion_type TEXT NOT NULL,
+    status         TEXT NOT NULL,
+    start_date     INTEGER,
+    end_date       INTEGER,
+    updated_at     INTEGER NOT NULL,
+    PRIMARY KEY (account, promotion_id)
+);
+
+CREATE TABLE IF NOT EXISTS promotion_reports (
+    account        TEXT NOT NULL,
+    promotion_id   TEXT NOT NULL,
+    recorded_at    INTEGER NOT NULL,
+    currency       TEXT,
+    base_sale      REAL,
+    promotion_sale REAL,
+    total_sale     REAL,
+    total_discount REAL,
+    items_sold     INTEGER,
+    orders_sold    INTEGER,
+    sales_lift     REAL,
+    PRIMARY KEY (account, promotion_id, recorded_at)
+);
+
+CREATE TABLE IF NOT EXISTS promotion_summary_reports (
+    account        TEXT NOT NULL,
+    recorded_at    INTEGER NOT NULL,
+    currency       TEXT,
+    base_sale      REAL,
+    promotion_sale REAL,
+    total_sale     REAL,
+    sales_lift     REAL,
+    PRIMARY KEY (account, recorded_at)
+);
+";
+
+/// Fills `listing_metrics` from snapshots stored before the table existed.
+const BACKFILL_METRICS: &str = r#"
+INSERT OR IGNORE INTO listing_metrics
+    (sync_id, account, item_id, recorded_at, price, currency,
+     quantity_available, watch_count, hit_count)
+SELECT s.sync_id, s.account, s.item_id, syncs.started_at,
+       json_extract(s.row_json, '$."Current Price"'),
+       json_extract(s.row_json, '$.Currency'),
+       json_extract(s.row_json, '$."Quantity Available"'),
+       json_extract(s.row_json, '$."Watch Count"'),
+       json_extract(s.row_json, '$."Hit Count"')
+FROM listing_snapshots s JOIN syncs ON syncs.id = s.sync_id;
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncKind {
+    /// Every selling list (and optionally the listing history).
+    Full,
+    /// Only listings modified since the last successful sync.
+    Incremental,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncStatus {
+    Running,
+    Complete,
+    Failed,
+}
+
+/// The Trading call a batch of rows came from. Each leaves some columns
+/// out; only those keep their stored value when a row is recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowSource {
+    /// `GetMyeBaySelling`: every column, tagged with its selling list.
+    SellingLists,
+    /// `GetSellerList`: no selling list.
+    SellerList,
+    /// `GetSellerEvents`: no selling list, description or item specifics.
+    SellerEvents,
+}
+
+impl RowSource {
+    /// Whether rows from this source never carry `column`.
+    fn lacks(self, column: &str) -> bool {
+        const SPECIFIC_COLUMNS: [&str; 5] = ["Brand", "Model", "Size", "Color", "Material"];
+        match self {
+            RowSource::SellingLists => false,
+            RowSource::SellerList => column == listing_rows::LISTING_STATUS_CATEGORY,
+            RowSource::SellerEvents => {
+                column == listing_rows::LISTING_STATUS_CATEGORY
+                    || column == "Description"
+                    || SPECIFIC_COLUMNS.contains(&column)
+                    || listing_rows::specific_name(column).is_some()
+            }
+        }
+    }
+}
+
+impl SyncKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            SyncKind::Full => "full",
+            SyncKind::Incremental => "incremental",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "full" => Some(SyncKind::Full),
+            "incremental" => Some(SyncKind::Incremental),
+            _ => None,
+        }
+    }
+}
+
+impl SyncStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            SyncStatus::Running => "running",
+            SyncStatus::Complete => "complete",
+            SyncStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "running" => Some(SyncStatus::Running),
+            "complete" => Some(SyncStatus::Complete),
+            "failed" => Some(SyncStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncRecord {
+    pub id: i64,
+    pub account: String,
+    pub kind: SyncKind,
+    pub status: SyncStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Modification-time range the sync covered. A full sync has no start.
+    pub mod_time_from: Option<DateTime<Utc>>,
+    pub mod_time_to: Option<DateTime<Utc>>,
+    pub item_count: u64,
+    pub error: Option<String>,
+}
+
+/// How many latest listing rows of an account carry a column.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnUsage {
+    pub name: String,
+    /// Rows that have the column, empty or not.
+    pub listings: u64,
+    /// Rows with a value in it.
+    pub filled: u64,
+}
+
+/// How many listings of an account use an item specific.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecificUsage {
+    pub name: String,
+    /// Listings that have the specific.
+    pub listings: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredListing {
+    pub row: ListingRow,
+    pub first_seen_sync: i64,
+    pub last_sync: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct Warehouse {
+    conn: StdMutex<Connection>,
+}
+
+impl Warehouse {
+    /// Open (or create) the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::init(conn)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(Error::InvalidResponse(format!(
+                "listing database is version {version}, newer than this app ({SCHEMA_VERSION})"
+            )));
+        }
+        conn.execute_batch(SCHEMA)?;
+        if version < 2 {
+            conn.execute_batch(BACKFILL_METRICS)?;
+        }
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(Self {
+            conn: StdMutex::new(conn),
+        })
+    }
+
+    pub(crate) fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().expect("warehouse lock poisoned")
+    }
+
+    pub fn begin_sync(
+        &self,
+        account: &str,
+        kind: SyncKind,
+        mod_time_from: Option<DateTime<Utc>>,
+        mod_time_to: Option<DateTime<Utc>>,
+    ) -> Result<SyncRecord> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO syncs (account, kind, status, started_at, mod_time_from, mod_time_to)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                account,
+                kind.as_str(),
+                SyncStatus::Running.as_str(),
+                Utc::now().timestamp(),
+                mod_time_from.map(|t| t.timestamp()),
+                mod_time_to.map(|t| t.timestamp()),
+            ],
+        )?;
+        sync_by_id(&conn, conn.last_insert_rowid())
+    }
+
+    /// Store `rows` under `sync_id`. Columns `source` never returns keep
+    /// their stored value, so a row from `GetSellerEvents` keeps the
+    /// description and item specifics an earlier full sync stored; any other
+    /// column the row leaves empty is cleared. Returns the number of rows
+    /// stored.
+    pub fn record(&self, sync_id: i64, source: RowSource, rows: &[ListingRow]) -> Result<usize> {
+        let mut conn = self.conn();
+        let (account, started_at): (String, i64) = conn.query_row(
+            "SELECT account, started_at FROM syncs WHERE id = ?1",
+            [sync_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )?;
+        let now = Utc::now().timestamp();
+
+        let tx = conn.transaction()?;
+        for row in rows {
+            let item_id = row.item_id();
+            if item_id.is_empty() {
+                continue;
+            }
+
+            let stored: Option<String> = tx
+                .query_row(
+                    "SELECT row_json FROM listings WHERE account = ?1 AND item_id = ?2",
+                    params![account, item_id],
+                    |r| r.get(0),
+                )
+                .optional()?;
+            let mut merged = row.clone();
+            if let Some(json) = stored {
+                let stored = serde_json::from_str::<ListingRow>(&json)?;
+                for (column, cell) in stored.columns().filter(|(column, _)| source.lacks(column)) {
+                    merged.set(column, cell.clone());
+                }
+            }
+            let json = serde_json::to_string(&merged)?;
+
+            tx.execute(
+                "INSERT OR REPLACE INTO listing_snapshots (sync_id, account, item_id, row_json)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![sync_id, account, item_id, json],
+            )?;
+            tx.execute(
+                "INSERT INTO listings (account, item_id, row_json, first_seen_sync, last_sync, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?4, ?5)
+                 ON CONFLICT (account, item_id) DO UPDATE SET
+                     row_json = excluded.row_json,
+                     last_sync = excluded.last_sync,
+                     updated_at = CASE WHEN listings.row_json = excluded.row_json
+                                       THEN listings.updated_at
+                                       ELSE excluded.updated_at END",
+                params![account, item_id, json, sync_id, now],
+            )?;
+            tx.execute(
+                "INSERT OR REPLACE INTO listing_metrics
+                     (sync_id, account, item_id, recorded_at, price, currency,
+                      quantity_available, watch_count, hit_count)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    sync_id,
+                    account,
+                    item_id,
+                    started_at,
+                    merged.get("Current Price").as_f64(),
+                    merged.get("Currency").as_str(),
+                    integer(merged.get("Quantity Available")),
+                    integer(merged.get("Watch Count")),
+                    integer(merged.get("Hit Count")),
+                ],
+            )?;
+        }
+        tx.execute(
+            "UPDATE syncs SET item_count =
+                 (SELECT COUNT(*) FROM listing_snapshots WHERE sync_id = ?1)
+             WHERE id = ?1",
+            [sync_id],
+        )?;
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    pub fn finish_sync(&self, sync_id: i64) -> Result<SyncRecord> {
+        self.close_sync(sync_id, SyncStatus::Complete, None)
+    }
+
+    /// Mark a sync as failed. Rows it already stored are kept; the next
+    /// incremental sync starts from the last complete one.
+    pub fn fail_sync(&self, sync_id: i64, error: &str) -> Result<SyncRecord> {
+        self.close_sync(sync_id, SyncStatus::Failed, Some(error))
+    }
+
+    fn close_sync(
+        &self,
+        sync_id: i64,
+        status: SyncStatus,
+        error: Option<&str>,
+    ) -> Result<SyncRecord> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE syncs SET status = ?2, finished_at = ?3, error = ?4 WHERE id = ?1",
+            params![sync_id, status.as_str(), Utc::now().timestamp(), error],
+        )?;
+        sync_by_id(&conn, sync_id)
+    }
+
+    /// Newest sync for `account` that finished successfully.
+    pub fn last_complete_sync(&self, account: &str) -> Result<Option<SyncRecord>> {
+        let conn = self.conn();
+        Ok(conn
+            .query_row(
+                &format!("{SYNC_SELECT} WHERE account = ?1 AND status = 'complete' ORDER BY id DESC LIMIT 1"),
+                [account],
+                sync_from_row,
+            )
+            .optional()?)
+    }
+
+    /// Every sync for `account`, newest first.
+    pub fn syncs(&self, account: &str) -> Result<Vec<SyncRecord>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "{SYNC_SELECT} WHERE account = ?1 ORDER BY id DESC"
+        ))?;
+        let syncs = stmt
+            .query_map([account], sync_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(syncs)
+    }
+
+    /// Latest row for every listing of `account`, ordered by Item ID.
+    pub fn listings(&self, account: &str) -> Result<Vec<ListingRow>> {
+        self.rows(
+            "SELECT row_json FROM listings WHERE account = ?1 ORDER BY item_id",
+            params![account],
+        )
+    }
+
+    /// Hand each latest row of `account` to `f` in Item ID order, one at a
+    /// time, without loading them all. The warehouse is locked until `f` has
+    /// seen the last row, so `f` must not call back into it. Returns the
+    /// number of rows read.
+    pub fn for_each_listing(
+        &self,
+        account: &str,
+        mut f: impl FnMut(ListingRow) -> Result<()>,
+    ) -> Result<usize> {
+        let conn = self.conn();
+        let mut stmt =
+            conn.prepare("SELECT row_json FROM listings WHERE account = ?1 ORDER BY item_id")?;
+        let mut rows = stmt.query([account])?;
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            let json: String = row.get(0)?;
+            f(serde_json::from_str(&json)?)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Every column the latest rows of `account` carry, counted in SQLite so
+    /// a writer can pick its columns before reading the rows. Ordered by the
+    /// first Item ID that has each, as [`Self::for_each_listing`] meets them.
+    pub fn listing_column_usage(&self, account: &str) -> Result<Vec<ColumnUsage>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT cell.key, COUNT(*),
+                    SUM(cell.value IS NOT NULL AND cell.value != ''),
+                    MIN(listings.item_id) AS first
+             FROM listings, json_each(listings.row_json) AS cell
+             WHERE listings.account = ?1
+             GROUP BY cell.key
+             ORDER BY first, cell.key",
+        )?;
+        let usage = stmt
+            .query_map([account], |r| {
+                Ok(ColumnUsage {
+                    name: r.get(0)?,
+                    listings: r.get::<_, i64>(1)?.max(0) as u64,
+                    filled: r.get::<_, i64>(2)?.max(0) as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(usage)
+    }
+
+    pub fn listing(&self, account: &str, item_id: &str) -> Result<Option<StoredListing>> {
+        let conn = self.conn();
+        let stored = conn
+            .query_row(
+                "SELECT row_json, first_seen_sync, last_sync, updated_at
+                 FROM listings WHERE account = ?1 AND item_id = ?2",
+                params![account, item_id],
+                |r| {
+                    Ok((
+                        r.get::<_, String>(0)?,
+                        r.get(1)?,
+                        r.get(2)?,
+                        r.get::<_, i64>(3)?,
+                    ))
+                },
+            )
+            .optional()?;
+        stored
+            .map(|(json, first_seen_sync, last_sync, updated_at)| {
+                Ok(StoredListing {
+                    row: serde_json::from_str(&json)?,
+                    first_seen_sync,
+                    last_sync,
+                    updated_at: from_timestamp(updated_at),
+                })
+            })
+            .transpose()
+    }
+
+    /// The catalog of `account` as it stood after `sync_id`: the items the
+    /// latest full sync up to it saw (its own snapshots are the membership
+    /// list), plus those incremental syncs since then touched, each as its
+    /// newest snapshot taken at or before `sync_id`. An item the full sync
+    /// no longer saw has ended or vanished and is left out. The full sync
+    /// must have completed, unless it is `sync_id` itself; with none, every
+    /// item seen so far counts.
+    pub fn snapshot_at(&self, account: &str, sync_id: i64) -> Result<Vec<ListingRow>> {
+        self.rows(
+            "SELECT s.row_json FROM listing_snapshots s
+             JOIN (SELECT item_id, MAX(sync_id) AS sync_id FROM listing_snapshots
+                   WHERE account = ?1 AND sync_id <= ?2 GROUP BY item_id) latest
+               ON latest.item_id = s.item_id AND latest.sync_id = s.sync_id
+             WHERE s.account = ?1
+               AND latest.sync_id >= (
+                   SELECT COALESCE(MAX(id), 0) FROM syncs
+                   WHERE account = ?1 AND kind = ?3 AND id <= ?2
+                     AND (status = ?4 OR id = ?2))
+             ORDER BY s.item_id",
+            params![
+                account,
+                sync_id,
+                SyncKind::Full.as_str(),
+                SyncStatus::Complete.as_str()
+            ],
+        )
+    }
+
+    /// Replace the stored item specifics of each listing in `listings`.
+    pub fn record_specifics(&self, account: &str, listings: &[ListingSpecifics]) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        for listing in listings {
+            tx.execute(
+                "DELETE FROM listing_specifics WHERE account = ?1 AND item_id = ?2",
+                params![account, listing.item_id],
+            )?;
+            for specific in &listing.specifics {
+                for (position, value) in specific.values.iter().enumerate() {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO listing_specifics
+                             (account, item_id, name, position, value)
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![
+                            account,
+                            listing.item_id,
+                            specific.name,
+                            position as i64,
+                            value
+                        ],
+                    )?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// The stored item specifics of one listing, by name.
+    pub fn specifics(&self, account: &str, item_id: &str) -> Result<Vec<ItemSpecific>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT name, value FROM listing_specifics
+             WHERE account = ?1 AND item_id = ?2
+             ORDER BY name, position",
+        )?;
+        let mut specifics: Vec<ItemSpecific> = Vec::new();
+        for row in stmt.query_map(params![account, item_id], |r| {
+            Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))
+        })? {
+            let (name, value) = row?;
+            match specifics.last_mut() {
+                Some(last) if last.name == name => last.values.push(value),
+                _ => specifics.push(ItemSpecific {
+                    name,
+                    values: vec![value],
+                }),
+            }
+        }
+        Ok(specifics)
+    }
+
+    /// Every item specific the account's listings use, most common first.
+    /// Names that differ only in case count as one.
+    pub fn specific_usage(&self, account: &str) -> Result<Vec<SpecificUsage>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT MIN(name) AS name, COUNT(DISTINCT item_id) AS listings FROM listing_specifics
+             WHERE account = ?1
+             GROUP BY name COLLATE NOCASE
+             ORDER BY listings DESC, name",
+        )?;
+        let usage = stmt
+            .query_map([account], |r| {
+                Ok(SpecificUsage {
+                    name: r.get(0)?,
+                    listings: r.get::<_, i64>(1)?.max(0) as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(usage)
+    }
+
+    /// Latest rows of the listings whose item specific `name` has `value`
+    /// (among its values), both ignoring case.
+    pub fn listings_with_specific(
+        &self,
+        account: &str,
+        name: &str,
+        value: &str,
+    ) -> Result<Vec<ListingRow>> {
+        self.rows(
+            "SELECT l.row_json FROM listings l
+             WHERE l.account = ?1 AND EXISTS (
+                 SELECT 1 FROM listing_specifics s
+                 WHERE s.account = l.account AND s.item_id = l.item_id
+                   AND s.name = ?2 COLLATE NOCASE AND s.value = ?3 COLLATE NOCASE)
+             ORDER BY l.item_id",
+            params![account, name, value],
+        )
+    }
+
+    fn rows(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<ListingRow>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(sql)?;
+        let json = stmt
+            .query_map(params, |r| r.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        json.iter()
+            .map(|json| Ok(serde_json::from_str(json)?))
+            .collect()
+    }
+}
+
+const SYNC_SELECT: &str = "SELECT id, account, kind, status, started_at, finished_at,
+    mod_time_from, mod_time_to, item_count, error FROM syncs";
+
+/// Text column `index` read through `parse`. A value `parse` does not know
+/// fails the row rather than being read as something it is not.
+pub(crate) fn parsed<T>(
+    row: &Row<'_>,
+    index: usize,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> rusqlite::Result<T> {
+    let text: String = row.get(index)?;
+    parse(&text).ok_or_else(|| unknown_value(index, &text))
+}
+
+/// [`parsed`] for a nullable column: NULL is `None`.
+pub(crate) fn parsed_optional<T>(
+    row: &Row<'_>,
+    index: usize,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> rusqlite::Result<Option<T>> {
+    match row.get::<_, Option<String>>(index)? {
+        Some(text) => parse(&text)
+            .map(Some)
+            .ok_or_else(|| unknown_value(index, &text)),
+        None => Ok(None),
+    }
+}
+
+fn unknown_value(index: usize, text: &str) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(
+        index,
+        Type::Text,
+        format!("unknown value {text:?}").into(),
+    )
+}
+
+fn sync_by_id(conn: &Connection, id: i64) -> Result<SyncRecord> {
+    Ok(conn.query_row(&format!("{SYNC_SELECT} WHERE id = ?1"), [id], sync_from_row)?)
+}
+
+fn sync_from_row(row: &Row<'_>) -> rusqlite::Result<SyncRecord> {
+    Ok(SyncRecord {
+        id: row.get(0)?,
+        account: row.get(1)?,
+        kind: parsed(row, 2, SyncKind::parse)?,
+        status: parsed(row, 3, SyncStatus::parse)?,
+        started_at: from_timestamp(row.get(4)?),
+        finished_at: row.get::<_, Option<i64>>(5)?.map(from_timestamp),
+        mod_time_from: row.get::<_, Option<i64>>(6)?.map(from_timestamp),
+        mod_time_to: row.get::<_, Option<i64>>(7)?.map(from_timestamp),
+        item_count: row.get::<_, i64>(8)?.max(0) as u64,
+        error: row.get(9)?,
+    })
+}
+
+fn integer(cell: &Cell) -> Option<i64> {
+    match cell {
+        Cell::Integer(value) => Some(*value),
+        _ => None,
+    }
+}
+
+pub(crate) fn from_timestamp(secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs, 0).unwrap_or_default()
+}
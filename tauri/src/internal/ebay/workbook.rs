@@ -0,0 +1,672 @@
+//! The exporter's XLSX workbook.
+//!
+//! `ExcelExporter.exportToExcel` in `ebay-listings-exporter.js` writes one
+//! row per listing to an "All Listings" sheet (header row from
+//! `processSellerListings`) and totals to a "Summary" sheet. This writes the
+//! same workbook natively, so no Node.js is needed, and reads one back into
+//! [`ListingRow`]s whether it came from the JS exporter or from here.
+//!
+//! On top of the JS layout the listings sheet gets typed cells: a frozen
+//! header row and Item ID column, currency formats on prices, real dates on
+//! Start Time and End Time, and Time Left (Days) highlighted when a listing
+//! is about to end. Item-specific columns follow the fixed ones, the aspects
+//! most listings share first.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use calamine::{open_workbook, Data, Reader, Xlsx};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use rust_xlsxwriter::{
+    column_number_to_name, ConditionalFormatFormula, Format, Workbook, Worksheet,
+};
+use serde::Serialize;
+
+use super::listing_rows::{
+    self, Cell, ColumnKind, ColumnSpec, ListingRow, ITEM_ID, LISTING_COLUMNS,
+    LISTING_STATUS_CATEGORY,
+};
+use super::warehouse::ColumnUsage;
+use crate::error::{Error, Result};
+
+pub const LISTINGS_SHEET: &str = "All Listings";
+pub const SUMMARY_SHEET: &str = "Summary";
+
+/// Rows `calculateColumnWidths` looks at.
+const WIDTH_SAMPLE_ROWS: usize = 100;
+const MIN_COLUMN_WIDTH: usize = 8;
+const MAX_COLUMN_WIDTH: usize = 50;
+
+/// Time Left (Days) below this is highlighted as ending.
+const ENDING_DAYS: f64 = 1.0;
+/// Time Left (Days) below this (and not ending) is highlighted as ending soon.
+const ENDING_SOON_DAYS: f64 = 3.0;
+
+/// A sheet column: its header and how its values are typed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub kind: ColumnKind,
+}
+
+impl From<&ColumnSpec> for Column {
+    fn from(spec: &ColumnSpec) -> Self {
+        Column::new(spec.name, spec.kind)
+    }
+}
+
+impl Column {
+    pub fn new(name: impl Into<String>, kind: ColumnKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+        }
+    }
+}
+
+/// What `exportToExcel` returned.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportResult {
+    pub filename: PathBuf,
+    pub record_count: usize,
+    pub file_size: u64,
+}
+
+/// `exportToExcel`: write the "All Listings" and "Summary" sheets to `path`.
+pub fn export_to_excel(rows: &[ListingRow], path: impl AsRef<Path>) -> Result<ExportResult> {
+    let path = path.as_ref();
+    log::info!("Creating Excel file: {}", path.display());
+    save_export(listings_workbook(rows)?, path, rows.len())
+}
+
+/// Save `workbook` to `path` and report what was written.
+pub(crate) fn save_export(
+    mut workbook: Workbook,
+    path: &Path,
+    record_count: usize,
+) -> Result<ExportResult> {
+    workbook.save(path)?;
+
+    let file_size = fs::metadata(path)?.len();
+    log::info!(
+        "Excel file created: {} ({})",
+        path.display(),
+        format_file_size(file_size)
+    );
+    Ok(ExportResult {
+        filename: path.to_path_buf(),
+        record_count,
+        file_size,
+    })
+}
+
+/// The exporter workbook, not yet saved, so callers can add sheets of their
+/// own (a diff report, say) before writing it out.
+pub fn listings_workbook(rows: &[ListingRow]) -> Result<Workbook> {
+    let mut seen = SeenColumns::default();
+    rows.iter().for_each(|row| seen.add(row));
+    let sheet_rows: Vec<ListingRow> = rows.iter().map(|row| seen.normalize(row.clone())).collect();
+    shaped_workbook(rows, &sheet_rows, &seen.columns(), 1)
+}
+
+/// The exporter workbook with `sheet_rows` written under `columns` on the
+/// "All Listings" sheet and the "Summary" computed from `rows`, for exports
+/// that reshape the rows first.
+pub(crate) fn shaped_workbook(
+    rows: &[ListingRow],
+    sheet_rows: &[ListingRow],
+    columns: &[Column],
+    frozen_columns: u16,
+) -> Result<Workbook> {
+    if rows.is_empty() {
+        return Err(Error::Spreadsheet("No data to export".to_string()));
+    }
+
+    let mut formats = CellFormats::new();
+    let mut workbook = Workbook::new();
+
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(LISTINGS_SHEET)?;
+    write_header(sheet, columns, &formats)?;
+    for (width, col) in column_widths(columns, sheet_rows).into_iter().zip(0u16..) {
+        sheet.set_column_width(col, width)?;
+    }
+    for (row, listing) in (1u32..).zip(sheet_rows) {
+        write_listing(sheet, row, columns, listing, &mut formats)?;
+    }
+    finish_data_sheet(sheet, columns, sheet_rows.len() as u32, frozen_columns)?;
+
+    let mut summary = ListingSummary::default();
+    rows.iter().for_each(|row| summary.add(row));
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(SUMMARY_SHEET)?;
+    write_summary(sheet, &summary)?;
+
+    Ok(workbook)
+}
+
+/// Every exporter column, for writers that cannot look at all rows first.
+pub fn listing_columns() -> Vec<Column> {
+    LISTING_COLUMNS.iter().map(Column::from).collect()
+}
+
+/// Columns for `rows`: the exporter columns in order ("Listing Status
+/// Category" only if some row has it), then the item specifics the rows
+/// carry, most common first, then any other columns, by name. Item specifics
+/// whose names differ only in case share a column under the first spelling;
+/// [`listings_workbook`] writes the rows that way.
+pub fn columns_for(rows: &[ListingRow]) -> Vec<Column> {
+    let mut seen = SeenColumns::default();
+    rows.iter().for_each(|row| seen.add(row));
+    seen.columns()
+}
+
+/// The columns rows carry, gathered one row at a time, for
+/// [`columns_for`] over rows that are not all in memory.
+#[derive(Debug, Default)]
+pub(crate) struct SeenColumns {
+    has_category: bool,
+    /// First spelling and use count, by lowercased column name.
+    specifics: HashMap<String, (String, usize)>,
+    extra: BTreeSet<String>,
+}
+
+impl SeenColumns {
+    /// Every exporter column is kept, whether or not a row has it, for
+    /// formats with a fixed schema.
+    pub(crate) fn with_every_listing_column() -> Self {
+        Self {
+            has_category: true,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn add(&mut self, row: &ListingRow) {
+        self.has_category |= !row.get(LISTING_STATUS_CATEGORY).is_empty();
+        for (name, _) in row.columns() {
+            if listing_rows::specific_name(name).is_some() {
+                self.specifics
+                    .entry(name.to_lowercase())
+                    .or_insert_with(|| (name.to_string(), 0))
+                    .1 += 1;
+            } else if listing_rows::column(name).is_none() && !self.extra.contains(name) {
+                self.extra.insert(name.to_string());
+            }
+        }
+    }
+
+    /// Count a column the way [`Self::add`] would for each of `usage.listings`
+    /// rows.
+    pub(crate) fn add_usage(&mut self, usage: &ColumnUsage) {
+        let name = usage.name.as_str();
+        if name == LISTING_STATUS_CATEGORY {
+            self.has_category |= usage.filled > 0;
+        } else if listing_rows::specific_name(name).is_some() {
+            self.specifics
+                .entry(name.to_lowercase())
+                .or_insert_with(|| (name.to_string(), 0))
+                .1 += usage.listings as usize;
+        } else if listing_rows::column(name).is_none() {
+            self.extra.insert(name.to_string());
+        }
+    }
+
+    pub(crate) fn columns(&self) -> Vec<Column> {
+        let mut columns: Vec<Column> = LISTING_COLUMNS
+            .iter()
+            .filter(|spec| spec.name != LISTING_STATUS_CATEGORY || self.has_category)
+            .map(|spec| Column::new(spec.name, spec.kind))
+            .collect();
+
+        let mut specifics: Vec<&(String, usize)> = self.specifics.values().collect();
+        specifics.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        columns.extend(
+            specifics
+                .into_iter()
+                .map(|(name, _)| name)
+                .chain(&self.extra)
+                .map(|name| Column::new(name.as_str(), ColumnKind::Text)),
+        );
+        columns
+    }
+
+    /// `row` with its item-specific columns renamed to the spelling
+    /// [`Self::columns`] uses.
+    pub(crate) fn normalize(&self, mut row: ListingRow) -> ListingRow {
+        let renames: Vec<(String, String)> = row
+            .columns()
+            .filter(|(name, _)| listing_rows::specific_name(name).is_some())
+            .filter_map(|(name, _)| {
+                let (spelling, _) = self.specifics.get(&name.to_lowercase())?;
+                (spelling != name).then(|| (name.to_string(), spelling.clone()))
+            })
+            .collect();
+        for (from, to) in renames {
+            let cell = row.get(&from).clone();
+            row.retain(|name, _| name != from);
+            row.set(to, cell);
+        }
+        row
+    }
+}
+
+/// `calculateColumnWidths`: the longest of the header and the values in the
+/// first 100 rows, plus 2, kept between 8 and 50.
+pub fn column_widths(columns: &[Column], rows: &[ListingRow]) -> Vec<f64> {
+    columns
+        .iter()
+        .map(|column| {
+            let longest = rows
+                .iter()
+                .take(WIDTH_SAMPLE_ROWS)
+                .map(|row| row.get(&column.name).to_string().chars().count())
+                .chain([column.name.chars().count()])
+                .max()
+                .unwrap_or_default();
+            (longest + 2).clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH) as f64
+        })
+        .collect()
+}
+
+/// Number formats by column kind. Price formats are made per currency code
+/// and cached.
+pub(crate) struct CellFormats {
+    header: Format,
+    integer: Format,
+    decimal: Format,
+    days: Format,
+    datetime: Format,
+    date: Format,
+    price: Format,
+    currencies: HashMap<String, Format>,
+}
+
+impl CellFormats {
+    pub(crate) fn new() -> Self {
+        Self {
+            header: Format::new().set_bold(),
+            integer: Format::new().set_num_format("0"),
+            decimal: Format::new().set_num_format("0.0#"),
+            days: Format::new().set_num_format("0.0"),
+            datetime: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+            date: Format::new().set_num_format("yyyy-mm-dd"),
+            price: Format::new().set_num_format("#,##0.00"),
+            currencies: HashMap::new(),
+        }
+    }
+
+    fn price(&mut self, currency: Option<&str>) -> &Format {
+        match currency.filter(|code| !code.is_empty()) {
+            Some(code) => self
+                .currencies
+                .entry(code.to_string())
+                .or_insert_with(|| Format::new().set_num_format(format!("[${code}] #,##0.00"))),
+            None => &self.price,
+        }
+    }
+}
+
+pub(crate) fn write_header(
+    sheet: &mut Worksheet,
+    columns: &[Column],
+    formats: &CellFormats,
+) -> Result<()> {
+    for (col, column) in (0u16..).zip(columns) {
+        sheet.write_string_with_format(0, col, &column.name, &formats.header)?;
+    }
+    Ok(())
+}
+
+/// Write one listing to `row`, typed and formatted by column.
+pub(crate) fn write_listing(
+    sheet: &mut Worksheet,
+    row: u32,
+    columns: &[Column],
+    listing: &ListingRow,
+    formats: &mut CellFormats,
+) -> Result<()> {
+    let currency = listing.get("Currency").as_str();
+    for (col, column) in (0u16..).zip(columns) {
+        let cell = listing.get(&column.name);
+        match (column.kind, cell) {
+            (_, Cell::Empty) => {}
+            (ColumnKind::DateTime | ColumnKind::Date, Cell::Text(text)) => {
+                let format = match column.kind {
+                    ColumnKind::Date => &formats.date,
+                    _ => &formats.datetime,
+                };
+                match parse_datetime(text) {
+                    Some(at) => {
+                        sheet.write_datetime_with_format(row, col, at, format)?;
+                    }
+                    None => {
+                        sheet.write_string(row, col, text)?;
+                    }
+                }
+            }
+            (ColumnKind::Price, Cell::Integer(_) | Cell::Number(_)) => {
+                write_cell(sheet, row, col, cell, Some(formats.price(currency)))?;
+            }
+            (ColumnKind::Integer, Cell::Integer(_) | Cell::Number(_)) => {
+                write_cell(sheet, row, col, cell, Some(&formats.integer))?;
+            }
+            (ColumnKind::Decimal, Cell::Integer(_) | Cell::Number(_)) => {
+                write_cell(sheet, row, col, cell, Some(&formats.decimal))?;
+            }
+            (ColumnKind::Days, Cell::Integer(_) | Cell::Number(_)) => {
+                write_cell(sheet, row, col, cell, Some(&formats.days))?;
+            }
+            _ => write_cell(sheet, row, col, cell, None)?,
+        }
+    }
+    Ok(())
+}
+
+/// Freeze the header row and the first `frozen_columns` columns, add the
+/// autofilter and the Time Left (Days) highlight once `rows` rows have been
+/// written below the header.
+pub(crate) fn finish_data_sheet(
+    sheet: &mut Worksheet,
+    columns: &[Column],
+    rows: u32,
+    frozen_columns: u16,
+) -> Result<()> {
+    let last_col = columns.len().saturating_sub(1) as u16;
+    sheet.set_freeze_panes(1, frozen_columns)?;
+    sheet.autofilter(0, 0, rows, last_col)?;
+
+    let Some(days) = columns.iter().position(|c| c.kind == ColumnKind::Days) else {
+        return Ok(());
+    };
+    if rows == 0 {
+        return Ok(());
+    }
+    let days = days as u16;
+    // Formula rules, since a cell rule treats a blank Time Left as 0 and
+    // would mark listings without one (GTC, ended) as ending. The reference
+    // is to the first data cell and moves down with each row.
+    let cell = format!("{}2", column_number_to_name(days));
+    let ending = ConditionalFormatFormula::new()
+        .set_rule(format!("=AND(ISNUMBER({cell}),{cell}<{ENDING_DAYS})").as_str())
+        .set_format(
+            Format::new()
+                .set_font_color("#9C0006")
+                .set_background_color("#FFC7CE"),
+        );
+    let ending_soon = ConditionalFormatFormula::new()
+        .set_rule(
+            format!("=AND(ISNUMBER({cell}),{cell}>={ENDING_DAYS},{cell}<={ENDING_SOON_DAYS})")
+                .as_str(),
+        )
+        .set_format(
+            Format::new()
+                .set_font_color("#9C5700")
+                .set_background_color("#FFEB9C"),
+        );
+    sheet.add_conditional_format(1, days, rows, days, &ending)?;
+    sheet.add_conditional_format(1, days, rows, days, &ending_soon)?;
+    Ok(())
+}
+
+pub(crate) fn parse_datetime(text: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|at| at.naive_utc())
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })
+}
+
+/// Counts in first-seen order, like the JS object the summary was built on.
+#[derive(Debug, Clone, Default)]
+struct Tally {
+    index: HashMap<String, usize>,
+    counts: Vec<(String, usize)>,
+}
+
+impl Tally {
+    fn add(&mut self, key: String) {
+        match self.index.get(&key) {
+            Some(&i) => self.counts[i].1 += 1,
+            None => {
+                self.index.insert(key.clone(), self.counts.len());
+                self.counts.push((key, 1));
+            }
+        }
+    }
+}
+
+/// Running totals for `createSummary`. Rows can be added one at a time, so
+/// a writer does not have to keep them all.
+#[derive(Debug, Clone, Default)]
+pub struct ListingSummary {
+    count: usize,
+    total_value: f64,
+    total_watchers: i64,
+    total_quantity: i64,
+    statuses: Tally,
+    types: Tally,
+    categories: Tally,
+}
+
+impl ListingSummary {
+    pub fn add(&mut self, row: &ListingRow) {
+        let label = |column: &str| match row.get(column) {
+            cell if cell.is_empty() => "Unknown".to_string(),
+            cell => cell.to_string(),
+        };
+        let whole = |column: &str| row.get(column).as_f64().map_or(0, |v| v.trunc() as i64);
+
+        self.count += 1;
+        self.total_value += row.get("Current Price").as_f64().unwrap_or(0.0);
+        self.total_watchers += whole("Watch Count");
+        self.total_quantity += whole("Quantity Available");
+        self.statuses.add(label("Listing Status"));
+        self.types.add(label("Listing Type"));
+        self.categories.add(label("Category Name"));
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// `createSummary` rows as (Metric, Value).
+    pub fn entries(&self) -> Vec<(String, Cell)> {
+        let blank = || (String::new(), Cell::Empty);
+        let heading = |text: &str| (text.to_string(), Cell::Empty);
+        let count =
+            |(key, count): &(String, usize)| (format!("  {key}"), Cell::Integer(*count as i64));
+        let average = if self.count > 0 {
+            self.total_value / self.count as f64
+        } else {
+            0.0
+        };
+
+        let mut entries = vec![
+            heading("ACTIVE LISTINGS SUMMARY"),
+            blank(),
+            (
+                "Total Active Listings".to_string(),
+                Cell::Integer(self.count as i64),
+            ),
+            (
+                "Total Inventory Value".to_string(),
+                Cell::Number(round2(self.total_value)),
+            ),
+            (
+                "Total Items Available".to_string(),
+                Cell::Integer(self.total_quantity),
+            ),
+            (
+                "Total Watchers".to_string(),
+                Cell::Integer(self.total_watchers),
+            ),
+            (
+                "Average Price per Item".to_string(),
+                Cell::Number(round2(average)),
+            ),
+            blank(),
+            heading("BY LISTING STATUS:"),
+        ];
+        entries.extend(self.statuses.counts.iter().map(count));
+        entries.push(blank());
+        entries.push(heading("BY LISTING TYPE:"));
+        entries.extend(self.types.counts.iter().map(count));
+        entries.push(blank());
+        entries.push(heading("TOP CATEGORIES:"));
+
+        let mut categories = self.categories.counts.clone();
+        categories.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        entries.extend(categories.iter().take(10).map(count));
+        entries
+    }
+}
+
+/// The "Summary" sheet: a Metric / Value table.
+pub(crate) fn write_summary(sheet: &mut Worksheet, summary: &ListingSummary) -> Result<()> {
+    write_metrics(sheet, summary.entries())
+}
+
+/// A Metric / Value table in the "Summary" layout. A metric without a value
+/// is a bold heading.
+pub(crate) fn write_metrics(sheet: &mut Worksheet, entries: Vec<(String, Cell)>) -> Result<()> {
+    let bold = Format::new().set_bold();
+    let money = Format::new().set_num_format("#,##0.00");
+    sheet.write_string_with_format(0, 0, "Metric", &bold)?;
+    sheet.write_string_with_format(0, 1, "Value", &bold)?;
+    sheet.set_column_width(0, 40)?;
+    sheet.set_column_width(1, 16)?;
+
+    for (row, (metric, value)) in (1u32..).zip(entries) {
+        let heading = value.is_empty() && !metric.is_empty();
+        if heading {
+            sheet.write_string_with_format(row, 0, &metric, &bold)?;
+        } else {
+            sheet.write_string(row, 0, &metric)?;
+        }
+        let format = matches!(value, Cell::Number(_)).then_some(&money);
+        write_cell(sheet, row, 1, &value, format)?;
+    }
+    Ok(())
+}
+
+/// `formatFileSize`.
+pub fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+pub(crate) fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Rows of the "All Listings" sheet (or the first sheet if there is none by
+/// that name). Values are coerced to each column's type; rows without an
+/// Item ID are skipped.
+pub fn read_listings(path: impl AsRef<Path>) -> Result<Vec<ListingRow>> {
+    let path = path.as_ref();
+    let mut workbook: Xlsx<_> = open_workbook(path)?;
+    let sheet = workbook
+        .sheet_names()
+        .into_iter()
+        .find(|name| name == LISTINGS_SHEET)
+        .or_else(|| workbook.sheet_names().into_iter().next())
+        .ok_or_else(|| Error::Spreadsheet(format!("{} has no sheets", path.display())))?;
+    let range = workbook.worksheet_range(&sheet)?;
+
+    let mut rows = range.rows();
+    let Some(header) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let columns: Vec<(String, ColumnKind)> = header
+        .iter()
+        .map(|cell| {
+            let name = cell.to_string();
+            let kind = listing_rows::column(&name).map_or(ColumnKind::Text, |spec| spec.kind);
+            (name, kind)
+        })
+        .collect();
+    if !columns.iter().any(|(name, _)| name == ITEM_ID) {
+        return Err(Error::Spreadsheet(format!(
+            "sheet {sheet:?} has no {ITEM_ID:?} column"
+        )));
+    }
+
+    let listings = rows
+        .map(|values| {
+            let mut row = ListingRow::default();
+            for ((name, kind), value) in columns.iter().zip(values) {
+                if !name.is_empty() {
+                    row.set(name.clone(), kind.coerce(cell_from(value, *kind)));
+                }
+            }
+            row
+        })
+        .filter(|row| !row.item_id().is_empty())
+        .collect();
+    Ok(listings)
+}
+
+fn cell_from(value: &Data, kind: ColumnKind) -> Cell {
+    match value {
+        Data::Empty | Data::Error(_) => Cell::Empty,
+        Data::Bool(value) => Cell::Bool(*value),
+        Data::Int(value) => Cell::Integer(*value),
+        Data::Float(value) => Cell::Number(*value),
+        Data::String(value) | Data::DateTimeIso(value) | Data::DurationIso(value) => {
+            Cell::Text(value.clone())
+        }
+        Data::DateTime(value) => match (value.as_datetime(), kind) {
+            (Some(at), ColumnKind::Date) => listing_rows::date(Some(at.and_utc())),
+            (Some(at), ColumnKind::DateTime) => listing_rows::timestamp(Some(at.and_utc())),
+            _ => Cell::Number(value.as_f64()),
+        },
+    }
+}
+
+/// Write `cell` with its natural type (string, number or boolean). Empty
+/// cells are left blank, or written blank when there is a format to apply.
+pub(crate) fn write_cell(
+    sheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    cell: &Cell,
+    format: Option<&Format>,
+) -> Result<()> {
+    match (cell, format) {
+        (Cell::Empty, None) => {}
+        (Cell::Empty, Some(format)) => {
+            sheet.write_blank(row, col, format)?;
+        }
+        (Cell::Text(text), None) => {
+            sheet.write_string(row, col, text)?;
+        }
+        (Cell::Text(text), Some(format)) => {
+            sheet.write_string_with_format(row, col, text, format)?;
+        }
+        (Cell::Bool(value), None) => {
+            sheet.write_boolean(row, col, *value)?;
+        }
+        (Cell::Bool(value), Some(format)) => {
+            sheet.write_boolean_with_format(row, col, *value, format)?;
+        }
+        (Cell::Integer(_) | Cell::Number(_), format) => {
+            let value = cell.as_f64().unwrap_or_default();
+            match format {
+                Some(format) => sheet.write_number_with_format(row, col, value, format)?,
+                None => sheet.write_number(row, col, value)?,
+            };
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,230 @@
+//! XLSX export that writes row by row.
+//!
+//! The buyer-listings analyzer switched to `IncrementalCsvExporter` past 200
+//! items because the xlsx npm package builds the whole workbook in memory.
+//! This writes each data sheet in constant memory (rows go to a temporary
+//! file as they are added), starts a new sheet when one reaches Excel's
+//! 1,048,576-row limit, and adds the summary sheets once the data is done.
+//!
+//! [`export_listings_streaming`] reads an account's listings from the
+//! [`Warehouse`] one row at a time. The column names (item specifics differ
+//! between categories) are counted in SQLite first, so one pass over the rows
+//! keeps the column set of the in-memory export.
+//!
+//! Only the first 100 rows are held back, to size the columns the way
+//! `calculateColumnWidths` does, unless widths are given up front.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rust_xlsxwriter::Workbook;
+
+use super::listing_rows::ListingRow;
+use super::warehouse::Warehouse;
+use super::workbook::{
+    column_widths, finish_data_sheet, format_file_size, write_header, write_listing, write_summary,
+    CellFormats, Column, ExportResult, ListingSummary, SeenColumns, LISTINGS_SHEET, SUMMARY_SHEET,
+};
+use crate::error::{Error, Result};
+
+/// Rows in an Excel worksheet, header included.
+pub const MAX_SHEET_ROWS: u32 = 1_048_576;
+
+/// Rows held back to size the columns.
+const WIDTH_SAMPLE_ROWS: usize = 100;
+
+pub struct StreamingWorkbook {
+    workbook: Workbook,
+    path: PathBuf,
+    sheet_name: String,
+    columns: Vec<Column>,
+    widths: Option<Vec<f64>>,
+    frozen_columns: u16,
+    max_rows: u32,
+    formats: CellFormats,
+    pending: Vec<ListingRow>,
+    /// Index of the sheet being written and the data rows on it so far.
+    sheet: Option<(usize, u32)>,
+    sheets: usize,
+    total: usize,
+}
+
+impl StreamingWorkbook {
+    /// Data sheets are named `sheet_name`, then `sheet_name (2)` and so on.
+    pub fn create(
+        path: impl Into<PathBuf>,
+        sheet_name: impl Into<String>,
+        columns: Vec<Column>,
+    ) -> Self {
+        Self {
+            workbook: Workbook::new(),
+            path: path.into(),
+            sheet_name: sheet_name.into(),
+            columns,
+            widths: None,
+            frozen_columns: 1,
+            max_rows: MAX_SHEET_ROWS - 1,
+            formats: CellFormats::new(),
+            pending: Vec::new(),
+            sheet: None,
+            sheets: 0,
+            total: 0,
+        }
+    }
+
+    /// Fixed column widths; rows are then written straight away.
+    pub fn with_column_widths(mut self, widths: Vec<f64>) -> Self {
+        self.widths = Some(widths);
+        self
+    }
+
+    /// Columns kept in view when scrolling sideways (default 1).
+    pub fn with_frozen_columns(mut self, columns: u16) -> Self {
+        self.frozen_columns = columns;
+        self
+    }
+
+    /// Data rows per sheet before rolling over (used by tests).
+    pub fn with_max_rows_per_sheet(mut self, rows: u32) -> Self {
+        self.max_rows = rows.clamp(1, MAX_SHEET_ROWS - 1);
+        self
+    }
+
+    pub fn append(&mut self, row: &ListingRow) -> Result<()> {
+        if self.widths.is_none() {
+            self.pending.push(row.clone());
+            if self.pending.len() >= WIDTH_SAMPLE_ROWS {
+                self.flush_pending()?;
+            }
+            return Ok(());
+        }
+        self.write_row(row)
+    }
+
+    pub fn append_rows<'r>(
+        &mut self,
+        rows: impl IntoIterator<Item = &'r ListingRow>,
+    ) -> Result<()> {
+        rows.into_iter().try_for_each(|row| self.append(row))
+    }
+
+    /// Rows appended so far.
+    pub fn rows_written(&self) -> usize {
+        self.total + self.pending.len()
+    }
+
+    /// Close the last data sheet, let `summaries` add sheets after it, and
+    /// save.
+    pub fn finish(
+        mut self,
+        summaries: impl FnOnce(&mut Workbook) -> Result<()>,
+    ) -> Result<ExportResult> {
+        self.flush_pending()?;
+        if self.total == 0 {
+            return Err(Error::Spreadsheet("No data to export".to_string()));
+        }
+        self.close_sheet()?;
+        summaries(&mut self.workbook)?;
+
+        self.workbook.save(&self.path)?;
+        let file_size = fs::metadata(&self.path)?.len();
+        log::info!(
+            "Excel file created: {} ({}, {} rows on {} sheet(s))",
+            self.path.display(),
+            format_file_size(file_size),
+            self.total,
+            self.sheets
+        );
+        Ok(ExportResult {
+            filename: self.path,
+            record_count: self.total,
+            file_size,
+        })
+    }
+
+    fn flush_pending(&mut self) -> Result<()> {
+        if self.widths.is_none() {
+            self.widths = Some(column_widths(&self.columns, &self.pending));
+        }
+        for row in std::mem::take(&mut self.pending) {
+            self.write_row(&row)?;
+        }
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &ListingRow) -> Result<()> {
+        let (index, written) = match self.sheet {
+            Some((index, written)) if written < self.max_rows => (index, written),
+            _ => {
+                self.close_sheet()?;
+                (self.open_sheet()?, 0)
+            }
+        };
+
+        let sheet = self.workbook.worksheet_from_index(index)?;
+        write_listing(sheet, written + 1, &self.columns, row, &mut self.formats)?;
+        self.sheet = Some((index, written + 1));
+        self.total += 1;
+        Ok(())
+    }
+
+    fn open_sheet(&mut self) -> Result<usize> {
+        self.sheets += 1;
+        let name = match self.sheets {
+            1 => self.sheet_name.clone(),
+            n => format!("{} ({n})", self.sheet_name),
+        };
+        if self.sheets > 1 {
+            log::info!("Sheet row limit reached, continuing on {name:?}");
+        }
+
+        let sheet = self.workbook.add_worksheet_with_constant_memory();
+        sheet.set_name(&name)?;
+        write_header(sheet, &self.columns, &self.formats)?;
+        for (col, width) in (0u16..).zip(self.widths.iter().flatten()) {
+            sheet.set_column_width(col, *width)?;
+        }
+        Ok(self.workbook.worksheets().len() - 1)
+    }
+
+    fn close_sheet(&mut self) -> Result<()> {
+        if let Some((index, written)) = self.sheet.take() {
+            let sheet = self.workbook.worksheet_from_index(index)?;
+            finish_data_sheet(sheet, &self.columns, written, self.frozen_columns)?;
+        }
+        Ok(())
+    }
+}
+
+/// The exporter workbook ("All Listings" plus "Summary") of `account`'s
+/// listings, for catalogs too big to hold in memory. The columns are those
+/// [`columns_for`] would pick, item specifics included.
+///
+/// [`columns_for`]: super::workbook::columns_for
+pub fn export_listings_streaming(
+    warehouse: &Warehouse,
+    account: &str,
+    path: impl AsRef<Path>,
+) -> Result<ExportResult> {
+    let path = path.as_ref();
+    log::info!("Creating Excel file: {}", path.display());
+
+    let mut seen = SeenColumns::default();
+    warehouse
+        .listing_column_usage(account)?
+        .iter()
+        .for_each(|usage| seen.add_usage(usage));
+
+    let mut writer = StreamingWorkbook::create(path, LISTINGS_SHEET, seen.columns());
+    let mut summary = ListingSummary::default();
+    warehouse.for_each_listing(account, |row| {
+        summary.add(&row);
+        writer.append(&seen.normalize(row))
+    })?;
+
+    writer.finish(|workbook| {
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(SUMMARY_SHEET)?;
+        write_summary(sheet, &summary)
+    })
+}
@@ -0,0 +1,4 @@
+//! Main API logic: listings exporters and anything else built on the
+//! seller's private APIs.
+
+pub mod ebay;
@@ -0,0 +1,18 @@
+//! Rust backend for the eBay Connect desktop app.
+//!
+//! Modules mirror the Node.js layout under `src/`:
+//! - `adapters`: authentication and token handling
+//! - `internal`: seller API clients and the listings exporter
+//! - `trade`: the buyer-side competitor analyzer
+//! - `accounts`: registry of seller accounts every job runs against
+
+pub mod accounts;
+pub mod adapters;
+pub mod environment;
+pub mod error;
+pub mod internal;
+pub mod trade;
+
+pub use accounts::{Account, AccountRegistry};
+pub use environment::Environment;
+pub use error::{Error, Result};
@@ -0,0 +1,155 @@
+//! Competitor rows from the buyer listings analyzer.
+//!
+//! `CompetitorAnalyzer.findCompetitors` in `ebay-buyer-listings.js` builds one
+//! row per competing listing: the seller's own item ("My …"), the competitor
+//! ("Comp …"), comparison metrics and YES/NO selling signals. Past 200 items
+//! the analyzer streams those rows to CSV with `IncrementalCsvExporter`. This
+//! reads that CSV back with each column typed, and writes the rows to Parquet.
+//!
+//! Rows use the same [`ListingRow`] map as the seller exporter, keyed by the
+//! JS column names.
+
+use std::path::Path;
+
+use crate::error::Result;
+use crate::internal::ebay::listing_rows::{col, Cell, ColumnKind, ColumnSpec, ListingRow};
+use crate::internal::ebay::parquet_export::ParquetWriter;
+use crate::internal::ebay::workbook::{Column, ExportResult};
+
+/// `findCompetitors` columns in export order (the `sampleHeaders` of the CSV
+/// mode).
+pub const COMPETITOR_COLUMNS: &[ColumnSpec] = {
+    use ColumnKind::*;
+    &[
+        col("My Product", Text),
+        col("My Category", Text),
+        col("My Item ID", Text),
+        col("My SKU", Text),
+        col("My Price", Price),
+        col("My Quantity", Integer),
+        col("My Watchers", Integer),
+        col("My Views", Integer),
+        col("My Sold", Integer),
+        col("My URL", Text),
+        col("Comp Title", Text),
+        col("Comp Price", Price),
+        col("Comp Shipping", Price),
+        col("Comp Total Price", Price),
+        col("Comp Seller", Text),
+        col("Comp Feedback %", Decimal),
+        col("Comp Feedback Score", Integer),
+        col("Comp Location", Text),
+        col("Comp Available", Integer),
+        col("Comp Sold", Integer),
+        col("Comp URL", Text),
+        col("Comp Item ID", Text),
+        col("Price Diff ($)", Price),
+        col("Price Diff (%)", Decimal),
+        col("Price Position", Text),
+        col("Feedback Advantage", Text),
+        col("Search Rank", Integer),
+        col("Fast Selling", Bool),
+        col("Low Stock", Bool),
+        col("Top Rated", Bool),
+        col("Free Shipping", Bool),
+    ]
+};
+
+pub fn column(name: &str) -> Option<&'static ColumnSpec> {
+    COMPETITOR_COLUMNS.iter().find(|spec| spec.name == name)
+}
+
+/// Rows of an `IncrementalCsvExporter` file, values coerced to their column
+/// kind. Columns the analyzer does not know are kept as text. Prices may
+/// carry a currency symbol or thousands separators ("US $1,299.00"); a value
+/// that still does not fit its column is logged and left empty, so one odd
+/// cell does not stop the export.
+pub fn read_competitor_csv(
+    path: impl AsRef<Path>,
+) -> Result<impl Iterator<Item = Result<ListingRow>>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let columns: Vec<(String, ColumnKind)> = reader
+        .headers()?
+        .iter()
+        .map(|name| {
+            let kind = column(name).map_or(ColumnKind::Text, |spec| spec.kind);
+            (name.to_string(), kind)
+        })
+        .collect();
+
+    Ok(reader.into_records().map(move |record| {
+        let record = record?;
+        let line = record.position().map_or(0, |p| p.line());
+        let mut row = ListingRow::default();
+        for ((name, kind), value) in columns.iter().zip(record.iter()) {
+            row.set(name.clone(), typed_cell(*kind, value, name, line));
+        }
+        Ok(row)
+    }))
+}
+
+fn typed_cell(kind: ColumnKind, value: &str, name: &str, line: u64) -> Cell {
+    let cell = match kind.coerce(Cell::Text(value.to_string())) {
+        Cell::Text(text) if kind == ColumnKind::Price => {
+            parse_price(&text).map_or(Cell::Text(text), Cell::Number)
+        }
+        cell => cell,
+    };
+    match (kind, cell) {
+        (ColumnKind::Text | ColumnKind::Date | ColumnKind::DateTime, cell) => cell,
+        (_, Cell::Text(text)) => {
+            log::warn!("Line {line}: {name:?} value {text:?} is not a valid {kind:?}; left empty");
+            Cell::Empty
+        }
+        (_, cell) => cell,
+    }
+}
+
+/// `text` without currency symbols, codes and thousands separators.
+fn parse_price(text: &str) -> Option<f64> {
+    let negative = text.trim_start().starts_with('-');
+    let amount: String = text
+        .trim_start_matches(|c: char| !c.is_ascii_digit() && c != '.')
+        .trim_end_matches(|c: char| !c.is_ascii_digit())
+        .chars()
+        .filter(|c| *c != ',')
+        .collect();
+    let value: f64 = amount.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+fn parquet_columns() -> Vec<Column> {
+    COMPETITOR_COLUMNS.iter().map(Column::from).collect()
+}
+
+/// Write competitor rows to `path` with the [`COMPETITOR_COLUMNS`] schema.
+pub fn export_competitors_parquet(
+    rows: impl IntoIterator<Item = ListingRow>,
+    path: impl AsRef<Path>,
+) -> Result<ExportResult> {
+    let path = path.as_ref();
+    log::info!("Creating Parquet file: {}", path.display());
+    let mut writer = ParquetWriter::create(path, &parquet_columns())?;
+    for row in rows {
+        writer.append(&row)?;
+    }
+    writer.finish()
+}
+
+/// Convert an analyzer CSV to Parquet without loading it whole.
+pub fn competitor_csv_to_parquet(
+    csv_path: impl AsRef<Path>,
+    parquet_path: impl AsRef<Path>,
+) -> Result<ExportResult> {
+    let parquet_path = parquet_path.as_ref();
+    log::info!(
+        "Converting {} to Parquet: {}",
+        csv_path.as_ref().display(),
+        parquet_path.display()
+    );
+    let mut writer = ParquetWriter::create(parquet_path, &parquet_columns())?;
+    for row in read_competitor_csv(csv_path)? {
+        writer.append(&row?)?;
+    }
+    writer.finish()
+}
@@ -0,0 +1,3 @@
+//! eBay buyer listings analyzer.
+
+pub mod competitors;
@@ -0,0 +1,3 @@
+//! Buyer-side tools: competitor analysis built on eBay's public listings.
+
+pub mod ebay;
@@ -0,0 +1,194 @@
+use std::path::PathBuf;
+
+use ebay_connect::accounts::AccountConfig;
+use ebay_connect::adapters::ebay::oauth::{DEFAULT_SCOPES, MARKETING_READ_SCOPE, MARKETING_SCOPE};
+use ebay_connect::adapters::ebay::{StoredTokens, TokenVault, VaultKey};
+use ebay_connect::internal::ebay::marketing::{MarketingClient, PromotionType};
+use ebay_connect::{AccountRegistry, Environment, Error};
+
+fn key() -> VaultKey {
+    VaultKey::Machine([7; 32])
+}
+
+fn registry_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "ebay-connect-{}-accounts-{name}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn config(name: &str, environment: Environment) -> AccountConfig {
+    AccountConfig {
+        name: name.to_string(),
+        client_id: format!("{name}-client"),
+        client_secret: "secret".to_string(),
+        dev_id: None,
+        ru_name: format!("{name}-ru"),
+        environment,
+        site_id: 0,
+        marketplace_id: "EBAY_US".to_string(),
+        scopes: Vec::new(),
+    }
+}
+
+/// Store tokens for `name` the way a finished consent flow would.
+fn authorize(dir: &std::path::Path, name: &str, environment: Environment) {
+    authorize_scopes(dir, name, environment, &[]);
+}
+
+fn authorize_scopes(dir: &std::path::Path, name: &str, environment: Environment, scopes: &[&str]) {
+    let vault = TokenVault::new(dir.join("tokens").join(format!("{name}.vault")), key());
+    vault
+        .save(&StoredTokens {
+            access_token: format!("{name}-access"),
+            refresh_token: Some(format!("{name}-refresh")),
+            expires_at: 4_000_000_000,
+            refresh_token_expires_at: None,
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            environment,
+        })
+        .unwrap();
+}
+
+#[test]
+fn accounts_survive_a_reload() {
+    let dir = registry_dir("reload");
+    let mut registry = AccountRegistry::open(&dir, key()).unwrap();
+    registry
+        .add(config("bedding", Environment::Production))
+        .unwrap();
+    registry
+        .add(config("outlet", Environment::Sandbox))
+        .unwrap();
+    authorize(&dir, "bedding", Environment::Production);
+
+    let reloaded = AccountRegistry::open(&dir, key()).unwrap();
+    let names: Vec<&str> = reloaded.accounts().iter().map(|a| a.name()).collect();
+    assert_eq!(names, ["bedding", "outlet"]);
+    let bedding = reloaded.get("bedding").unwrap();
+    assert_eq!(bedding.environment(), Environment::Production);
+    assert_eq!(bedding.config().client_id, "bedding-client");
+    let summaries = reloaded.summaries();
+    assert!(summaries[0].authorized);
+    assert!(!summaries[1].authorized);
+
+    // The registry file is encrypted.
+    let raw = std::fs::read(dir.join("accounts.vault")).unwrap();
+    assert!(!String::from_utf8_lossy(&raw).contains("bedding-client"));
+    assert!(matches!(
+        AccountRegistry::open(&dir, VaultKey::Machine([8; 32])),
+        Err(Error::Vault(_))
+    ));
+}
+
+#[test]
+fn duplicate_and_unsafe_names_are_refused() {
+    let dir = registry_dir("names");
+    let mut registry = AccountRegistry::open(&dir, key()).unwrap();
+    registry
+        .add(config("bedding", Environment::Sandbox))
+        .unwrap();
+
+    assert!(matches!(
+        registry.add(config("bedding", Environment::Sandbox)),
+        Err(Error::Account(_))
+    ));
+    assert!(matches!(
+        registry.add(config("../bedding", Environment::Sandbox)),
+        Err(Error::Account(_))
+    ));
+    assert!(matches!(
+        registry.update(config("missing", Environment::Sandbox)),
+        Err(Error::Account(_))
+    ));
+    assert_eq!(registry.accounts().len(), 1);
+}
+
+#[test]
+fn update_keeps_tokens_unless_the_environment_changes() {
+    let dir = registry_dir("update");
+    let mut registry = AccountRegistry::open(&dir, key()).unwrap();
+    registry
+        .add(config("bedding", Environment::Sandbox))
+        .unwrap();
+    authorize(&dir, "bedding", Environment::Sandbox);
+
+    let mut changed = config("bedding", Environment::Sandbox);
+    changed.marketplace_id = "EBAY_GB".to_string();
+    let account = registry.update(changed).unwrap();
+    assert_eq!(account.marketplace_id(), "EBAY_GB");
+    assert!(account.stored_tokens().unwrap().is_some());
+
+    let account = registry
+        .update(config("bedding", Environment::Production))
+        .unwrap();
+    assert!(account.stored_tokens().unwrap().is_none());
+
+    let reloaded = AccountRegistry::open(&dir, key()).unwrap();
+    let bedding = reloaded.get("bedding").unwrap();
+    assert_eq!(bedding.environment(), Environment::Production);
+    assert_eq!(bedding.marketplace_id(), "EBAY_US");
+    assert!(!reloaded.summaries()[0].authorized);
+}
+
+#[test]
+fn remove_deletes_the_account_and_its_tokens() {
+    let dir = registry_dir("remove");
+    let mut registry = AccountRegistry::open(&dir, key()).unwrap();
+    registry
+        .add(config("bedding", Environment::Sandbox))
+        .unwrap();
+    registry
+        .add(config("outlet", Environment::Sandbox))
+        .unwrap();
+    authorize(&dir, "bedding", Environment::Sandbox);
+
+    registry.remove("bedding").unwrap();
+    assert!(!dir.join("tokens").join("bedding.vault").exists());
+    assert!(matches!(registry.remove("bedding"), Err(Error::Account(_))));
+
+    let reloaded = AccountRegistry::open(&dir, key()).unwrap();
+    let names: Vec<&str> = reloaded.accounts().iter().map(|a| a.name()).collect();
+    assert_eq!(names, ["outlet"]);
+}
+
+#[tokio::test]
+async fn marketing_needs_a_marketing_grant() {
+    assert!(DEFAULT_SCOPES.contains(&MARKETING_SCOPE));
+    assert!(DEFAULT_SCOPES.contains(&MARKETING_READ_SCOPE));
+
+    let dir = registry_dir("marketing");
+    let mut registry = AccountRegistry::open(&dir, key()).unwrap();
+    let inventory_only = "https://api.ebay.com/oauth/api_scope/sell.inventory";
+    for name in ["inventory", "readonly", "full"] {
+        registry.add(config(name, Environment::Sandbox)).unwrap();
+    }
+    assert!(matches!(
+        MarketingClient::for_account(&registry.get("inventory").unwrap()),
+        Err(Error::NotAuthorized(_))
+    ));
+
+    authorize_scopes(&dir, "inventory", Environment::Sandbox, &[inventory_only]);
+    authorize_scopes(
+        &dir,
+        "readonly",
+        Environment::Sandbox,
+        &[MARKETING_READ_SCOPE],
+    );
+    authorize_scopes(&dir, "full", Environment::Sandbox, &[MARKETING_SCOPE]);
+
+    let err = MarketingClient::for_account(&registry.get("inventory").unwrap()).unwrap_err();
+    assert!(
+        matches!(&err, Error::NotAuthorized(message) if message.contains("sell.marketing")),
+        "{err}"
+    );
+    // Reads are allowed, changes are refused before anything is sent.
+    let readonly = MarketingClient::for_account(&registry.get("readonly").unwrap()).unwrap();
+    assert!(matches!(
+        readonly.pause("P1", PromotionType::OrderDiscount).await,
+        Err(Error::NotAuthorized(_))
+    ));
+    MarketingClient::for_account(&registry.get("full").unwrap()).unwrap();
+}
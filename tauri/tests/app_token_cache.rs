@@ -0,0 +1,173 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use ebay_connect::adapters::ebay::app_token::{AppTokenCache, DEFAULT_APP_SCOPE};
+use ebay_connect::Environment;
+
+use common::{MockResponse, MockServer};
+
+const BROWSE_SCOPE: &str = "https://api.ebay.com/oauth/api_scope/buy.item.bulk";
+
+/// Identity server that hands out numbered tokens valid for `expires_in`.
+async fn identity_server(expires_in: u64) -> MockServer {
+    let issued = AtomicUsize::new(0);
+    MockServer::start(move |request| {
+        assert_eq!(request.form_value("grant_type").as_deref(), Some("client_credentials"));
+        let n = issued.fetch_add(1, Ordering::SeqCst) + 1;
+        MockResponse::json(
+            200,
+            format!(
+                r#"{{"access_token":"app-token-{n}","expires_in":{expires_in},"token_type":"Application Access Token"}}"#
+            ),
+        )
+    })
+    .await
+}
+
+fn cache(server: &MockServer) -> AppTokenCache {
+    AppTokenCache::new("client-id", "client-secret")
+        .with_token_url(
+            Environment::Sandbox,
+            server.url("/identity/v1/oauth2/token"),
+        )
+        .with_token_url(
+            Environment::Production,
+            server.url("/identity/v1/oauth2/token"),
+        )
+}
+
+#[tokio::test]
+async fn concurrent_callers_share_one_fetch() {
+    let server = identity_server(7200).await;
+    let cache = Arc::new(cache(&server));
+
+    let tasks: Vec<_> = (0..50)
+        .map(|_| {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                cache
+                    .token(Environment::Production, &[DEFAULT_APP_SCOPE])
+                    .await
+                    .unwrap()
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        assert_eq!(task.await.unwrap(), "app-token-1");
+    }
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn tokens_are_kept_per_environment_and_scope_set() {
+    let server = identity_server(7200).await;
+    let cache = cache(&server);
+
+    let a = cache
+        .token(Environment::Production, &[DEFAULT_APP_SCOPE])
+        .await
+        .unwrap();
+    let b = cache
+        .token(Environment::Sandbox, &[DEFAULT_APP_SCOPE])
+        .await
+        .unwrap();
+    let c = cache
+        .token(Environment::Production, &[DEFAULT_APP_SCOPE, BROWSE_SCOPE])
+        .await
+        .unwrap();
+    // Same scope set in a different order hits the same entry.
+    let d = cache
+        .token(Environment::Production, &[BROWSE_SCOPE, DEFAULT_APP_SCOPE])
+        .await
+        .unwrap();
+
+    assert_ne!(a, b);
+    assert_ne!(a, c);
+    assert_eq!(c, d);
+    assert_eq!(server.requests().len(), 3);
+
+    let scopes: Vec<_> = server
+        .requests()
+        .iter()
+        .map(|r| r.form_value("scope").unwrap())
+        .collect();
+    assert!(scopes.contains(&format!("{DEFAULT_APP_SCOPE} {BROWSE_SCOPE}")));
+}
+
+#[tokio::test]
+async fn token_close_to_expiry_is_refetched() {
+    // Inside the 300-second buffer, so every call needs a new token.
+    let server = identity_server(120).await;
+    let cache = cache(&server);
+
+    let first = cache
+        .token(Environment::Production, &[DEFAULT_APP_SCOPE])
+        .await
+        .unwrap();
+    let second = cache
+        .token(Environment::Production, &[DEFAULT_APP_SCOPE])
+        .await
+        .unwrap();
+
+    assert_eq!(first, "app-token-1");
+    assert_eq!(second, "app-token-2");
+}
+
+#[tokio::test]
+async fn invalidate_only_drops_the_stale_token() {
+    let server = identity_server(7200).await;
+    let cache = cache(&server);
+
+    let first = cache
+        .token(Environment::Production, &[DEFAULT_APP_SCOPE])
+        .await
+        .unwrap();
+    cache
+        .invalidate(
+            Environment::Production,
+            &[DEFAULT_APP_SCOPE],
+            "some-other-token",
+        )
+        .await;
+    assert_eq!(
+        cache
+            .token(Environment::Production, &[DEFAULT_APP_SCOPE])
+            .await
+            .unwrap(),
+        first
+    );
+
+    cache
+        .invalidate(Environment::Production, &[DEFAULT_APP_SCOPE], &first)
+        .await;
+    assert_eq!(
+        cache
+            .token(Environment::Production, &[DEFAULT_APP_SCOPE])
+            .await
+            .unwrap(),
+        "app-token-2"
+    );
+}
+
+#[tokio::test]
+async fn no_scopes_means_the_default_scope() {
+    let server = identity_server(7200).await;
+    let cache = cache(&server);
+
+    let none = cache.token(Environment::Production, &[]).await.unwrap();
+    let default = cache
+        .token(Environment::Production, &[DEFAULT_APP_SCOPE])
+        .await
+        .unwrap();
+
+    assert_eq!(none, default);
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].form_value("scope").as_deref(),
+        Some(DEFAULT_APP_SCOPE)
+    );
+}
@@ -0,0 +1,242 @@
+use chrono::{DateTime, TimeZone, Utc};
+use ebay_connect::internal::ebay::listing_rows::{Cell, ListingRow};
+use ebay_connect::internal::ebay::marketing::{
+    ItemPriceMarkdown, ItemPromotion, Promotion, PromotionCalendar,
+};
+use serde_json::{json, Value};
+
+fn day(n: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, n, 0, 0, 0).unwrap()
+}
+
+fn listing(
+    item_id: &str,
+    sku: &str,
+    category: &str,
+    brand: &str,
+    price: f64,
+    currency: &str,
+) -> ListingRow {
+    let mut row = ListingRow::default();
+    row.set("Item ID", Cell::Text(item_id.to_string()));
+    row.set("SKU", Cell::Text(sku.to_string()));
+    row.set("Title", Cell::Text(format!("Listing {item_id}")));
+    row.set("Category ID", Cell::Text(category.to_string()));
+    row.set("Brand", Cell::Text(brand.to_string()));
+    row.set("Condition ID", Cell::Text("1000".to_string()));
+    row.set("Current Price", Cell::Number(price));
+    row.set("Currency", Cell::Text(currency.to_string()));
+    row
+}
+
+fn catalog() -> Vec<ListingRow> {
+    vec![
+        listing("1001", "SKU-1", "123", "Acme", 20.0, "USD"),
+        listing("1002", "SKU-2", "123", "Acme", 60.0, "USD"),
+        listing("1003", "SKU-3", "123", "Acme", 20.0, "GBP"),
+        listing("1004", "SKU-4", "123", "acme", 25.0, "USD"),
+        listing("1005", "SKU-5", "456", "Acme", 30.0, "USD"),
+    ]
+}
+
+fn by_value(listing_ids: &[&str], skus: &[&str]) -> Value {
+    let items: Vec<Value> = skus
+        .iter()
+        .map(|sku| json!({ "inventoryReferenceId": sku }))
+        .collect();
+    json!({
+        "inventoryCriterionType": "INVENTORY_BY_VALUE",
+        "listingIds": listing_ids,
+        "inventoryItems": items
+    })
+}
+
+fn markdown(id: &str, status: &str, start: u32, end: u32, criterion: Value) -> (String, Promotion) {
+    let markdown: ItemPriceMarkdown = serde_json::from_value(json!({
+        "promotionId": id,
+        "name": format!("Markdown {id}"),
+        "marketplaceId": "EBAY_US",
+        "promotionStatus": status,
+        "startDate": day(start),
+        "endDate": day(end),
+        "selectedInventoryDiscounts": [{
+            "discountBenefit": { "percentageOffItem": "20" },
+            "inventoryCriterion": criterion
+        }]
+    }))
+    .unwrap();
+    (id.to_string(), markdown.into())
+}
+
+fn order_discount(id: &str, start: u32, end: u32, criterion: Value) -> (String, Promotion) {
+    let promotion: ItemPromotion = serde_json::from_value(json!({
+        "promotionId": id,
+        "name": format!("Order discount {id}"),
+        "marketplaceId": "EBAY_US",
+        "promotionType": "ORDER_DISCOUNT",
+        "promotionStatus": "RUNNING",
+        "startDate": day(start),
+        "endDate": day(end),
+        "discountRules": [{ "discountBenefit": { "amountOffOrder": { "value": "5.00", "currency": "USD" } } }],
+        "inventoryCriterion": criterion
+    }))
+    .unwrap();
+    (id.to_string(), promotion.into())
+}
+
+fn item_ids(calendar: &PromotionCalendar, promotion_id: &str) -> Vec<String> {
+    calendar
+        .promotions()
+        .iter()
+        .find(|p| p.promotion_id == promotion_id)
+        .unwrap()
+        .item_ids
+        .iter()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn criteria_resolve_against_the_catalog() {
+    let by_rule = json!({
+        "inventoryCriterionType": "INVENTORY_BY_RULE",
+        "ruleCriteria": {
+            "selectionRules": [{
+                "categoryIds": ["123"],
+                "brands": ["ACME"],
+                "minPrice": { "value": "10.00", "currency": "USD" },
+                "maxPrice": { "value": "50.00", "currency": "USD" }
+            }],
+            "excludeListingIds": ["1004"]
+        }
+    });
+    let calendar = PromotionCalendar::new(
+        vec![
+            markdown(
+                "VALUE",
+                "RUNNING",
+                1,
+                10,
+                by_value(&["1001", "9999"], &["SKU-2"]),
+            ),
+            markdown("RULE", "RUNNING", 1, 10, by_rule),
+            order_discount(
+                "ANY",
+                1,
+                10,
+                json!({ "inventoryCriterionType": "INVENTORY_ANY" }),
+            ),
+        ],
+        &catalog(),
+    );
+
+    // 9999 is not in the catalog.
+    assert_eq!(item_ids(&calendar, "VALUE"), ["1001", "1002"]);
+    // 1002 is too dear, 1003 is priced in pounds, 1004 is excluded and 1005
+    // is in another category.
+    assert_eq!(item_ids(&calendar, "RULE"), ["1001"]);
+    assert_eq!(item_ids(&calendar, "ANY").len(), 5);
+    let any = &calendar.promotions()[0];
+    assert_eq!(any.promotion_id, "ANY");
+    assert_eq!(any.discount.as_deref(), Some("5.00 USD off order"));
+}
+
+#[test]
+fn overlapping_markdowns_are_clipped_to_the_range() {
+    let calendar = PromotionCalendar::new(
+        vec![
+            markdown("A", "RUNNING", 1, 20, by_value(&["1001", "1002"], &[])),
+            markdown("B", "SCHEDULED", 10, 31, by_value(&["1001", "1003"], &[])),
+            // Paused, and only order discounts: neither counts.
+            markdown("C", "PAUSED", 1, 31, by_value(&["1001"], &[])),
+            order_discount("D", 1, 31, by_value(&["1001"], &[])),
+            // Same listings as B, but only after A has ended.
+            markdown("E", "SCHEDULED", 20, 25, by_value(&["1001"], &[])),
+        ],
+        &catalog(),
+    );
+
+    let overlaps = calendar.overlaps(day(1), day(31));
+    let pairs: Vec<(&[String; 2], DateTime<Utc>, DateTime<Utc>)> = overlaps
+        .iter()
+        .map(|o| (&o.promotion_ids, o.from, o.to))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            (&["A".to_string(), "B".to_string()], day(10), day(20)),
+            (&["B".to_string(), "E".to_string()], day(20), day(25)),
+        ]
+    );
+    assert_eq!(overlaps[0].item_ids, ["1001"]);
+
+    let clipped = calendar.overlaps(day(12), day(15));
+    assert_eq!(clipped.len(), 1);
+    assert_eq!((clipped[0].from, clipped[0].to), (day(12), day(15)));
+    assert!(calendar.overlaps(day(26), day(31)).is_empty());
+}
+
+/// Item ID, from, until and the promotion that opened the gap.
+type GapSummary<'a> = (
+    &'a str,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+    Option<&'a str>,
+);
+
+#[test]
+fn gaps_are_found_by_walking_each_listings_promotions() {
+    let calendar = PromotionCalendar::new(
+        vec![
+            markdown("A", "RUNNING", 1, 10, by_value(&["1001", "1002"], &[])),
+            // Picks up right where A ends.
+            markdown("B", "SCHEDULED", 10, 20, by_value(&["1001"], &[])),
+            markdown("C", "SCHEDULED", 25, 31, by_value(&["1001"], &[])),
+            markdown("P", "PAUSED", 1, 31, by_value(&["1003"], &[])),
+            // Ends before the walk starts.
+            markdown("OLD", "RUNNING", 1, 3, by_value(&["1004"], &[])),
+        ],
+        &catalog(),
+    );
+
+    let gaps = calendar.gaps_after(day(5));
+    let summary: Vec<GapSummary<'_>> = gaps
+        .iter()
+        .map(|g| {
+            (
+                g.item_id.as_str(),
+                g.from,
+                g.until,
+                g.last_promotion_id.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("1003", day(5), None, None),
+            ("1004", day(5), None, None),
+            ("1005", day(5), None, None),
+            ("1002", day(10), None, Some("A")),
+            ("1001", day(20), Some(day(25)), Some("B")),
+        ]
+    );
+    assert_eq!(gaps[0].sku, "SKU-3");
+
+    let uncovered: Vec<String> = calendar
+        .uncovered_after(day(5))
+        .into_iter()
+        .map(|g| g.item_id)
+        .collect();
+    assert_eq!(uncovered, ["1003", "1004", "1005", "1002"]);
+
+    let view = calendar.view(day(5), day(15));
+    let promotions: Vec<&str> = view
+        .promotions
+        .iter()
+        .map(|p| p.promotion_id.as_str())
+        .collect();
+    assert_eq!(promotions, ["A", "P", "B"]);
+    // 1001's gap opens after the range.
+    assert!(view.gaps.iter().all(|g| g.item_id != "1001"));
+}
@@ -0,0 +1,84 @@
+use std::fs::File;
+use std::path::PathBuf;
+
+use arrow_array::{Array, BooleanArray, Decimal128Array, Float64Array, Int64Array, StringArray};
+use arrow_schema::DataType;
+use ebay_connect::internal::ebay::listing_rows::Cell;
+use ebay_connect::trade::ebay::competitors::{competitor_csv_to_parquet, read_competitor_csv};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+const CSV: &str = "\
+My Item ID,My Price,Comp Title,Comp Price,Comp Sold,Comp Feedback %,Price Diff ($),Fast Selling,Notes
+110001,24.99,Linen sheet set,US $1299.00,12,99.5%,-$1.00,YES,first
+110002,$19.50,\"Cotton sheets, queen\",\"$1,050.25\",n/a,98%,0.50,NO,second
+";
+
+fn write_csv(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-competitors", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, CSV).unwrap();
+    path
+}
+
+#[test]
+fn prices_with_currency_symbols_are_read_as_numbers() {
+    let path = write_csv("read.csv");
+    let rows: Vec<_> = read_competitor_csv(&path)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(rows[0].get("Comp Price"), &Cell::Number(1299.0));
+    assert_eq!(rows[0].get("Price Diff ($)"), &Cell::Number(-1.0));
+    assert_eq!(rows[1].get("My Price"), &Cell::Number(19.5));
+    assert_eq!(rows[1].get("Comp Price"), &Cell::Number(1050.25));
+    // Not a number: left empty rather than failing the export.
+    assert_eq!(rows[1].get("Comp Sold"), &Cell::Empty);
+    assert_eq!(rows[0].get("Fast Selling"), &Cell::Bool(true));
+}
+
+#[test]
+fn csv_converts_to_typed_parquet() {
+    let csv = write_csv("convert.csv");
+    let parquet = csv.with_extension("parquet");
+    let result = competitor_csv_to_parquet(&csv, &parquet).unwrap();
+    assert_eq!(result.record_count, 2);
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&parquet).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let batch = reader.into_iter().next().unwrap().unwrap();
+    assert_eq!(batch.num_rows(), 2);
+    // The full competitor schema, whatever the CSV carried; unknown columns
+    // are left out.
+    assert!(batch.column_by_name("Comp Seller").is_some());
+    assert!(batch.column_by_name("Notes").is_none());
+
+    let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+    let item_ids = column("My Item ID");
+    let item_ids = item_ids.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(item_ids.value(0), "110001");
+
+    let prices = column("Comp Price");
+    assert_eq!(prices.data_type(), &DataType::Decimal128(18, 2));
+    let prices = prices.as_any().downcast_ref::<Decimal128Array>().unwrap();
+    assert_eq!(prices.value(0), 129_900);
+    assert_eq!(prices.value(1), 105_025);
+
+    let sold = column("Comp Sold");
+    let sold = sold.as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(sold.value(0), 12);
+    assert!(sold.is_null(1));
+
+    let feedback = column("Comp Feedback %");
+    let feedback = feedback.as_any().downcast_ref::<Float64Array>().unwrap();
+    assert_eq!(feedback.value(0), 99.5);
+
+    let fast = column("Fast Selling");
+    let fast = fast.as_any().downcast_ref::<BooleanArray>().unwrap();
+    assert!(fast.value(0));
+    assert!(!fast.value(1));
+    assert!(column("Top Rated").is_null(0));
+}
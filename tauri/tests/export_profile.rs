@@ -0,0 +1,102 @@
+use calamine::{open_workbook, Data, Reader, Xlsx};
+use ebay_connect::internal::ebay::export_profile::{
+    Derivation, DeriveOp, ExportProfile, ProfileColumn, ProfileSet,
+};
+use ebay_connect::internal::ebay::listing_rows::{specific_column, Cell, ListingRow};
+use ebay_connect::Error;
+
+fn row(item_id: &str, price: f64, shipping: f64) -> ListingRow {
+    let mut row = ListingRow::default();
+    row.set("Item ID", Cell::Text(item_id.to_string()));
+    row.set("Title", Cell::Text(format!("Listing {item_id}")));
+    row.set("Currency", Cell::Text("USD".to_string()));
+    row.set("Current Price", Cell::Number(price));
+    row.set("Shipping Cost", Cell::Number(shipping));
+    row.set(specific_column("Brand"), Cell::Text("Acme".to_string()));
+    row
+}
+
+fn pricing() -> ExportProfile {
+    ExportProfile::new(
+        "pricing",
+        vec![
+            ProfileColumn::source("Item ID"),
+            ProfileColumn::source("Current Price").with_label("Price"),
+            ProfileColumn::derived(
+                "Price + Shipping",
+                Derivation::new(DeriveOp::Sum, &["Current Price", "Shipping Cost"]),
+            ),
+            ProfileColumn::source(specific_column("Brand")).with_label("Brand"),
+        ],
+    )
+}
+
+#[test]
+fn builtin_profiles_are_valid() {
+    for profile in ProfileSet::builtin().profiles {
+        profile.validate().unwrap();
+    }
+}
+
+#[test]
+fn unknown_source_columns_are_rejected() {
+    let typo = ExportProfile::new("typo", vec![ProfileColumn::source("Curent Price")]);
+    let err = typo.validate().unwrap_err();
+    assert!(matches!(&err, Error::Profile(message) if message.contains("Curent Price")));
+
+    let derived = ExportProfile::new(
+        "derived",
+        vec![ProfileColumn::derived(
+            "Margin",
+            Derivation::new(DeriveOp::Difference, &["Current Price", "Cost"]),
+        )],
+    );
+    assert!(matches!(derived.validate(), Err(Error::Profile(_))));
+
+    let json = r#"{ "profiles": [ { "name": "bad", "columns": [ { "column": "Prize" } ] } ] }"#;
+    assert!(matches!(
+        ProfileSet::from_json(json),
+        Err(Error::Profile(_))
+    ));
+
+    pricing().validate().unwrap();
+}
+
+#[test]
+fn profile_export_writes_the_projected_columns() {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-profile", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("pricing.xlsx");
+
+    let result = pricing()
+        .export(&[row("1001", 10.0, 4.5), row("1002", 20.0, 0.0)], &path)
+        .unwrap();
+    assert_eq!(result.record_count, 2);
+
+    let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+    assert_eq!(workbook.sheet_names(), ["All Listings", "Summary"]);
+    let sheet = workbook.worksheet_range("All Listings").unwrap();
+    let rows: Vec<&[Data]> = sheet.rows().collect();
+    assert_eq!(
+        rows[0],
+        [
+            Data::String("Item ID".to_string()),
+            Data::String("Price".to_string()),
+            Data::String("Price + Shipping".to_string()),
+            Data::String("Brand".to_string()),
+        ]
+    );
+    assert_eq!(rows[1][2], Data::Float(14.5));
+    assert_eq!(rows[2][3], Data::String("Acme".to_string()));
+    assert_eq!(rows.len(), 3);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn empty_export_is_refused() {
+    assert!(matches!(
+        pricing().workbook(&[]),
+        Err(Error::Spreadsheet(_))
+    ));
+}
@@ -0,0 +1,233 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use ebay_connect::internal::ebay::fees::{FeeEntry, FeeFetcher, FeeKind, FeeLedger};
+use ebay_connect::internal::ebay::listing_rows::{Cell, ListingRow};
+use ebay_connect::internal::ebay::trading::TradingClient;
+use ebay_connect::Environment;
+
+use common::{MockResponse, MockServer, RecordedRequest, StaticToken};
+
+fn entry(item_id: &str, entry_type: &str, amount: f64, currency: &str) -> FeeEntry {
+    FeeEntry {
+        date: Utc.with_ymd_and_hms(2024, 3, 5, 12, 0, 0).unwrap(),
+        item_id: Some(item_id.to_string()),
+        title: None,
+        kind: FeeKind::classify(entry_type),
+        entry_type: entry_type.to_string(),
+        description: None,
+        amount,
+        currency: currency.to_string(),
+        ref_number: None,
+    }
+}
+
+fn row(item_id: &str, listing_fee: f64, final_value_fee: f64) -> ListingRow {
+    let mut row = ListingRow::default();
+    row.set("Item ID", Cell::Text(item_id.to_string()));
+    row.set("Listing Fee", Cell::Number(listing_fee));
+    row.set("Final Value Fee", Cell::Number(final_value_fee));
+    row.set("Total Fees", Cell::Number(listing_fee + final_value_fee));
+    row
+}
+
+#[test]
+fn entry_types_are_classified_by_exact_code() {
+    assert_eq!(FeeKind::classify("FeeInsertion"), FeeKind::Insertion);
+    assert_eq!(FeeKind::classify("CreditInsertion"), FeeKind::Insertion);
+    assert_eq!(
+        FeeKind::classify("FeeFinalValueShipping"),
+        FeeKind::FinalValue
+    );
+    assert_eq!(FeeKind::classify("CreditFinalValue"), FeeKind::FinalValue);
+    assert_eq!(
+        FeeKind::classify("PromotedListingFee"),
+        FeeKind::PromotedListing
+    );
+    assert_eq!(
+        FeeKind::classify("FeeInternationalListing"),
+        FeeKind::International
+    );
+    // Unlisted codes are not matched on fragments of their name.
+    assert_eq!(FeeKind::classify("FeeGallery"), FeeKind::Other);
+    assert_eq!(FeeKind::classify("feeinsertion"), FeeKind::Other);
+    assert_eq!(FeeKind::classify("FeeInsertionBundle"), FeeKind::Other);
+}
+
+#[test]
+fn only_charged_kinds_replace_the_listed_fees() {
+    let ledger = FeeLedger::new(vec![
+        entry("1001", "FeeFinalValue", 4.5, "USD"),
+        entry("1001", "CreditFinalValue", -1.0, "USD"),
+        entry("1001", "PromotedListingFee", 0.75, "USD"),
+    ]);
+    let mut rows = [row("1001", 0.35, 0.0), row("1002", 0.35, 2.0)];
+
+    let mixed = ledger.apply(&mut rows);
+    assert!(mixed.is_empty());
+
+    assert_eq!(rows[0].get("Listing Fee"), &Cell::Number(0.35));
+    assert_eq!(rows[0].get("Final Value Fee"), &Cell::Number(3.5));
+    assert_eq!(rows[0].get("Total Fees"), &Cell::Number(4.6));
+    // No entries: the row keeps what the item said.
+    assert_eq!(rows[1].get("Final Value Fee"), &Cell::Number(2.0));
+    assert_eq!(rows[1].get("Total Fees"), &Cell::Number(2.35));
+}
+
+#[test]
+fn listings_charged_in_several_currencies_are_left_alone() {
+    let ledger = FeeLedger::new(vec![
+        entry("1001", "FeeInsertion", 0.35, "USD"),
+        entry("1001", "FeeFinalValue", 3.0, "GBP"),
+        entry("1002", "FeeInsertion", 0.30, "GBP"),
+    ]);
+    let mut rows = [row("1001", 0.0, 1.0), row("1002", 0.0, 1.0)];
+
+    let mixed = ledger.apply(&mut rows);
+    assert_eq!(mixed, ["1001"]);
+
+    assert_eq!(rows[0].get("Listing Fee"), &Cell::Number(0.0));
+    assert_eq!(rows[0].get("Final Value Fee"), &Cell::Number(1.0));
+    assert_eq!(rows[0].get("Total Fees"), &Cell::Number(1.0));
+    assert_eq!(rows[1].get("Listing Fee"), &Cell::Number(0.3));
+    assert_eq!(rows[1].get("Total Fees"), &Cell::Number(1.3));
+
+    // The per-listing view still keeps the currencies apart.
+    let items = ledger.by_item(&[]);
+    assert_eq!(items.len(), 3);
+}
+
+fn tag<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let start = body.find(&format!("<{name}>"))? + name.len() + 2;
+    let end = body[start..].find(&format!("</{name}>"))? + start;
+    Some(&body[start..end])
+}
+
+fn march(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap()
+}
+
+fn account_entry(entry_type: &str, date: DateTime<Utc>, amount: &str, reference: &str) -> String {
+    format!(
+        "<AccountEntry><AccountDetailsEntryType>{entry_type}</AccountDetailsEntryType><Date>{}</Date><NetDetailAmount{amount}</NetDetailAmount><ItemID>1001</ItemID><RefNumber>{reference}</RefNumber></AccountEntry>",
+        date.to_rfc3339()
+    )
+}
+
+fn account_page(entries: &[String], page: u32, pages: u32) -> MockResponse {
+    MockResponse::xml(
+        200,
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><GetAccountResponse xmlns="urn:ebay:apis:eBLBaseComponents"><Ack>Success</Ack><Currency>GBP</Currency><AccountEntries>{}</AccountEntries><PaginationResult><TotalNumberOfPages>{pages}</TotalNumberOfPages></PaginationResult><PageNumber>{page}</PageNumber></GetAccountResponse>"#,
+            entries.concat()
+        ),
+    )
+}
+
+/// March 1-6 has two pages, March 6-11 one. The final value fee posted at
+/// midnight on the 6th comes back in both windows.
+fn account(request: &RecordedRequest) -> MockResponse {
+    let begin: DateTime<Utc> = tag(&request.body, "BeginDate").unwrap().parse().unwrap();
+    let page = tag(&request.body, "PageNumber").unwrap();
+    let boundary = account_entry("FeeFinalValue", march(6), ">1.50", "R2");
+    match (begin == march(1), page) {
+        (true, "1") => account_page(
+            &[account_entry(
+                "FeeInsertion",
+                march(2),
+                r#" currencyID="USD">0.35"#,
+                "R1",
+            )],
+            1,
+            2,
+        ),
+        (true, _) => account_page(
+            &[
+                account_entry("Payment", march(4), ">-20.00", "P1"),
+                boundary,
+            ],
+            2,
+            2,
+        ),
+        (false, _) => account_page(
+            &[
+                boundary,
+                account_entry("CreditFinalValue", march(8), ">1.50", "R3"),
+            ],
+            1,
+            1,
+        ),
+    }
+}
+
+fn client(server: &MockServer) -> TradingClient {
+    TradingClient::new(
+        Environment::Production,
+        "app-id",
+        3,
+        Arc::new(StaticToken("user")),
+    )
+    .with_endpoint(server.url("/ws/api.dll"))
+}
+
+#[tokio::test]
+async fn fetcher_pages_each_window_and_drops_payments_and_repeats() {
+    let server = MockServer::start(account).await;
+    let client = client(&server);
+
+    let ledger = FeeFetcher::new(&client)
+        .with_window(TimeDelta::days(5))
+        .with_entries_per_page(2)
+        .with_page_delay(Duration::ZERO)
+        .fetch(march(1), march(11))
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    let windows: Vec<_> = requests
+        .iter()
+        .map(|request| {
+            let body = &request.body;
+            (
+                tag(body, "BeginDate")
+                    .unwrap()
+                    .parse::<DateTime<Utc>>()
+                    .unwrap(),
+                tag(body, "EndDate")
+                    .unwrap()
+                    .parse::<DateTime<Utc>>()
+                    .unwrap(),
+                tag(body, "PageNumber").unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        windows,
+        [
+            (march(1), march(6), "1"),
+            (march(1), march(6), "2"),
+            (march(6), march(11), "1"),
+        ]
+    );
+    assert!(requests
+        .iter()
+        .all(|request| tag(&request.body, "EntriesPerPage") == Some("2")));
+
+    let entries: Vec<(&str, f64, &str)> = ledger
+        .entries
+        .iter()
+        .map(|e| (e.entry_type.as_str(), e.amount, e.currency.as_str()))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("FeeInsertion", 0.35, "USD"),
+            // No currencyID: the account's currency applies.
+            ("FeeFinalValue", 1.5, "GBP"),
+            ("CreditFinalValue", -1.5, "GBP"),
+        ]
+    );
+}
@@ -0,0 +1,133 @@
+use std::fs::File;
+
+use arrow_array::{Array, StringArray};
+use calamine::{open_workbook, Data, Reader, Xlsx};
+use ebay_connect::internal::ebay::listing_rows::{
+    listing_specifics, process_item, specific_column, Cell,
+};
+use ebay_connect::internal::ebay::parquet_export::export_listings_parquet;
+use ebay_connect::internal::ebay::trading::types::{ItemSpecifics, NameValueList};
+use ebay_connect::internal::ebay::trading::Item;
+use ebay_connect::internal::ebay::warehouse::Warehouse;
+use ebay_connect::internal::ebay::workbook::{columns_for, export_to_excel};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+fn item(item_id: &str, specifics: &[(&str, &[&str])]) -> Item {
+    Item {
+        item_id: item_id.to_string(),
+        title: Some(format!("Listing {item_id}")),
+        item_specifics: Some(ItemSpecifics {
+            name_value_list: specifics
+                .iter()
+                .map(|(name, values)| NameValueList {
+                    name: name.to_string(),
+                    values: values.iter().map(|v| v.to_string()).collect(),
+                })
+                .collect(),
+        }),
+        ..Item::default()
+    }
+}
+
+fn export_dir() -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-specifics", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn multi_valued_specifics_are_joined_with_commas() {
+    let row = process_item(
+        &item(
+            "1001",
+            &[
+                ("Color", &["White", "Ivory"]),
+                ("Features", &["Hypoallergenic", " ", "Breathable"]),
+                ("features", &["Breathable", "Washable"]),
+            ],
+        ),
+        None,
+    );
+    assert_eq!(row.get("Color"), &Cell::Text("White, Ivory".to_string()));
+    assert_eq!(
+        row.get(&specific_column("Color")),
+        &Cell::Text("White, Ivory".to_string())
+    );
+    // Names that differ only in case are one specific, values deduplicated.
+    assert_eq!(
+        row.get(&specific_column("Features")),
+        &Cell::Text("Hypoallergenic, Breathable, Washable".to_string())
+    );
+    assert!(row.get(&specific_column("features")).is_empty());
+}
+
+#[test]
+fn specifics_spelled_differently_across_listings_share_a_column() {
+    let rows = [
+        process_item(&item("1001", &[("Thread Count", &["400"])]), None),
+        process_item(&item("1002", &[("thread count", &["600"])]), None),
+        process_item(&item("1003", &[("Pattern", &["Solid"])]), None),
+    ];
+
+    let columns = columns_for(&rows);
+    let specifics: Vec<&str> = columns
+        .iter()
+        .map(|c| c.name.as_str())
+        .filter(|name| name.starts_with("Specific: "))
+        .collect();
+    assert_eq!(specifics, ["Specific: Thread Count", "Specific: Pattern"]);
+
+    let path = export_dir().join("specifics.xlsx");
+    export_to_excel(&rows, &path).unwrap();
+    let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+    let sheet = workbook.worksheet_range("All Listings").unwrap();
+    let sheet: Vec<&[Data]> = sheet.rows().collect();
+    let column = sheet[0]
+        .iter()
+        .position(|cell| *cell == Data::String(specific_column("Thread Count")))
+        .unwrap();
+    assert_eq!(sheet[1][column], Data::String("400".to_string()));
+    assert_eq!(sheet[2][column], Data::String("600".to_string()));
+}
+
+#[test]
+fn parquet_export_carries_the_specifics() {
+    let rows = vec![
+        process_item(&item("1001", &[("Thread Count", &["400"])]), None),
+        process_item(&item("1002", &[("THREAD COUNT", &["600"])]), None),
+    ];
+    let path = export_dir().join("specifics.parquet");
+    export_listings_parquet(rows, &path).unwrap();
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let batch = reader.into_iter().next().unwrap().unwrap();
+    let threads = batch
+        .column_by_name("Specific: Thread Count")
+        .expect("specific column")
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(threads.len(), 2);
+    assert_eq!(threads.value(0), "400");
+    assert_eq!(threads.value(1), "600");
+    assert!(batch.column_by_name("Specific: THREAD COUNT").is_none());
+}
+
+#[test]
+fn specific_usage_counts_names_ignoring_case() {
+    let warehouse = Warehouse::open_in_memory().unwrap();
+    let items = [
+        item("1001", &[("Thread Count", &["400"])]),
+        item("1002", &[("thread count", &["600"])]),
+    ];
+    warehouse
+        .record_specifics("main-store", &listing_specifics(&items))
+        .unwrap();
+
+    let usage = warehouse.specific_usage("main-store").unwrap();
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].listings, 2);
+}
@@ -0,0 +1,102 @@
+use ebay_connect::internal::ebay::listing_diff::{diff, DiffOptions, Snapshot};
+use ebay_connect::internal::ebay::listing_rows::{Cell, ListingRow};
+use ebay_connect::internal::ebay::warehouse::{RowSource, SyncKind, Warehouse};
+use ebay_connect::Error;
+
+const ACCOUNT: &str = "main-store";
+
+fn row(item_id: &str, price: f64) -> ListingRow {
+    let mut row = ListingRow::default();
+    row.set("Item ID", Cell::Text(item_id.to_string()));
+    row.set("Title", Cell::Text(format!("Listing {item_id}")));
+    row.set("Current Price", Cell::Number(price));
+    row
+}
+
+fn sync(warehouse: &Warehouse, kind: SyncKind, rows: &[ListingRow]) -> i64 {
+    let sync = warehouse.begin_sync(ACCOUNT, kind, None, None).unwrap();
+    warehouse
+        .record(sync.id, RowSource::SellingLists, rows)
+        .unwrap();
+    warehouse.finish_sync(sync.id).unwrap();
+    sync.id
+}
+
+fn item_ids(rows: &[ListingRow]) -> Vec<&str> {
+    rows.iter().map(ListingRow::item_id).collect()
+}
+
+#[test]
+fn listing_a_later_full_sync_no_longer_sees_is_removed() {
+    let warehouse = Warehouse::open_in_memory().unwrap();
+    let first = sync(
+        &warehouse,
+        SyncKind::Full,
+        &[row("1001", 10.0), row("1002", 20.0)],
+    );
+    let second = sync(&warehouse, SyncKind::Full, &[row("1001", 12.5)]);
+
+    let before = Snapshot::from_sync(&warehouse, ACCOUNT, first).unwrap();
+    let after = Snapshot::from_sync(&warehouse, ACCOUNT, second).unwrap();
+    assert_eq!(item_ids(&after.rows), ["1001"]);
+
+    let report = diff(&before, &after, &DiffOptions::default());
+    assert_eq!(report.summary.removed, 1);
+    assert_eq!(item_ids(&report.removed), ["1002"]);
+    assert_eq!(report.summary.changed, 1);
+    assert_eq!(report.changed[0].item_id, "1001");
+    assert_eq!(report.summary.added, 0);
+}
+
+#[test]
+fn incremental_syncs_add_to_the_last_full_sync() {
+    let warehouse = Warehouse::open_in_memory().unwrap();
+    sync(
+        &warehouse,
+        SyncKind::Full,
+        &[row("1001", 10.0), row("1002", 20.0)],
+    );
+    let full = sync(&warehouse, SyncKind::Full, &[row("1001", 10.0)]);
+    let incremental = sync(&warehouse, SyncKind::Incremental, &[row("1003", 5.0)]);
+
+    let rows = warehouse.snapshot_at(ACCOUNT, incremental).unwrap();
+    assert_eq!(item_ids(&rows), ["1001", "1003"]);
+
+    let report = diff(
+        &Snapshot::from_sync(&warehouse, ACCOUNT, full).unwrap(),
+        &Snapshot::from_sync(&warehouse, ACCOUNT, incremental).unwrap(),
+        &DiffOptions::default(),
+    );
+    assert_eq!(item_ids(&report.added), ["1003"]);
+    assert!(report.removed.is_empty());
+}
+
+#[test]
+fn failed_full_sync_does_not_drop_listings() {
+    let warehouse = Warehouse::open_in_memory().unwrap();
+    let complete = sync(
+        &warehouse,
+        SyncKind::Full,
+        &[row("1001", 10.0), row("1002", 20.0)],
+    );
+    let failed = warehouse
+        .begin_sync(ACCOUNT, SyncKind::Full, None, None)
+        .unwrap();
+    warehouse
+        .record(failed.id, RowSource::SellingLists, &[row("1001", 11.0)])
+        .unwrap();
+    warehouse.fail_sync(failed.id, "connection reset").unwrap();
+    let incremental = sync(&warehouse, SyncKind::Incremental, &[]);
+
+    let rows = warehouse.snapshot_at(ACCOUNT, incremental).unwrap();
+    assert_eq!(item_ids(&rows), ["1001", "1002"]);
+    assert_eq!(rows[0].get("Current Price"), &Cell::Number(11.0));
+    assert_eq!(warehouse.snapshot_at(ACCOUNT, complete).unwrap().len(), 2);
+}
+
+#[test]
+fn unknown_sync_is_not_found() {
+    let warehouse = Warehouse::open_in_memory().unwrap();
+    let err = Snapshot::from_sync(&warehouse, ACCOUNT, 42).unwrap_err();
+    assert!(matches!(err, Error::NotFound(_)), "{err}");
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use ebay_connect::internal::ebay::listing_history::{
+    HistoryCrawlConfig, ListingHistoryCrawler, MAX_WINDOW_DAYS,
+};
+use ebay_connect::internal::ebay::trading::TradingClient;
+use ebay_connect::Environment;
+
+use common::{MockResponse, MockServer, StaticToken};
+
+fn seller_list(item_ids: &[&str], has_more: bool) -> MockResponse {
+    let items: String = item_ids
+        .iter()
+        .map(|id| format!("<Item><ItemID>{id}</ItemID><Title>Listing {id}</Title></Item>"))
+        .collect();
+    MockResponse::xml(
+        200,
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><GetSellerListResponse xmlns="urn:ebay:apis:eBLBaseComponents"><Ack>Success</Ack><HasMoreItems>{has_more}</HasMoreItems><ItemArray>{items}</ItemArray></GetSellerListResponse>"#
+        ),
+    )
+}
+
+fn client(server: &MockServer) -> TradingClient {
+    TradingClient::new(
+        Environment::Production,
+        "app-id",
+        0,
+        Arc::new(StaticToken("user")),
+    )
+    .with_endpoint(server.url("/ws/api.dll"))
+}
+
+fn config(window_days: i64, floor: Option<DateTime<Utc>>) -> HistoryCrawlConfig {
+    HistoryCrawlConfig {
+        window: TimeDelta::days(window_days),
+        floor,
+        max_empty_windows: 2,
+        max_windows: 3,
+        page_delay: Duration::ZERO,
+        window_delay: Duration::ZERO,
+        ..HistoryCrawlConfig::default()
+    }
+}
+
+fn checkpoint(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-history", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+fn item_ids(items: &[ebay_connect::internal::ebay::trading::Item]) -> Vec<&str> {
+    items.iter().map(|item| item.item_id.as_str()).collect()
+}
+
+fn tag<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let start = body.find(&format!("<{name}>"))? + name.len() + 2;
+    let end = body[start..].find(&format!("</{name}>"))? + start;
+    Some(&body[start..end])
+}
+
+#[tokio::test]
+async fn windows_are_contiguous_and_stop_at_the_floor() {
+    let server = MockServer::start(|_| MockResponse::xml(500, "")).await;
+    let client = client(&server);
+    let anchor = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+    let floor = Utc.with_ymd_and_hms(2023, 9, 1, 0, 0, 0).unwrap();
+
+    let crawler = ListingHistoryCrawler::new(&client).with_config(HistoryCrawlConfig {
+        max_windows: 50,
+        ..config(120, None)
+    });
+    let windows = crawler.windows(anchor, Some(floor));
+    assert_eq!(windows.len(), 3);
+    assert_eq!(windows[0].end, anchor);
+    for pair in windows.windows(2) {
+        assert_eq!(pair[1].end, pair[0].start);
+    }
+    assert_eq!(windows[2].start, floor);
+
+    // Longer windows are cut below eBay's 121-day limit.
+    let crawler = ListingHistoryCrawler::new(&client).with_config(config(365, None));
+    let windows = crawler.windows(anchor, None);
+    assert_eq!(windows.len(), 3);
+    for window in &windows {
+        assert!(window.end - window.start < TimeDelta::days(MAX_WINDOW_DAYS));
+    }
+}
+
+#[tokio::test]
+async fn each_window_is_paged_until_no_more_items() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server = MockServer::start({
+        let calls = calls.clone();
+        // Only the first window has a second page.
+        move |_| match calls.fetch_add(1, Ordering::SeqCst) {
+            0 => seller_list(&["1001", "1002"], true),
+            1 => seller_list(&["1002", "1003"], false),
+            _ => seller_list(&["1004"], false),
+        }
+    })
+    .await;
+    let client = client(&server);
+    let now = Utc::now();
+    let floor = now - TimeDelta::days(45);
+    let path = checkpoint("paging.json");
+
+    let items = ListingHistoryCrawler::new(&client)
+        .with_config(config(30, Some(floor)))
+        .with_checkpoint(&path)
+        .crawl()
+        .await
+        .unwrap();
+
+    assert_eq!(item_ids(&items), ["1001", "1002", "1003", "1004"]);
+    let pages: Vec<_> = server
+        .requests()
+        .iter()
+        .map(|r| tag(&r.body, "PageNumber").unwrap_or_default().to_string())
+        .collect();
+    assert_eq!(pages, ["1", "2", "1"]);
+    // The last window starts at the floor, not 30 days before its end.
+    let last = server.requests().pop().unwrap();
+    assert_eq!(
+        tag(&last.body, "StartTimeFrom").unwrap()[..10],
+        floor.format("%Y-%m-%d").to_string()
+    );
+    assert!(!path.exists(), "checkpoint left behind");
+}
+
+#[tokio::test]
+async fn resumed_window_with_only_saved_items_is_not_empty() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server = MockServer::start({
+        let calls = calls.clone();
+        move |_| match calls.fetch_add(1, Ordering::SeqCst) {
+            // The page saved before the crash, fetched again.
+            0 => seller_list(&["1001", "1002"], false),
+            1 => seller_list(&[], false),
+            _ => seller_list(&["1003"], false),
+        }
+    })
+    .await;
+    let client = client(&server);
+
+    // Cut off after the page's items were written but before the position
+    // was, with one empty window already behind it.
+    let path = checkpoint("resume.json");
+    let state = serde_json::json!({
+        "version": 1,
+        "anchor": Utc::now(),
+        "floor": null,
+        "window": 0,
+        "next_page": 1,
+        "empty_windows": 1,
+        "window_items": 0
+    });
+    std::fs::write(&path, serde_json::to_vec(&state).unwrap()).unwrap();
+    let saved: String = ["1001", "1002"]
+        .iter()
+        .map(|id| format!("{{\"ItemID\":\"{id}\"}}\n"))
+        .collect();
+    std::fs::write(path.with_file_name("resume.json.items.jsonl"), saved).unwrap();
+
+    let items = ListingHistoryCrawler::new(&client)
+        .with_config(config(30, None))
+        .with_checkpoint(&path)
+        .crawl()
+        .await
+        .unwrap();
+
+    assert_eq!(item_ids(&items), ["1001", "1002", "1003"]);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert!(!path.exists());
+}
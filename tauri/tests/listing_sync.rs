@@ -0,0 +1,296 @@
+mod common;
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use ebay_connect::internal::ebay::listing_history::HistoryCrawlConfig;
+use ebay_connect::internal::ebay::listing_rows::{Cell, LISTING_STATUS_CATEGORY};
+use ebay_connect::internal::ebay::listing_sync::{ListingSync, SyncConfig, MAX_EVENTS_PER_CALL};
+use ebay_connect::internal::ebay::trading::TradingClient;
+use ebay_connect::internal::ebay::warehouse::{SyncKind, SyncStatus, Warehouse};
+use ebay_connect::Environment;
+use rusqlite::Connection;
+
+use common::{MockResponse, MockServer, RecordedRequest, StaticToken};
+
+const ACCOUNT: &str = "main-store";
+
+fn client(server: &MockServer) -> TradingClient {
+    TradingClient::new(
+        Environment::Production,
+        "app-id",
+        0,
+        Arc::new(StaticToken("user")),
+    )
+    .with_endpoint(server.url("/ws/api.dll"))
+}
+
+fn config() -> SyncConfig {
+    SyncConfig {
+        overlap: TimeDelta::zero(),
+        window_delay: Duration::ZERO,
+        ..SyncConfig::default()
+    }
+}
+
+fn database(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-sync", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+    path
+}
+
+fn call_name(request: &RecordedRequest) -> &str {
+    request.header("X-EBAY-API-CALL-NAME").unwrap_or_default()
+}
+
+fn tag<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let start = body.find(&format!("<{name}>"))? + name.len() + 2;
+    let end = body[start..].find(&format!("</{name}>"))? + start;
+    Some(&body[start..end])
+}
+
+fn time_tag(body: &str, name: &str) -> DateTime<Utc> {
+    tag(body, name).unwrap().parse().unwrap()
+}
+
+fn items(item_ids: &[&str], title: &str) -> String {
+    item_ids
+        .iter()
+        .map(|id| format!("<Item><ItemID>{id}</ItemID><Title>{title}</Title></Item>"))
+        .collect()
+}
+
+fn response(call: &str, body: &str) -> MockResponse {
+    acked(call, "Success", body)
+}
+
+fn acked(call: &str, ack: &str, body: &str) -> MockResponse {
+    MockResponse::xml(
+        200,
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><{call}Response xmlns="urn:ebay:apis:eBLBaseComponents"><Ack>{ack}</Ack>{body}</{call}Response>"#
+        ),
+    )
+}
+
+const ONE_PAGE: &str =
+    "<PaginationResult><TotalNumberOfPages>1</TotalNumberOfPages></PaginationResult>";
+
+/// `GetMyeBaySelling` with item 1001 both active and sold, and 1002 unsold.
+fn my_ebay(request: &RecordedRequest) -> MockResponse {
+    let body = &request.body;
+    let list = if body.contains("<ActiveList>") {
+        format!(
+            "<ActiveList><ItemArray>{}</ItemArray>{ONE_PAGE}</ActiveList>",
+            items(&["1001"], "Active copy")
+        )
+    } else if body.contains("<SoldList>") {
+        format!(
+            "<SoldList><OrderTransactionArray><OrderTransaction><Transaction>{}</Transaction></OrderTransaction></OrderTransactionArray>{ONE_PAGE}</SoldList>",
+            items(&["1001"], "Sold copy")
+        )
+    } else if body.contains("<UnsoldList>") {
+        format!(
+            "<UnsoldList><ItemArray>{}</ItemArray>{ONE_PAGE}</UnsoldList>",
+            items(&["1002"], "Unsold copy")
+        )
+    } else {
+        String::new()
+    };
+    response("GetMyeBaySelling", &list)
+}
+
+fn seller_events(item_ids: &[&str]) -> MockResponse {
+    response(
+        "GetSellerEvents",
+        &format!("<ItemArray>{}</ItemArray>", items(item_ids, "Changed")),
+    )
+}
+
+fn calls(server: &MockServer, name: &str) -> Vec<RecordedRequest> {
+    server
+        .requests()
+        .into_iter()
+        .filter(|request| call_name(request) == name)
+        .collect()
+}
+
+#[tokio::test]
+async fn sync_is_full_until_a_recent_complete_sync_exists() {
+    let server = MockServer::start(|request| match call_name(request) {
+        "GetMyeBaySelling" => my_ebay(request),
+        _ => seller_events(&["1002"]),
+    })
+    .await;
+    let client = client(&server);
+    let path = database("kind.db");
+    let warehouse = Warehouse::open(&path).unwrap();
+    let sync = ListingSync::new(&client, &warehouse, ACCOUNT).with_config(config());
+
+    let first = sync.sync().await.unwrap();
+    assert_eq!(first.kind, SyncKind::Full);
+    assert_eq!(first.status, SyncStatus::Complete);
+    assert!(calls(&server, "GetSellerEvents").is_empty());
+
+    let second = sync.sync().await.unwrap();
+    assert_eq!(second.kind, SyncKind::Incremental);
+    assert_eq!(second.mod_time_from, first.mod_time_to);
+    let events = calls(&server, "GetSellerEvents");
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        Some(time_tag(&events[0].body, "ModTimeFrom")),
+        first.mod_time_to
+    );
+
+    // A last sync older than `max_incremental_age` is not caught up on.
+    let db = Connection::open(&path).unwrap();
+    let stale = (Utc::now() - TimeDelta::days(31)).timestamp();
+    db.execute("UPDATE syncs SET mod_time_to = ?1", [stale])
+        .unwrap();
+    let third = sync.sync().await.unwrap();
+    assert_eq!(third.kind, SyncKind::Full);
+    assert_eq!(calls(&server, "GetSellerEvents").len(), 1);
+}
+
+#[tokio::test]
+async fn full_windows_are_split_in_two() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let server = MockServer::start({
+        let count = count.clone();
+        move |_| match count.fetch_add(1, Ordering::SeqCst) {
+            0 => {
+                let ids: Vec<String> = (0..MAX_EVENTS_PER_CALL)
+                    .map(|n| (500_000 + n).to_string())
+                    .collect();
+                let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+                seller_events(&ids)
+            }
+            1 => seller_events(&["1001"]),
+            _ => seller_events(&["1002"]),
+        }
+    })
+    .await;
+    let client = client(&server);
+    let warehouse = Warehouse::open_in_memory().unwrap();
+    let since = Utc::now() - TimeDelta::hours(4);
+
+    let record = ListingSync::new(&client, &warehouse, ACCOUNT)
+        .with_config(config())
+        .incremental(since)
+        .await
+        .unwrap();
+
+    let windows: Vec<_> = server
+        .requests()
+        .iter()
+        .map(|request| {
+            (
+                time_tag(&request.body, "ModTimeFrom"),
+                time_tag(&request.body, "ModTimeTo"),
+            )
+        })
+        .collect();
+    assert_eq!(windows.len(), 3);
+    let (from, to) = windows[0];
+    assert_eq!(windows[1].0, from);
+    assert_eq!(windows[1].1, windows[2].0);
+    assert_eq!(windows[2].1, to);
+    assert_eq!(windows[1].1, from + (to - from) / 2);
+
+    // The cut-short window is not stored; its halves are.
+    assert_eq!(record.item_count, 2);
+    let stored: Vec<String> = warehouse
+        .listings(ACCOUNT)
+        .unwrap()
+        .iter()
+        .map(|row| row.item_id().to_string())
+        .collect();
+    assert_eq!(stored, ["1001", "1002"]);
+}
+
+#[tokio::test]
+async fn full_sync_keeps_the_first_list_an_item_is_in() {
+    let server = MockServer::start(|request| match call_name(request) {
+        "GetMyeBaySelling" => my_ebay(request),
+        // The listing history has 1002 again and 1003 that no list has.
+        _ => response(
+            "GetSellerList",
+            &format!(
+                "<HasMoreItems>false</HasMoreItems><ItemArray>{}</ItemArray>",
+                items(&["1002", "1003"], "History copy")
+            ),
+        ),
+    })
+    .await;
+    let client = client(&server);
+    let warehouse = Warehouse::open_in_memory().unwrap();
+
+    let record = ListingSync::new(&client, &warehouse, ACCOUNT)
+        .with_config(SyncConfig {
+            include_history: true,
+            history: HistoryCrawlConfig {
+                max_windows: 1,
+                page_delay: Duration::ZERO,
+                window_delay: Duration::ZERO,
+                ..HistoryCrawlConfig::default()
+            },
+            ..config()
+        })
+        .full()
+        .await
+        .unwrap();
+    assert_eq!(record.item_count, 3);
+
+    let stored = |item_id: &str| warehouse.listing(ACCOUNT, item_id).unwrap().unwrap().row;
+    let active = stored("1001");
+    assert_eq!(active.get("Title"), &Cell::Text("Active copy".to_string()));
+    assert_eq!(
+        active.get(LISTING_STATUS_CATEGORY),
+        &Cell::Text("Active".to_string())
+    );
+    assert_eq!(
+        stored("1002").get("Title"),
+        &Cell::Text("Unsold copy".to_string())
+    );
+    let history = stored("1003");
+    assert_eq!(
+        history.get("Title"),
+        &Cell::Text("History copy".to_string())
+    );
+    assert!(history.get(LISTING_STATUS_CATEGORY).is_empty());
+}
+
+#[tokio::test]
+async fn errors_fail_the_sync() {
+    let server = MockServer::start(|_| {
+        acked(
+            "GetMyeBaySelling",
+            "Failure",
+            "<Errors><ShortMessage>Internal error</ShortMessage><ErrorCode>10007</ErrorCode><SeverityCode>Error</SeverityCode></Errors>",
+        )
+    })
+    .await;
+    let client = client(&server);
+    let warehouse = Warehouse::open_in_memory().unwrap();
+
+    let sync = ListingSync::new(&client, &warehouse, ACCOUNT).with_config(config());
+    assert!(sync.sync().await.is_err());
+
+    let syncs = warehouse.syncs(ACCOUNT).unwrap();
+    assert_eq!(syncs.len(), 1);
+    assert_eq!(syncs[0].status, SyncStatus::Failed);
+    assert!(syncs[0].finished_at.is_some());
+    assert!(syncs[0]
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("Internal error"));
+    assert!(warehouse.last_complete_sync(ACCOUNT).unwrap().is_none());
+}
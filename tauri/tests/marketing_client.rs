@@ -0,0 +1,299 @@
+mod common;
+
+use std::sync::Arc;
+
+use ebay_connect::internal::ebay::marketing::{
+    ItemPriceMarkdown, ItemPromotion, MarketingClient, Promotion, PromotionFilter, PromotionStatus,
+    PromotionType,
+};
+use ebay_connect::{Environment, Error};
+
+use common::{MockResponse, MockServer, RecordedRequest, StaticToken};
+
+const PROMOTIONS: &str = "/sell/marketing/v1/promotion";
+
+fn client(server: &MockServer) -> MarketingClient {
+    MarketingClient::new(
+        Environment::Production,
+        "EBAY_US",
+        Arc::new(StaticToken("user")),
+    )
+    .with_base_url(server.url("/"))
+}
+
+fn order_discount() -> ItemPromotion {
+    serde_json::from_str(
+        r#"{
+            "promotionId": "P1",
+            "name": "Spring",
+            "marketplaceId": "EBAY_US",
+            "promotionType": "ORDER_DISCOUNT",
+            "promotionStatus": "SCHEDULED",
+            "startDate": "2026-03-01T00:00:00Z",
+            "endDate": "2026-03-31T00:00:00Z",
+            "discountRules": [{ "discountBenefit": { "percentageOffOrder": "10" } }],
+            "inventoryCriterion": {
+                "inventoryCriterionType": "INVENTORY_BY_VALUE",
+                "listingIds": ["1001"]
+            }
+        }"#,
+    )
+    .unwrap()
+}
+
+fn markdown() -> ItemPriceMarkdown {
+    serde_json::from_str(
+        r#"{
+            "promotionId": "M1",
+            "name": "Clearance",
+            "marketplaceId": "EBAY_US",
+            "promotionStatus": "RUNNING",
+            "startDate": "2026-03-01T00:00:00Z",
+            "endDate": "2026-03-31T00:00:00Z",
+            "selectedInventoryDiscounts": [{
+                "discountBenefit": { "percentageOffItem": "20" },
+                "inventoryCriterion": {
+                    "inventoryCriterionType": "INVENTORY_BY_VALUE",
+                    "listingIds": ["1002"]
+                }
+            }]
+        }"#,
+    )
+    .unwrap()
+}
+
+fn summary(id: &str) -> String {
+    format!(
+        r#"{{"promotionId":"{id}","name":"Promotion {id}","marketplaceId":"EBAY_US","promotionType":"ORDER_DISCOUNT","promotionStatus":"RUNNING"}}"#
+    )
+}
+
+fn query_value(request: &RecordedRequest, key: &str) -> Option<String> {
+    let query = request.target.split_once('?')?.1;
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.into_owned())
+}
+
+fn offset(request: &RecordedRequest) -> u32 {
+    query_value(request, "offset")
+        .and_then(|o| o.parse().ok())
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn promotions_follow_next_until_an_empty_page() {
+    let server = MockServer::start(|request| {
+        let body = match offset(request) {
+            0 => format!(
+                r#"{{"offset":0,"next":"{PROMOTIONS}?marketplace_id=EBAY_US&limit=200&offset=1","promotions":[{}]}}"#,
+                summary("P1")
+            ),
+            1 => format!(
+                r#"{{"offset":1,"next":"{PROMOTIONS}?marketplace_id=EBAY_US&limit=200&offset=2","promotions":[{}]}}"#,
+                summary("P2")
+            ),
+            // eBay still links onwards, but there is nothing left.
+            _ => format!(
+                r#"{{"offset":2,"next":"{PROMOTIONS}?marketplace_id=EBAY_US&limit=200&offset=3","promotions":[]}}"#
+            ),
+        };
+        MockResponse::json(200, body)
+    })
+    .await;
+    let filter = PromotionFilter {
+        status: Some(PromotionStatus::Running),
+        ..PromotionFilter::default()
+    };
+
+    let promotions = client(&server).promotions(&filter).await.unwrap();
+
+    let ids: Vec<&str> = promotions.iter().map(|p| p.promotion_id.as_str()).collect();
+    assert_eq!(ids, ["P1", "P2"]);
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    let first = &requests[0];
+    assert_eq!(first.path(), PROMOTIONS);
+    assert_eq!(
+        query_value(first, "marketplace_id").as_deref(),
+        Some("EBAY_US")
+    );
+    assert_eq!(query_value(first, "limit").as_deref(), Some("200"));
+    assert_eq!(
+        query_value(first, "promotion_status").as_deref(),
+        Some("RUNNING")
+    );
+    assert_eq!(first.header("X-EBAY-C-MARKETPLACE-ID"), Some("EBAY_US"));
+    assert_eq!(first.header("Authorization"), Some("Bearer user"));
+}
+
+#[tokio::test]
+async fn paging_stops_on_a_link_back_to_the_same_page() {
+    let server = MockServer::start(|request| {
+        // `next` repeats the request's own URL.
+        MockResponse::json(
+            200,
+            format!(
+                r#"{{"next":"{}","promotionReports":[{{"promotionId":"P1"}}]}}"#,
+                request.target
+            ),
+        )
+    })
+    .await;
+
+    let reports = client(&server)
+        .promotion_reports(&PromotionFilter::default())
+        .await
+        .unwrap();
+
+    assert_eq!(reports.len(), 1);
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(
+        server.requests()[0].path(),
+        "/sell/marketing/v1/promotion_report"
+    );
+}
+
+#[tokio::test]
+async fn create_returns_the_id_from_location() {
+    let server = MockServer::start(|request| match request.path() {
+        "/sell/marketing/v1/item_promotion" => MockResponse::json(201, "").with_header(
+            "Location",
+            "https://api.ebay.com/sell/marketing/v1/item_promotion/5551212",
+        ),
+        // No Location: fall back to the body.
+        "/sell/marketing/v1/item_price_markdown" => MockResponse::json(
+            201,
+            r#"{"href":"https://api.ebay.com/sell/marketing/v1/item_price_markdown/7770000/"}"#,
+        ),
+        _ => MockResponse::json(404, "{}"),
+    })
+    .await;
+    let client = client(&server);
+
+    let id = client.create(&order_discount().into()).await.unwrap();
+    assert_eq!(id, "5551212");
+    let id = client.create(&markdown().into()).await.unwrap();
+    assert_eq!(id, "7770000");
+
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "POST");
+    let sent: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+    assert!(sent.get("promotionId").is_none(), "read-only field sent");
+    assert_eq!(sent["promotionType"], "ORDER_DISCOUNT");
+}
+
+#[tokio::test]
+async fn create_without_an_id_is_an_error() {
+    let server = MockServer::start(|_| MockResponse::json(201, "")).await;
+
+    let err = client(&server)
+        .create(&order_discount().into())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidResponse(_)), "{err}");
+}
+
+#[tokio::test]
+async fn each_promotion_type_uses_its_resource() {
+    let item = serde_json::to_string(&order_discount()).unwrap();
+    let price = serde_json::to_string(&markdown()).unwrap();
+    let server = MockServer::start(move |request| match request.method.as_str() {
+        "GET" if request.path().contains("item_price_markdown") => {
+            MockResponse::json(200, price.clone())
+        }
+        "GET" => MockResponse::json(200, item.clone()),
+        _ => MockResponse::json(204, ""),
+    })
+    .await;
+    let client = client(&server);
+
+    for kind in PromotionType::ALL {
+        let promotion = client.get("42", kind).await.unwrap();
+        assert_eq!(
+            matches!(promotion, Promotion::Markdown(_)),
+            kind == PromotionType::MarkdownSale
+        );
+        client.update("42", &promotion).await.unwrap();
+        client.delete("42", kind).await.unwrap();
+    }
+
+    let calls: Vec<String> = server
+        .requests()
+        .iter()
+        .map(|r| format!("{} {}", r.method, r.path()))
+        .collect();
+    let mut expected = Vec::new();
+    for kind in PromotionType::ALL {
+        let resource = match kind {
+            PromotionType::MarkdownSale => "item_price_markdown",
+            _ => "item_promotion",
+        };
+        for method in ["GET", "PUT", "DELETE"] {
+            expected.push(format!("{method} /sell/marketing/v1/{resource}/42"));
+        }
+    }
+    assert_eq!(calls, expected);
+}
+
+#[tokio::test]
+async fn markdowns_cannot_be_paused() {
+    let server = MockServer::start(|_| MockResponse::json(204, "")).await;
+    let client = client(&server);
+
+    client
+        .pause("P1", PromotionType::OrderDiscount)
+        .await
+        .unwrap();
+    client
+        .resume("P1", PromotionType::VolumeDiscount)
+        .await
+        .unwrap();
+    let err = client
+        .pause("M1", PromotionType::MarkdownSale)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Promotion(_)), "{err}");
+    assert!(client
+        .resume("M1", PromotionType::MarkdownSale)
+        .await
+        .is_err());
+
+    let calls: Vec<String> = server
+        .requests()
+        .iter()
+        .map(|r| format!("{} {}", r.method, r.path()))
+        .collect();
+    assert_eq!(
+        calls,
+        [
+            "POST /sell/marketing/v1/promotion/P1/pause",
+            "POST /sell/marketing/v1/promotion/P1/resume"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn api_errors_carry_ebay_messages() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(
+            400,
+            r#"{"errors":[{"errorId":38212,"message":"The start date is in the past."},{"errorId":38213,"message":"The name is too long."}]}"#,
+        )
+    })
+    .await;
+
+    let err = client(&server).item_promotion("P1").await.unwrap_err();
+    match err {
+        Error::Api {
+            status, message, ..
+        } => {
+            assert_eq!(status, 400);
+            assert_eq!(
+                message,
+                "The start date is in the past.; The name is too long."
+            );
+        }
+        other => panic!("expected an API error, got {other}"),
+    }
+}
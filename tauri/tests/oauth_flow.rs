@@ -0,0 +1,253 @@
+mod common;
+
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ebay_connect::adapters::ebay::{OAuthClient, OAuthConfig, RedirectListener};
+use ebay_connect::{Environment, Error};
+use url::Url;
+
+use common::{MockResponse, MockServer};
+
+const TOKEN_BODY: &str = r#"{
+    "access_token": "v^1.1#i^1#access",
+    "expires_in": 7200,
+    "refresh_token": "v^1.1#i^1#refresh",
+    "refresh_token_expires_in": 47304000,
+    "token_type": "User Access Token"
+}"#;
+
+fn config() -> OAuthConfig {
+    OAuthConfig::new(
+        "client-id",
+        "client-secret",
+        "My_RuName-abc",
+        Environment::Sandbox,
+    )
+}
+
+async fn token_server() -> MockServer {
+    MockServer::start(|request| {
+        if request.path() != "/identity/v1/oauth2/token" {
+            return MockResponse::json(404, "{}");
+        }
+        if request.form_value("code").as_deref() == Some("v^1.1#i^1#code") {
+            MockResponse::json(200, TOKEN_BODY)
+        } else {
+            MockResponse::json(
+                400,
+                r#"{"error":"invalid_grant","error_description":"the provided authorization grant code is invalid"}"#,
+            )
+        }
+    })
+    .await
+}
+
+/// Simulate the browser following eBay's redirect to the accept URL.
+fn follow_redirect(redirect: Url, consent: &Url, code: &str, state_override: Option<&str>) {
+    let state = consent
+        .query_pairs()
+        .find(|(k, _)| k == "state")
+        .map(|(_, v)| v.into_owned())
+        .unwrap();
+    let mut redirect = redirect;
+    redirect
+        .query_pairs_mut()
+        .append_pair("state", state_override.unwrap_or(&state))
+        .append_pair("code", code);
+
+    tokio::spawn(async move {
+        let favicon = redirect.join("/favicon.ico").unwrap();
+        let _ = reqwest::get(favicon).await;
+        let _ = reqwest::get(redirect).await;
+    });
+}
+
+#[test]
+fn consent_url_matches_node_script() {
+    let client = OAuthClient::new(config());
+    let url = client.consent_url("auth-state");
+
+    assert_eq!(url.host_str(), Some("auth.sandbox.ebay.com"));
+    assert_eq!(url.path(), "/oauth2/authorize");
+
+    let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("client_id".into(), "client-id".into()),
+            ("response_type".into(), "code".into()),
+            ("redirect_uri".into(), "My_RuName-abc".into()),
+            (
+                "scope".into(),
+                // The Node script's scopes, plus the Marketing ones promotions need.
+                "https://api.ebay.com/oauth/api_scope https://api.ebay.com/oauth/api_scope/sell.inventory.readonly https://api.ebay.com/oauth/api_scope/sell.inventory https://api.ebay.com/oauth/api_scope/sell.marketing.readonly https://api.ebay.com/oauth/api_scope/sell.marketing".into()
+            ),
+            ("state".into(), "auth-state".into()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn authorize_captures_code_and_exchanges_it() {
+    let server = token_server().await;
+    let client = OAuthClient::new(config()).with_token_url(server.url("/identity/v1/oauth2/token"));
+    let listener = RedirectListener::bind("127.0.0.1:0", "/callback")
+        .await
+        .unwrap();
+    let redirect = listener.redirect_url().unwrap();
+
+    let tokens = client
+        .authorize(
+            &listener,
+            |consent| follow_redirect(redirect.clone(), consent, "v^1.1#i^1#code", None),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(tokens.access_token, "v^1.1#i^1#access");
+    assert_eq!(tokens.refresh_token.as_deref(), Some("v^1.1#i^1#refresh"));
+    assert_eq!(tokens.expires_in, 7200);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(
+        request.header("authorization"),
+        Some(format!("Basic {}", STANDARD.encode("client-id:client-secret")).as_str())
+    );
+    assert_eq!(
+        request.form_value("grant_type").as_deref(),
+        Some("authorization_code")
+    );
+    assert_eq!(
+        request.form_value("redirect_uri").as_deref(),
+        Some("My_RuName-abc")
+    );
+}
+
+#[tokio::test]
+async fn authorize_rejects_mismatched_state() {
+    let server = token_server().await;
+    let client = OAuthClient::new(config()).with_token_url(server.url("/identity/v1/oauth2/token"));
+    let listener = RedirectListener::bind("127.0.0.1:0", "/callback")
+        .await
+        .unwrap();
+    let redirect = listener.redirect_url().unwrap();
+
+    let result = client
+        .authorize(
+            &listener,
+            |consent| follow_redirect(redirect.clone(), consent, "v^1.1#i^1#code", Some("forged")),
+            Duration::from_secs(5),
+        )
+        .await;
+
+    assert!(matches!(result, Err(Error::StateMismatch)));
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn authorize_reports_declined_consent() {
+    let client = OAuthClient::new(config());
+    let listener = RedirectListener::bind("127.0.0.1:0", "/callback")
+        .await
+        .unwrap();
+    let mut redirect = listener.redirect_url().unwrap();
+    redirect
+        .query_pairs_mut()
+        .append_pair("error", "access_denied");
+
+    let result = client
+        .authorize(
+            &listener,
+            move |_| {
+                tokio::spawn(reqwest::get(redirect));
+            },
+            Duration::from_secs(5),
+        )
+        .await;
+
+    assert!(matches!(result, Err(Error::AuthorizationDenied(msg)) if msg == "access_denied"));
+}
+
+#[tokio::test]
+async fn exchange_code_surfaces_invalid_grant() {
+    let server = token_server().await;
+    let client = OAuthClient::new(config()).with_token_url(server.url("/identity/v1/oauth2/token"));
+
+    let result = client.exchange_code("expired").await;
+
+    match result {
+        Err(Error::OAuth { status, error, .. }) => {
+            assert_eq!(status, 400);
+            assert_eq!(error, "invalid_grant");
+        }
+        other => panic!("expected invalid_grant, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn authorize_times_out_without_redirect() {
+    let client = OAuthClient::new(config());
+    let listener = RedirectListener::bind("127.0.0.1:0", "/callback")
+        .await
+        .unwrap();
+
+    let result = client
+        .authorize(&listener, |_| {}, Duration::from_millis(50))
+        .await;
+
+    assert!(matches!(result, Err(Error::Timeout(_))));
+}
+
+#[tokio::test]
+async fn unreadable_token_responses_do_not_echo_the_body() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(
+            200,
+            r#"{"access_token": "v^1.1#i^1#secret", "expires_in": "soon"}"#,
+        )
+    })
+    .await;
+    let client = OAuthClient::new(config()).with_token_url(server.url("/identity/v1/oauth2/token"));
+
+    let err = client.exchange_code("v^1.1#i^1#code").await.unwrap_err();
+    let message = err.to_string();
+    assert!(matches!(err, Error::InvalidResponse(_)), "{message}");
+    assert!(!message.contains("secret"), "{message}");
+    assert!(!message.contains("soon"), "{message}");
+}
+
+#[tokio::test]
+async fn idle_connections_do_not_block_the_redirect() {
+    let listener = RedirectListener::bind("127.0.0.1:0", "/callback")
+        .await
+        .unwrap()
+        .with_read_timeout(Duration::from_millis(100));
+    let redirect = listener.redirect_url().unwrap();
+
+    // A connection that never sends a request, ahead of the real redirect.
+    let idle = tokio::net::TcpStream::connect(listener.local_addr().unwrap())
+        .await
+        .unwrap();
+    tokio::spawn(async move {
+        let mut redirect = redirect;
+        redirect
+            .query_pairs_mut()
+            .append_pair("state", "auth-state")
+            .append_pair("code", "v^1.1#i^1#code");
+        let _ = reqwest::get(redirect).await;
+    });
+
+    let callback = tokio::time::timeout(Duration::from_secs(5), listener.wait_for_callback())
+        .await
+        .expect("the idle connection blocked the listener")
+        .unwrap();
+    assert_eq!(callback.code, "v^1.1#i^1#code");
+    assert_eq!(callback.state.as_deref(), Some("auth-state"));
+    drop(idle);
+}
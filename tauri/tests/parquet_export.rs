@@ -0,0 +1,78 @@
+use std::fs::File;
+
+use arrow_array::{
+    Array, BooleanArray, Decimal128Array, Int64Array, StringArray, TimestampMillisecondArray,
+};
+use ebay_connect::internal::ebay::listing_rows::{Cell, ListingRow, LISTING_COLUMNS};
+use ebay_connect::internal::ebay::parquet_export::export_listings_parquet;
+use ebay_connect::Error;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+fn row(item_id: &str) -> ListingRow {
+    let mut row = ListingRow::default();
+    row.set("Item ID", Cell::Text(item_id.to_string()));
+    row.set("Current Price", Cell::Text("19.99".to_string()));
+    row.set("Quantity", Cell::Number(3.0));
+    row.set("Best Offer Enabled", Cell::Bool(true));
+    row.set(
+        "Start Time",
+        Cell::Text("2024-03-05T12:00:00.000Z".to_string()),
+    );
+    row
+}
+
+fn export_path(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-parquet", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+#[test]
+fn listings_read_back_with_their_types() {
+    let path = export_path("listings.parquet");
+    let rows = vec![row("123456789012"), ListingRow::default()];
+    assert_eq!(
+        export_listings_parquet(rows, &path).unwrap().record_count,
+        2
+    );
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let batch = reader.into_iter().next().unwrap().unwrap();
+    assert_eq!(batch.num_columns(), LISTING_COLUMNS.len());
+
+    let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+    let ids = column("Item ID");
+    let ids = ids.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(ids.value(0), "123456789012");
+    assert!(ids.is_null(1));
+
+    let price = column("Current Price");
+    let price = price.as_any().downcast_ref::<Decimal128Array>().unwrap();
+    assert_eq!(price.value(0), 1999);
+
+    let quantity = column("Quantity");
+    let quantity = quantity.as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(quantity.value(0), 3);
+
+    let offers = column("Best Offer Enabled");
+    let offers = offers.as_any().downcast_ref::<BooleanArray>().unwrap();
+    assert!(offers.value(0));
+
+    let started = column("Start Time");
+    let started = started
+        .as_any()
+        .downcast_ref::<TimestampMillisecondArray>()
+        .unwrap();
+    assert_eq!(started.value(0), 1_709_640_000_000);
+}
+
+#[test]
+fn value_that_does_not_fit_its_column_is_an_error() {
+    let mut bad = row("1001");
+    bad.set("Quantity", Cell::Text("several".to_string()));
+    let err = export_listings_parquet(vec![bad], export_path("bad.parquet")).unwrap_err();
+    assert!(matches!(&err, Error::Parquet(message) if message.contains("item 1001")));
+}
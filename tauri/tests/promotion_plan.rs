@@ -0,0 +1,239 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{TimeZone, Utc};
+use ebay_connect::internal::ebay::marketing::audit::AuditAction;
+use ebay_connect::internal::ebay::marketing::plan::diff;
+use ebay_connect::internal::ebay::marketing::{
+    ItemPriceMarkdown, MarketingClient, Promotion, PromotionAudit, PromotionPlan, PromotionStatus,
+};
+use ebay_connect::internal::ebay::warehouse::Warehouse;
+use ebay_connect::{Environment, Error};
+use serde_json::{json, Value};
+
+use common::{MockResponse, MockServer, StaticToken};
+
+fn markdown_json(id: &str, end: &str) -> Value {
+    json!({
+        "promotionId": id,
+        "name": format!("Markdown {id}"),
+        "marketplaceId": "EBAY_US",
+        "promotionStatus": "RUNNING",
+        "startDate": "2026-03-01T00:00:00Z",
+        "endDate": end,
+        "selectedInventoryDiscounts": [{
+            "discountBenefit": { "percentageOffItem": "20" },
+            "inventoryCriterion": {
+                "inventoryCriterionType": "INVENTORY_BY_VALUE",
+                "listingIds": ["1001"]
+            }
+        }]
+    })
+}
+
+fn markdown(id: &str) -> Promotion {
+    serde_json::from_value::<ItemPriceMarkdown>(markdown_json(id, "2026-03-31T00:00:00Z"))
+        .unwrap()
+        .into()
+}
+
+fn extended(promotion: &Promotion) -> Promotion {
+    let mut updated = promotion.clone();
+    updated.set_end_date(Utc.with_ymd_and_hms(2026, 4, 7, 0, 0, 0).unwrap());
+    updated
+}
+
+/// Serves the promotions in `live`, which a test can change after the plan
+/// is made, and accepts every update.
+async fn marketing(live: Arc<Mutex<Vec<Value>>>) -> MockServer {
+    MockServer::start(move |request| {
+        if request.method == "PUT" {
+            return MockResponse::json(204, "");
+        }
+        let id = request.path().rsplit('/').next().unwrap_or_default();
+        let live = live.lock().unwrap();
+        match live.iter().find(|p| p["promotionId"] == id) {
+            Some(promotion) => MockResponse::json(200, promotion.to_string()),
+            None => MockResponse::json(404, "{}"),
+        }
+    })
+    .await
+}
+
+fn client(server: &MockServer, marketplace_id: &str) -> MarketingClient {
+    MarketingClient::new(
+        Environment::Production,
+        marketplace_id,
+        Arc::new(StaticToken("user")),
+    )
+    .with_base_url(server.url("/"))
+}
+
+fn audit() -> PromotionAudit {
+    PromotionAudit::new(Arc::new(Warehouse::open_in_memory().unwrap()), "main-store")
+}
+
+fn plan() -> PromotionPlan {
+    let mut plan = PromotionPlan::new("EBAY_US");
+    for id in ["M1", "M2"] {
+        let current = markdown(id);
+        assert!(plan
+            .add(
+                id,
+                &current,
+                &extended(&current),
+                Some("spring".to_string())
+            )
+            .unwrap());
+    }
+    plan
+}
+
+#[test]
+fn diff_paths_name_every_changed_leaf() {
+    let before = json!({
+        "name": "Spring",
+        "endDate": "2026-03-31T00:00:00Z",
+        "selectedInventoryDiscounts": [
+            { "discountBenefit": { "percentageOffItem": "20" }, "ruleOrder": 1 }
+        ]
+    });
+    let after = json!({
+        "name": "Spring",
+        "endDate": "2026-04-07T00:00:00Z",
+        "selectedInventoryDiscounts": [
+            { "discountBenefit": { "percentageOffItem": "25" } },
+            { "discountBenefit": { "percentageOffItem": "10" } }
+        ],
+        "priority": "PRIORITY_1"
+    });
+
+    let fields: Vec<(String, Option<Value>, Option<Value>)> = diff(&before, &after)
+        .into_iter()
+        .map(|change| (change.field, change.before, change.after))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            (
+                "endDate".to_string(),
+                Some(json!("2026-03-31T00:00:00Z")),
+                Some(json!("2026-04-07T00:00:00Z"))
+            ),
+            ("priority".to_string(), None, Some(json!("PRIORITY_1"))),
+            (
+                "selectedInventoryDiscounts[0].discountBenefit.percentageOffItem".to_string(),
+                Some(json!("20")),
+                Some(json!("25"))
+            ),
+            (
+                "selectedInventoryDiscounts[0].ruleOrder".to_string(),
+                Some(json!(1)),
+                None
+            ),
+            (
+                "selectedInventoryDiscounts[1]".to_string(),
+                None,
+                Some(json!({ "discountBenefit": { "percentageOffItem": "10" } }))
+            ),
+        ]
+    );
+    assert!(diff(&before, &before).is_empty());
+}
+
+#[test]
+fn planned_changes_hold_the_body_and_warnings() {
+    let current = markdown("M1");
+    let mut updated = extended(&current);
+    let change = &plan().changes[0];
+    assert_eq!(change.path, "/sell/marketing/v1/item_price_markdown/M1");
+    assert_eq!(change.diff.len(), 1);
+    assert_eq!(change.diff[0].field, "endDate");
+    assert!(change.warnings.is_empty());
+    assert!(change.body.get("promotionId").is_none());
+
+    if let Promotion::Markdown(markdown) = &mut updated {
+        markdown.promotion_status = PromotionStatus::Scheduled;
+    }
+    let mut plan = PromotionPlan::new("EBAY_US");
+    plan.add("M1", &current, &updated, None).unwrap();
+    assert_eq!(plan.changes[0].warnings.len(), 1);
+
+    // Replacing a promotion with itself plans nothing.
+    assert!(!plan.add("M1", &current, &current, None).unwrap());
+}
+
+#[tokio::test]
+async fn changed_promotions_stop_the_whole_plan() {
+    let live = Arc::new(Mutex::new(vec![
+        markdown_json("M1", "2026-03-31T00:00:00Z"),
+        markdown_json("M2", "2026-03-31T00:00:00Z"),
+    ]));
+    let server = marketing(live.clone()).await;
+    let plan = plan();
+
+    // Someone shortened M2 on eBay after the plan was made.
+    live.lock().unwrap()[1]["endDate"] = json!("2026-03-25T00:00:00Z");
+
+    let audit = audit();
+    let err = plan
+        .apply(&client(&server, "EBAY_US"), &audit)
+        .await
+        .unwrap_err();
+    match err {
+        Error::Plan(message) => assert!(message.contains("M2 (endDate)"), "{message}"),
+        other => panic!("expected a plan error, got {other}"),
+    }
+    assert!(server.requests().iter().all(|r| r.method == "GET"));
+    assert!(audit.entries(10).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn unchanged_promotions_are_updated_and_audited() {
+    let live = Arc::new(Mutex::new(vec![
+        markdown_json("M1", "2026-03-31T00:00:00Z"),
+        markdown_json("M2", "2026-03-31T00:00:00Z"),
+    ]));
+    let server = marketing(live).await;
+    let plan = PromotionPlan::from_json(&plan().to_json().unwrap()).unwrap();
+
+    let audit = audit();
+    let report = plan
+        .apply(&client(&server, "EBAY_US"), &audit)
+        .await
+        .unwrap();
+
+    assert_eq!(report.failed(), 0);
+    let puts: Vec<String> = server
+        .requests()
+        .iter()
+        .filter(|r| r.method == "PUT")
+        .map(|r| r.path().to_string())
+        .collect();
+    assert_eq!(
+        puts,
+        [
+            "/sell/marketing/v1/item_price_markdown/M1",
+            "/sell/marketing/v1/item_price_markdown/M2"
+        ]
+    );
+    let entries = audit.entries(10).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries
+        .iter()
+        .all(|entry| entry.action == AuditAction::Extended
+            && entry.rule.as_deref() == Some("spring")));
+}
+
+#[tokio::test]
+async fn plans_only_apply_to_their_marketplace() {
+    let server = marketing(Arc::new(Mutex::new(Vec::new()))).await;
+
+    let err = plan()
+        .apply(&client(&server, "EBAY_GB"), &audit())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Plan(_)), "{err}");
+    assert!(server.requests().is_empty());
+}
@@ -0,0 +1,262 @@
+use std::sync::Arc;
+
+use calamine::{open_workbook, Data, Reader, Xlsx};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use ebay_connect::internal::ebay::fees::Period;
+use ebay_connect::internal::ebay::marketing::report::{
+    export_performance, performance_workbook, PERFORMANCE_SHEET, TO_DATE,
+};
+use ebay_connect::internal::ebay::marketing::{
+    PromotionDetail, PromotionPerformance, PromotionReportDetail, PromotionReports, SummaryReport,
+};
+use ebay_connect::internal::ebay::warehouse::Warehouse;
+use ebay_connect::Error;
+use serde_json::json;
+
+fn at(month: u32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, month, day, 12, 0, 0).unwrap()
+}
+
+/// A to-date report: base sale, promotion sale, units and orders.
+fn report(id: &str, base: f64, sale: f64, items: i64, orders: i64) -> PromotionReportDetail {
+    serde_json::from_value(json!({
+        "promotionId": id,
+        "baseSale": { "value": format!("{base:.2}"), "currency": "USD" },
+        "promotionSale": { "value": format!("{sale:.2}"), "currency": "USD" },
+        "totalSale": { "value": format!("{:.2}", base + sale), "currency": "USD" },
+        "totalDiscount": { "value": format!("{:.2}", sale * 0.25), "currency": "USD" },
+        "itemsSoldQuantity": items,
+        "numberOfOrdersSold": orders,
+        "percentageSalesLift": "12.5%"
+    }))
+    .unwrap()
+}
+
+fn summary() -> SummaryReport {
+    serde_json::from_value(json!({
+        "baseSale": { "value": "300.00", "currency": "USD" },
+        "promotionSale": { "value": "170.00", "currency": "USD" },
+        "totalSale": { "value": "470.00", "currency": "USD" },
+        "percentageSalesLift": "9.1"
+    }))
+    .unwrap()
+}
+
+/// P1 read three times over two months; P2 read once and never stored.
+fn reports() -> PromotionReports {
+    let reports =
+        PromotionReports::new(Arc::new(Warehouse::open_in_memory().unwrap()), "main-store");
+    let p1: PromotionDetail = serde_json::from_value(json!({
+        "promotionId": "P1",
+        "name": "Spring sale",
+        "promotionType": "MARKDOWN_SALE",
+        "promotionStatus": "RUNNING",
+        "startDate": "2026-03-01T00:00:00Z",
+        "endDate": "2026-04-30T00:00:00Z"
+    }))
+    .unwrap();
+    reports.store_promotions(&[p1], at(3, 10)).unwrap();
+
+    reports
+        .record(
+            &[
+                report("P1", 100.0, 80.0, 4, 3),
+                report("P2", 50.0, 20.0, 1, 1),
+            ],
+            None,
+            at(3, 10),
+        )
+        .unwrap();
+    reports
+        .record(&[report("P1", 150.0, 120.0, 6, 4)], None, at(3, 25))
+        .unwrap();
+    reports
+        .record(
+            &[report("P1", 200.0, 150.0, 8, 6)],
+            Some(&summary()),
+            at(4, 5),
+        )
+        .unwrap();
+    reports
+}
+
+fn amounts(row: &PromotionPerformance) -> (f64, f64, f64, i64, i64) {
+    (
+        row.base_sale,
+        row.promotion_sale,
+        row.total_discount,
+        row.items_sold,
+        row.orders_sold,
+    )
+}
+
+#[test]
+fn periods_get_the_difference_between_successive_reads() {
+    let rows = reports().performance(Some(Period::Month)).unwrap();
+
+    let keys: Vec<(&str, &str)> = rows
+        .iter()
+        .map(|r| (r.period.as_str(), r.promotion_id.as_str()))
+        .collect();
+    assert_eq!(
+        keys,
+        [("2026-03", "P1"), ("2026-03", "P2"), ("2026-04", "P1")]
+    );
+
+    // March ends at the 25th's read; everything before the first read counts
+    // towards it.
+    assert_eq!(amounts(&rows[0]), (150.0, 120.0, 30.0, 6, 4));
+    assert_eq!(amounts(&rows[1]), (50.0, 20.0, 5.0, 1, 1));
+    // April is the 5th's read minus the 25th's.
+    assert_eq!(amounts(&rows[2]), (50.0, 30.0, 7.5, 2, 2));
+    assert_eq!(rows[2].total_sale, 80.0);
+
+    assert_eq!(rows[0].period_start, NaiveDate::from_ymd_opt(2026, 3, 1));
+    assert_eq!(rows[0].name, "Spring sale");
+    assert_eq!(rows[0].currency.as_deref(), Some("USD"));
+    assert_eq!(rows[2].sales_lift, Some(12.5));
+    // P2 was never stored, so only its ID is known.
+    assert_eq!(rows[1].name, "");
+    assert_eq!(rows[1].promotion_type, None);
+}
+
+#[test]
+fn to_date_rows_hold_the_latest_read() {
+    let rows = reports().performance(None).unwrap();
+
+    assert_eq!(rows.len(), 2);
+    assert!(rows
+        .iter()
+        .all(|r| r.period == TO_DATE && r.period_start.is_none()));
+    assert_eq!(rows[0].promotion_id, "P1");
+    assert_eq!(amounts(&rows[0]), (200.0, 150.0, 37.5, 8, 6));
+    assert_eq!(amounts(&rows[1]), (50.0, 20.0, 5.0, 1, 1));
+
+    // The per-period rows add up to the to-date ones.
+    let monthly = reports().performance(Some(Period::Month)).unwrap();
+    let p1_sale: f64 = monthly
+        .iter()
+        .filter(|r| r.promotion_id == "P1")
+        .map(|r| r.promotion_sale)
+        .sum();
+    assert_eq!(p1_sale, rows[0].promotion_sale);
+}
+
+#[test]
+fn performance_workbook_has_the_rows_and_summary() {
+    let reports = reports();
+    let rows = reports.performance(Some(Period::Month)).unwrap();
+    let summary = reports.summary().unwrap().unwrap();
+    assert_eq!(summary.recorded_at, at(4, 5));
+    assert_eq!(summary.sales_lift, Some(9.1));
+
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-promotions", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("performance.xlsx");
+    let result = export_performance(&rows, Some(&summary), &path).unwrap();
+    assert_eq!(result.record_count, 3);
+
+    let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+    assert_eq!(workbook.sheet_names(), [PERFORMANCE_SHEET, "Summary"]);
+    let sheet = workbook.worksheet_range(PERFORMANCE_SHEET).unwrap();
+    let data: Vec<&[Data]> = sheet.rows().collect();
+    assert_eq!(data.len(), 4);
+    let column = |name: &str| {
+        data[0]
+            .iter()
+            .position(|cell| *cell == Data::String(name.to_string()))
+            .unwrap_or_else(|| panic!("no {name} column"))
+    };
+    assert_eq!(
+        data[3][column("Period")],
+        Data::String("2026-04".to_string())
+    );
+    assert_eq!(data[3][column("Promotion Sale")], Data::Float(30.0));
+    assert_eq!(data[3][column("Items Sold")], Data::Float(2.0));
+
+    let metrics = workbook.worksheet_range("Summary").unwrap();
+    let metric = |label: &str| {
+        metrics
+            .rows()
+            .find(|row| row[0] == Data::String(label.to_string()))
+            .map(|row| row[1].clone())
+            .unwrap_or_else(|| panic!("no {label} metric"))
+    };
+    assert_eq!(metric("Promotions Reported"), Data::Float(2.0));
+    assert_eq!(metric("Promotion Sale Revenue (USD)"), Data::Float(170.0));
+    assert_eq!(metric("  Total Sale Revenue (USD)"), Data::Float(470.0));
+    assert_eq!(metric("  Spring sale (USD)"), Data::Float(150.0));
+
+    assert!(matches!(
+        performance_workbook(&[], None),
+        Err(Error::Spreadsheet(_))
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn summary_totals_each_currency_on_its_own() {
+    let mut rows = reports().performance(None).unwrap();
+    rows[1].currency = Some("GBP".to_string());
+
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-promotions", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("currencies.xlsx");
+    export_performance(&rows, None, &path).unwrap();
+
+    let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+    let metrics = workbook.worksheet_range("Summary").unwrap();
+    let labels: Vec<(String, Data)> = metrics
+        .rows()
+        .filter_map(|row| match &row[0] {
+            Data::String(label) => Some((label.clone(), row[1].clone())),
+            _ => None,
+        })
+        .filter(|(label, _)| label.contains("Promotion Sale") || label.starts_with("  "))
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            (
+                "Promotion Sale Revenue (GBP)".to_string(),
+                Data::Float(20.0)
+            ),
+            (
+                "Promotion Sale Revenue (USD)".to_string(),
+                Data::Float(150.0)
+            ),
+            ("  P2 (GBP)".to_string(), Data::Float(20.0)),
+            ("  Spring sale (USD)".to_string(), Data::Float(150.0)),
+        ]
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn unknown_stored_values_fail_the_read() {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-promotions", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("unknown-status.db");
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+    let reports = PromotionReports::new(Arc::new(Warehouse::open(&path).unwrap()), "main-store");
+    let p1: PromotionDetail = serde_json::from_value(json!({
+        "promotionId": "P1",
+        "name": "Spring sale",
+        "promotionType": "MARKDOWN_SALE",
+        "promotionStatus": "RUNNING"
+    }))
+    .unwrap();
+    reports.store_promotions(&[p1], at(3, 10)).unwrap();
+    reports
+        .record(&[report("P1", 100.0, 80.0, 4, 3)], None, at(3, 10))
+        .unwrap();
+    assert!(reports.performance(None).is_ok());
+
+    let db = rusqlite::Connection::open(&path).unwrap();
+    db.execute("UPDATE promotions SET status = 'ARCHIVED'", [])
+        .unwrap();
+    let err = reports.performance(None).unwrap_err();
+    assert!(matches!(err, Error::Sqlite(_)), "{err}");
+}
@@ -0,0 +1,295 @@
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ebay_connect::adapters::ebay::analytics::{AnalyticsClient, QuotaMonitor, QuotaSource};
+use ebay_connect::adapters::ebay::rate_limit::{
+    Api, BucketConfig, Budget, BudgetScope, RateLimitConfig, RateLimiter, DAY_SECS,
+};
+use ebay_connect::{Environment, Error};
+
+use common::{MockResponse, MockServer, StaticToken};
+
+const APP_LIMITS: &str = r#"{
+    "rateLimits": [
+        {
+            "apiContext": "TradAPI",
+            "apiName": "TradingAPI",
+            "apiVersion": "v1",
+            "resources": [
+                {
+                    "name": "TradingAPI",
+                    "rates": [
+                        {
+                            "count": 1200,
+                            "limit": 5000,
+                            "remaining": 3800,
+                            "reset": "2030-01-02T07:00:00.000Z",
+                            "timeWindow": 86400
+                        }
+                    ]
+                }
+            ]
+        },
+        {
+            "apiContext": "sell",
+            "apiName": "Marketing",
+            "apiVersion": "v1",
+            "resources": [
+                {
+                    "name": "sell.marketing",
+                    "rates": [
+                        { "count": 10, "limit": 100000, "remaining": 99990, "reset": "2030-01-02T07:00:00.000Z", "timeWindow": 86400 }
+                    ]
+                },
+                {
+                    "name": "sell.marketing.promotion",
+                    "rates": [
+                        { "count": 40, "limit": 50, "remaining": 10, "reset": "2030-01-01T08:00:00.000Z", "timeWindow": 3600 }
+                    ]
+                }
+            ]
+        },
+        {
+            "apiContext": "developer",
+            "apiName": "Analytics",
+            "apiVersion": "v1_beta",
+            "resources": [
+                { "name": "developer.analytics", "rates": [ { "limit": 100, "remaining": 99 } ] }
+            ]
+        }
+    ]
+}"#;
+
+const USER_LIMITS: &str = r#"{
+    "rateLimits": [
+        {
+            "apiContext": "TradAPI",
+            "apiName": "TradingAPI",
+            "apiVersion": "v1",
+            "resources": [
+                {
+                    "name": "TradingAPI.GetSellerList",
+                    "rates": [
+                        { "count": 7, "limit": 300, "remaining": 293, "reset": "2030-01-02T07:00:00.000Z", "timeWindow": 86400 }
+                    ]
+                }
+            ]
+        }
+    ]
+}"#;
+
+fn monitor(server: &MockServer, limiter: Arc<RateLimiter>) -> QuotaMonitor {
+    let client = AnalyticsClient::new(Environment::Production, Arc::new(StaticToken("app")))
+        .with_user_tokens(Arc::new(StaticToken("user")))
+        .with_base_url(server.url("/"));
+    QuotaMonitor::new(client, limiter, "client-id", "main-store")
+}
+
+#[tokio::test]
+async fn live_quotas_replace_the_defaults() {
+    let server = MockServer::start(|request| match request.path() {
+        "/developer/analytics/v1_beta/rate_limit/" => MockResponse::json(200, APP_LIMITS),
+        "/developer/analytics/v1_beta/user_rate_limit/" => MockResponse::json(200, USER_LIMITS),
+        _ => MockResponse::json(404, "{}"),
+    })
+    .await;
+    let limiter = Arc::new(RateLimiter::new(RateLimitConfig::default()));
+
+    let status = monitor(&server, limiter.clone()).refresh().await;
+    assert_eq!(status.source, QuotaSource::Live);
+    assert!(status.error.is_none());
+
+    let trading = limiter.budget(BudgetScope::App, "client-id", Api::Trading);
+    assert_eq!(trading.limit, Some(5000));
+    assert_eq!(trading.used, 1200);
+    assert_eq!(trading.remaining(), Some(3800));
+    assert_eq!(trading.resets_at, 1_893_567_600);
+
+    // The tighter of the two Marketing resources wins.
+    let marketing = limiter.budget(BudgetScope::App, "client-id", Api::Marketing);
+    assert_eq!(marketing.limit, Some(50));
+    assert_eq!(marketing.remaining(), Some(10));
+    assert_eq!(marketing.window_secs, 3600);
+
+    let user = limiter.budget(BudgetScope::User, "main-store", Api::Trading);
+    assert_eq!(user.limit, Some(300));
+    assert_eq!(user.used, 7);
+
+    let requests = server.requests();
+    assert_eq!(requests[0].header("Authorization"), Some("Bearer app"));
+    assert_eq!(requests[1].header("Authorization"), Some("Bearer user"));
+}
+
+#[tokio::test]
+async fn unreachable_endpoint_keeps_defaults() {
+    let server =
+        MockServer::start(|_| MockResponse::json(503, r#"{"errors":[{"errorId":1}]}"#)).await;
+    let limiter = Arc::new(RateLimiter::new(RateLimitConfig::default()));
+
+    let status = monitor(&server, limiter.clone()).refresh().await;
+    assert_eq!(status.source, QuotaSource::Defaults);
+    assert!(status.error.unwrap().contains("503"));
+
+    let trading = limiter.budget(BudgetScope::App, "client-id", Api::Trading);
+    assert_eq!(trading.limit, Some(5000));
+    assert_eq!(trading.used, 0);
+    assert_eq!(trading.window_secs, DAY_SECS);
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn budget_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-{name}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("budgets.json");
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Trading only, at 10 calls a second after a burst of 2, limited to 10
+/// calls a day with nothing held back.
+fn trading_config() -> RateLimitConfig {
+    RateLimitConfig {
+        buckets: HashMap::from([(Api::Trading, BucketConfig::new(10.0, 2))]),
+        app_limits: HashMap::from([(Api::Trading, 10)]),
+        reserve: 0.0,
+        ..RateLimitConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn bucket_refills_at_its_rate_after_the_burst() {
+    let limiter = RateLimiter::new(trading_config());
+
+    let started = Instant::now();
+    limiter
+        .acquire(Api::Trading, "client-id", None)
+        .await
+        .unwrap();
+    limiter
+        .acquire(Api::Trading, "client-id", None)
+        .await
+        .unwrap();
+    assert!(started.elapsed() < Duration::from_millis(50));
+
+    // The burst is spent: the next two wait 100ms each for a token.
+    limiter
+        .acquire(Api::Trading, "client-id", None)
+        .await
+        .unwrap();
+    limiter
+        .acquire(Api::Trading, "client-id", None)
+        .await
+        .unwrap();
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(180), "{elapsed:?}");
+    assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+}
+
+#[tokio::test]
+async fn spent_budget_is_refused_until_its_window_resets() {
+    let limiter = RateLimiter::new(trading_config());
+    let now = unix_now();
+    let spent = Budget {
+        scope: BudgetScope::App,
+        owner: "client-id".to_string(),
+        api: Api::Trading,
+        limit: Some(10),
+        used: 10,
+        window_secs: DAY_SECS,
+        resets_at: now + 3600,
+    };
+    limiter.set_budget(spent.clone()).unwrap();
+
+    let err = limiter
+        .acquire(Api::Trading, "client-id", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::QuotaExhausted(_)), "{err}");
+
+    limiter
+        .set_budget(Budget {
+            resets_at: now - 1,
+            ..spent
+        })
+        .unwrap();
+    limiter
+        .acquire(Api::Trading, "client-id", None)
+        .await
+        .unwrap();
+
+    let budget = limiter.budget(BudgetScope::App, "client-id", Api::Trading);
+    assert_eq!(budget.used, 1);
+    assert!(budget.resets_at >= now + DAY_SECS);
+}
+
+#[tokio::test]
+async fn budgets_are_written_in_batches_and_reloaded() {
+    let path = budget_path("reload");
+    let config = RateLimitConfig {
+        persist_every: 3,
+        persist_interval: Duration::from_secs(3600),
+        ..trading_config()
+    };
+
+    let limiter = RateLimiter::open(&path, config.clone()).unwrap();
+    for _ in 0..2 {
+        limiter
+            .acquire(Api::Trading, "client-id", Some("main-store"))
+            .await
+            .unwrap();
+    }
+    assert!(!path.exists(), "written before the batch was full");
+
+    limiter
+        .acquire(Api::Trading, "client-id", Some("main-store"))
+        .await
+        .unwrap();
+    assert!(path.exists());
+
+    // The fourth call is only in memory until the limiter goes away.
+    limiter
+        .acquire(Api::Trading, "client-id", Some("main-store"))
+        .await
+        .unwrap();
+    drop(limiter);
+
+    let reloaded = RateLimiter::open(&path, config).unwrap();
+    assert_eq!(
+        reloaded
+            .budget(BudgetScope::App, "client-id", Api::Trading)
+            .used,
+        4
+    );
+    assert_eq!(
+        reloaded
+            .budget(BudgetScope::User, "main-store", Api::Trading)
+            .used,
+        4
+    );
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn budget_files_of_another_version_are_refused() {
+    let path = budget_path("version");
+    std::fs::write(&path, r#"{"version": 2, "budgets": []}"#).unwrap();
+
+    let err = RateLimiter::open(&path, trading_config()).unwrap_err();
+    assert!(
+        matches!(&err, Error::InvalidResponse(message) if message.contains("version 2")),
+        "{err}"
+    );
+
+    std::fs::write(&path, r#"{"version": 1, "budgets": []}"#).unwrap();
+    RateLimiter::open(&path, trading_config()).unwrap();
+}
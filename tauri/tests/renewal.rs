@@ -0,0 +1,317 @@
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use ebay_connect::internal::ebay::marketing::audit::AuditAction;
+use ebay_connect::internal::ebay::marketing::renewal::{
+    pattern_matches, renewed_end_date, RenewalDecision, RenewalOutcome,
+};
+use ebay_connect::internal::ebay::marketing::{
+    MarketingClient, PromotionAudit, RenewalAction, RenewalEngine, RenewalRule, RenewalRules,
+};
+use ebay_connect::internal::ebay::warehouse::Warehouse;
+use ebay_connect::{Environment, Error};
+use serde_json::{json, Value};
+
+use common::{MockResponse, MockServer, StaticToken};
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, 20, 12, 0, 0).unwrap()
+}
+
+fn hours(n: i64) -> DateTime<Utc> {
+    now() + TimeDelta::hours(n)
+}
+
+fn item_promotion(
+    id: &str,
+    kind: &str,
+    name: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Value {
+    json!({
+        "promotionId": id,
+        "name": name,
+        "marketplaceId": "EBAY_US",
+        "promotionType": kind,
+        "promotionStatus": "RUNNING",
+        "startDate": start,
+        "endDate": end,
+        "discountRules": [{ "discountBenefit": { "percentageOffOrder": "10" } }]
+    })
+}
+
+fn markdown(id: &str, sku: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Value {
+    json!({
+        "promotionId": id,
+        "name": format!("Markdown {id}"),
+        "marketplaceId": "EBAY_US",
+        "promotionType": "MARKDOWN_SALE",
+        "promotionStatus": "RUNNING",
+        "startDate": start,
+        "endDate": end,
+        "selectedInventoryDiscounts": [{
+            "discountBenefit": { "percentageOffItem": "20" },
+            "inventoryCriterion": {
+                "inventoryCriterionType": "INVENTORY_BY_VALUE",
+                "inventoryItems": [{ "inventoryReferenceId": sku }]
+            }
+        }]
+    })
+}
+
+/// Serves `promotions` from `getPromotions` and their resources, and
+/// accepts every update.
+async fn marketing(promotions: Vec<Value>) -> MockServer {
+    let by_id: HashMap<String, Value> = promotions
+        .iter()
+        .map(|p| (p["promotionId"].as_str().unwrap().to_string(), p.clone()))
+        .collect();
+    let listing = json!({ "promotions": promotions }).to_string();
+    MockServer::start(move |request| {
+        if request.method == "PUT" {
+            return MockResponse::json(204, "");
+        }
+        if request.path() == "/sell/marketing/v1/promotion" {
+            return MockResponse::json(200, listing.clone());
+        }
+        let id = request.path().rsplit('/').next().unwrap_or_default();
+        match by_id.get(id) {
+            Some(promotion) => MockResponse::json(200, promotion.to_string()),
+            None => MockResponse::json(404, "{}"),
+        }
+    })
+    .await
+}
+
+fn database(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-renewal", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+    path
+}
+
+fn engine(server: &MockServer, warehouse: Arc<Warehouse>, rules: RenewalRules) -> RenewalEngine {
+    let client = MarketingClient::new(
+        Environment::Production,
+        "EBAY_US",
+        Arc::new(StaticToken("user")),
+    )
+    .with_base_url(server.url("/"));
+    RenewalEngine::new(client, rules, PromotionAudit::new(warehouse, "main-store"))
+}
+
+fn rules() -> RenewalRules {
+    RenewalRules::from_json(
+        r#"{
+            "rules": [
+                { "name": "coupons lapse", "promotionTypes": ["CODED_COUPON"], "action": { "type": "lapse" } },
+                {
+                    "name": "shirt markdowns",
+                    "promotionTypes": ["MARKDOWN_SALE"],
+                    "sku": "SHIRT-*",
+                    "withinHours": 24,
+                    "action": { "type": "extend", "days": 7, "maxTotalDays": 30 }
+                },
+                { "name": "everything else", "action": { "type": "extend", "days": 14 } }
+            ]
+        }"#,
+    )
+    .unwrap()
+}
+
+fn decision<'a>(decisions: &'a [RenewalDecision], id: &str) -> &'a RenewalDecision {
+    decisions
+        .iter()
+        .find(|decision| decision.promotion_id == id)
+        .unwrap_or_else(|| panic!("no decision for {id}"))
+}
+
+#[test]
+fn patterns_match_globs_ignoring_case() {
+    assert!(pattern_matches("SHIRT-*", "shirt-blue-xl"));
+    assert!(pattern_matches("*-XL", "SHIRT-BLUE-xl"));
+    assert!(pattern_matches("SHIRT-?", "SHIRT-1"));
+    assert!(!pattern_matches("SHIRT-?", "SHIRT-12"));
+    assert!(pattern_matches("a*b*c", "aXXbYYc"));
+    assert!(!pattern_matches("a*b*c", "aXXbYY"));
+    assert!(pattern_matches("*", ""));
+    assert!(!pattern_matches("SHIRT", "SHIRT-1"));
+    assert!(!pattern_matches("?", ""));
+}
+
+#[test]
+fn extensions_stop_at_the_maximum_length() {
+    let start = now() - TimeDelta::days(20);
+    let end = now();
+    let extend = |days, max_total_days| RenewalAction::Extend {
+        days,
+        max_total_days,
+    };
+
+    assert_eq!(
+        renewed_end_date(extend(7, None), start, end),
+        Some(end + TimeDelta::days(7))
+    );
+    // 20 days run, 25 allowed: only 5 more.
+    assert_eq!(
+        renewed_end_date(extend(7, Some(25)), start, end),
+        Some(start + TimeDelta::days(25))
+    );
+    assert_eq!(renewed_end_date(extend(7, Some(20)), start, end), None);
+    assert_eq!(renewed_end_date(extend(7, Some(10)), start, end), None);
+    assert_eq!(renewed_end_date(RenewalAction::Lapse, start, end), None);
+}
+
+#[test]
+fn malformed_rules_are_refused() {
+    let zero = RenewalRule::new(
+        "zero",
+        RenewalAction::Extend {
+            days: 0,
+            max_total_days: None,
+        },
+    );
+    assert!(matches!(RenewalRules::new(vec![zero]), Err(Error::Rule(_))));
+    assert!(matches!(
+        RenewalRules::from_json(
+            r#"{"rules":[{"name":"cap","action":{"type":"extend","days":3,"maxTotalDays":0}}]}"#
+        ),
+        Err(Error::Rule(_))
+    ));
+}
+
+#[tokio::test]
+async fn first_matching_rule_decides() {
+    let server = marketing(vec![
+        item_promotion("C1", "CODED_COUPON", "Coupon", hours(-240), hours(10)),
+        markdown("M1", "SHIRT-RED", hours(-120), hours(10)),
+        markdown("M2", "PANTS-1", hours(-120), hours(10)),
+        // Due for "everything else", but not yet for the 24-hour shirt rule.
+        markdown("M3", "SHIRT-BLUE", hours(-120), hours(36)),
+        // Already 30 days long.
+        markdown(
+            "M4",
+            "SHIRT-GREEN",
+            hours(10) - TimeDelta::days(30),
+            hours(10),
+        ),
+        item_promotion("O1", "ORDER_DISCOUNT", "Later", hours(-24), hours(24 * 10)),
+    ])
+    .await;
+    let warehouse = Arc::new(Warehouse::open_in_memory().unwrap());
+    let report = engine(&server, warehouse, rules())
+        .plan(now())
+        .await
+        .unwrap();
+
+    assert_eq!(report.checked, 6);
+    let c1 = decision(&report.decisions, "C1");
+    assert_eq!(
+        (c1.rule.as_str(), c1.outcome),
+        ("coupons lapse", RenewalOutcome::Lapsed)
+    );
+    assert_eq!(c1.new_end_date, None);
+
+    let m1 = decision(&report.decisions, "M1");
+    assert_eq!(
+        (m1.rule.as_str(), m1.outcome),
+        ("shirt markdowns", RenewalOutcome::Planned)
+    );
+    assert_eq!(m1.new_end_date, Some(hours(10) + TimeDelta::days(7)));
+
+    let m2 = decision(&report.decisions, "M2");
+    assert_eq!(m2.rule, "everything else");
+    assert_eq!(m2.new_end_date, Some(hours(10) + TimeDelta::days(14)));
+
+    // The shirt rule is first but not due; later rules are not consulted.
+    assert!(report.decisions.iter().all(|d| d.promotion_id != "M3"));
+
+    let m4 = decision(&report.decisions, "M4");
+    assert_eq!(m4.outcome, RenewalOutcome::Capped);
+    assert!(report.decisions.iter().all(|d| d.promotion_id != "O1"));
+
+    let planned: Vec<&str> = report
+        .plan
+        .changes
+        .iter()
+        .map(|change| change.promotion_id.as_str())
+        .collect();
+    assert_eq!(planned, ["M1", "M2"]);
+    // Nothing was sent.
+    assert!(server.requests().iter().all(|r| r.method == "GET"));
+}
+
+#[tokio::test]
+async fn run_extends_and_audits() {
+    let server = marketing(vec![
+        item_promotion("C1", "CODED_COUPON", "Coupon", hours(-240), hours(10)),
+        markdown("M1", "SHIRT-RED", hours(-120), hours(10)),
+    ])
+    .await;
+    let warehouse = Arc::new(Warehouse::open_in_memory().unwrap());
+    let audit = PromotionAudit::new(warehouse.clone(), "main-store");
+
+    let report = engine(&server, warehouse, rules())
+        .run(now())
+        .await
+        .unwrap();
+
+    assert_eq!(report.count(RenewalOutcome::Extended), 1);
+    assert_eq!(report.count(RenewalOutcome::Lapsed), 1);
+    let puts: Vec<_> = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.method == "PUT")
+        .collect();
+    assert_eq!(puts.len(), 1);
+    assert_eq!(puts[0].path(), "/sell/marketing/v1/item_price_markdown/M1");
+    let body: Value = serde_json::from_str(&puts[0].body).unwrap();
+    assert_eq!(body["promotionStatus"], "RUNNING");
+
+    let entries = audit.history("M1").unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].action, AuditAction::Extended);
+    assert_eq!(entries[0].rule.as_deref(), Some("shirt markdowns"));
+    assert_eq!(entries[0].old_end_date, Some(hours(10)));
+    assert_eq!(
+        entries[0].new_end_date,
+        Some(hours(10) + TimeDelta::days(7))
+    );
+    assert!(audit.history("C1").unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn unknown_stored_values_fail_the_read() {
+    let server = marketing(vec![markdown("M1", "SHIRT-RED", hours(-120), hours(10))]).await;
+    let path = database("audit.db");
+    let warehouse = Arc::new(Warehouse::open(&path).unwrap());
+    let audit = PromotionAudit::new(warehouse.clone(), "main-store");
+    engine(&server, warehouse, rules())
+        .run(now())
+        .await
+        .unwrap();
+    assert_eq!(audit.entries(10).unwrap().len(), 1);
+
+    let db = rusqlite::Connection::open(&path).unwrap();
+    db.execute(
+        "UPDATE promotion_audit SET promotion_type = 'FLASH_SALE'",
+        [],
+    )
+    .unwrap();
+    assert!(matches!(audit.entries(10), Err(Error::Sqlite(_))));
+
+    db.execute(
+        "UPDATE promotion_audit SET promotion_type = 'MARKDOWN_SALE', action = 'rolled back'",
+        [],
+    )
+    .unwrap();
+    assert!(matches!(audit.history("M1"), Err(Error::Sqlite(_))));
+}
@@ -0,0 +1,251 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ebay_connect::adapters::ebay::retry::{
+    classify, retry_after, ErrorClass, Retrier, RetryPolicy,
+};
+use ebay_connect::internal::ebay::trading::types::{Ack, SeverityCode};
+use ebay_connect::internal::ebay::trading::{ApiError, TradingErrors};
+use ebay_connect::Error;
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+fn api(status: u16) -> Error {
+    Error::Api {
+        status,
+        message: format!("HTTP {status}"),
+        retry_after: None,
+    }
+}
+
+fn trading(code: &str, classification: &str) -> Error {
+    Error::Trading(TradingErrors {
+        call_name: "GetSellerList".to_string(),
+        ack: Ack::Failure,
+        errors: vec![ApiError {
+            short_message: "failed".to_string(),
+            long_message: None,
+            error_code: code.to_string(),
+            severity_code: SeverityCode::Error,
+            error_classification: Some(classification.to_string()),
+        }],
+    })
+}
+
+fn policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::from_millis(10),
+        throttle_delay: Duration::from_millis(10),
+        max_delay: Duration::from_secs(1),
+        failure_threshold: 2,
+        open_for: Duration::from_millis(100),
+    }
+}
+
+#[test]
+fn errors_are_classified_by_status_and_code() {
+    assert_eq!(classify(&api(429)), ErrorClass::Throttled);
+    assert_eq!(classify(&api(503)), ErrorClass::Transient);
+    assert_eq!(classify(&api(408)), ErrorClass::Transient);
+    assert_eq!(classify(&api(401)), ErrorClass::Auth);
+    assert_eq!(classify(&api(404)), ErrorClass::Validation);
+    assert_eq!(
+        classify(&trading("518", "RequestError")),
+        ErrorClass::Throttled
+    );
+    assert_eq!(classify(&trading("932", "RequestError")), ErrorClass::Auth);
+    assert_eq!(
+        classify(&trading("10007", "SystemError")),
+        ErrorClass::Transient
+    );
+    assert_eq!(
+        classify(&trading("21919", "RequestError")),
+        ErrorClass::Validation
+    );
+    assert_eq!(classify(&Error::Timeout("GetItem")), ErrorClass::Transient);
+    assert_eq!(
+        classify(&Error::QuotaExhausted("Trading".to_string())),
+        ErrorClass::Fatal
+    );
+    assert!(ErrorClass::Throttled.is_retryable());
+    assert!(!ErrorClass::Validation.is_retryable());
+}
+
+#[test]
+fn backoff_doubles_within_its_jitter_bounds() {
+    let policy = RetryPolicy {
+        base_delay: Duration::from_secs(2),
+        throttle_delay: Duration::from_secs(10),
+        max_delay: Duration::from_secs(30),
+        ..RetryPolicy::default()
+    };
+    for _ in 0..50 {
+        let first = policy.backoff(ErrorClass::Transient, 1);
+        assert!(first >= Duration::from_secs(1) && first <= Duration::from_secs(2));
+        let third = policy.backoff(ErrorClass::Transient, 3);
+        assert!(third >= Duration::from_secs(4) && third <= Duration::from_secs(8));
+        let throttled = policy.backoff(ErrorClass::Throttled, 1);
+        assert!(throttled >= Duration::from_secs(5) && throttled <= Duration::from_secs(10));
+        // Capped at max_delay before the jitter.
+        let capped = policy.backoff(ErrorClass::Transient, 10);
+        assert!(capped >= Duration::from_secs(15) && capped <= Duration::from_secs(30));
+    }
+}
+
+#[test]
+fn retry_after_reads_seconds_and_dates() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(retry_after(&headers), None);
+
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+    assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+
+    let at = (chrono::Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+    headers.insert(RETRY_AFTER, HeaderValue::from_str(&at).unwrap());
+    let wait = retry_after(&headers).unwrap();
+    assert!(wait > Duration::from_secs(85) && wait <= Duration::from_secs(90));
+
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+    assert_eq!(retry_after(&headers), None);
+}
+
+#[tokio::test]
+async fn server_retry_after_replaces_the_backoff() {
+    let retrier = Retrier::new(RetryPolicy {
+        base_delay: Duration::from_secs(30),
+        ..policy()
+    });
+    let calls = AtomicU32::new(0);
+    let started = Instant::now();
+    let value = retrier
+        .run("sell.marketing", || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(Error::Api {
+                    status: 503,
+                    message: "busy".to_string(),
+                    retry_after: Some(Duration::from_millis(20)),
+                }),
+                _ => Ok("done"),
+            }
+        })
+        .await
+        .unwrap();
+    assert_eq!(value, "done");
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    // A Retry-After past max_delay is not waited out.
+    let calls = AtomicU32::new(0);
+    let err = retrier
+        .run("sell.marketing", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(Error::Api {
+                status: 429,
+                message: "slow down".to_string(),
+                retry_after: Some(Duration::from_secs(3600)),
+            })
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Api { status: 429, .. }));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn only_successes_reset_the_failure_count() {
+    let retrier = Retrier::new(RetryPolicy {
+        max_attempts: 1,
+        ..policy()
+    });
+    let fail = |status: u16| {
+        let retrier = &retrier;
+        async move {
+            retrier
+                .run("trading", || async move { Err::<(), _>(api(status)) })
+                .await
+        }
+    };
+
+    fail(503).await.unwrap_err();
+    // A validation error neither counts nor clears the outage.
+    fail(400).await.unwrap_err();
+    fail(502).await.unwrap_err();
+
+    let err = retrier
+        .run("trading", || async { Ok(()) })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::CircuitOpen(_)), "{err}");
+}
+
+#[tokio::test]
+async fn open_circuit_lets_one_probe_through_then_closes() {
+    let retrier = Arc::new(Retrier::new(RetryPolicy {
+        max_attempts: 1,
+        ..policy()
+    }));
+    for _ in 0..2 {
+        retrier
+            .run("trading", || async { Err::<(), _>(api(503)) })
+            .await
+            .unwrap_err();
+    }
+    let calls = AtomicU32::new(0);
+    let err = retrier
+        .run("trading", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::CircuitOpen(_)));
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    // Half-open: the first call goes through, others are still refused.
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    let probe = tokio::spawn({
+        let retrier = retrier.clone();
+        async move {
+            retrier
+                .run("trading", || async {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Ok(())
+                })
+                .await
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let err = retrier
+        .run("trading", || async { Ok(()) })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::CircuitOpen(_)), "{err}");
+
+    probe.await.unwrap().unwrap();
+    retrier.run("trading", || async { Ok(()) }).await.unwrap();
+}
+
+#[tokio::test]
+async fn failed_probe_reopens_the_circuit() {
+    let retrier = Retrier::new(RetryPolicy {
+        max_attempts: 1,
+        ..policy()
+    });
+    for _ in 0..2 {
+        retrier
+            .run("trading", || async { Err::<(), _>(api(503)) })
+            .await
+            .unwrap_err();
+    }
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    retrier
+        .run("trading", || async { Err::<(), _>(api(503)) })
+        .await
+        .unwrap_err();
+
+    let err = retrier
+        .run("trading", || async { Ok(()) })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::CircuitOpen(_)), "{err}");
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use ebay_connect::internal::ebay::listing_rows::{process_listed_items, Cell};
+use ebay_connect::internal::ebay::selling_lists::{ListedItem, SellingList, SellingListsFetcher};
+use ebay_connect::internal::ebay::trading::TradingClient;
+use ebay_connect::Environment;
+
+use common::{MockResponse, MockServer, RecordedRequest, StaticToken};
+
+fn client(server: &MockServer) -> TradingClient {
+    TradingClient::new(
+        Environment::Production,
+        "app-id",
+        0,
+        Arc::new(StaticToken("user")),
+    )
+    .with_endpoint(server.url("/ws/api.dll"))
+}
+
+fn fetcher(client: &TradingClient) -> SellingListsFetcher<'_> {
+    SellingListsFetcher::new(client)
+        .with_entries_per_page(2)
+        .with_page_delay(Duration::ZERO)
+}
+
+fn tag<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let start = body.find(&format!("<{name}>"))? + name.len() + 2;
+    let end = body[start..].find(&format!("</{name}>"))? + start;
+    Some(&body[start..end])
+}
+
+fn item(item_id: &str) -> String {
+    format!("<Item><ItemID>{item_id}</ItemID></Item>")
+}
+
+fn pagination(pages: u32) -> String {
+    format!("<PaginationResult><TotalNumberOfPages>{pages}</TotalNumberOfPages></PaginationResult>")
+}
+
+fn response(lists: &str) -> MockResponse {
+    MockResponse::xml(
+        200,
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><GetMyeBaySellingResponse xmlns="urn:ebay:apis:eBLBaseComponents"><Ack>Success</Ack>{lists}</GetMyeBaySellingResponse>"#
+        ),
+    )
+}
+
+/// Active has two pages; Sold has one bare transaction and one order of two;
+/// Deleted From Sold has one transaction. The other lists are empty.
+fn my_ebay(request: &RecordedRequest) -> MockResponse {
+    let body = &request.body;
+    if let Some(active) = tag(body, "ActiveList") {
+        let page = tag(active, "PageNumber").unwrap_or("1");
+        let items = match page {
+            "1" => item("1001") + &item("1002"),
+            _ => item("1003"),
+        };
+        response(&format!(
+            "<ActiveList><ItemArray>{items}</ItemArray>{}</ActiveList>",
+            pagination(2)
+        ))
+    } else if tag(body, "SoldList").is_some() {
+        response(&format!(
+            "<SoldList><OrderTransactionArray>\
+             <OrderTransaction><Transaction><TransactionID>T1</TransactionID>{}</Transaction></OrderTransaction>\
+             <OrderTransaction><Order><OrderID>O1</OrderID><TransactionArray>\
+             <Transaction><TransactionID>T2</TransactionID>{}</Transaction>\
+             <Transaction><TransactionID>T3</TransactionID>{}</Transaction>\
+             </TransactionArray></Order></OrderTransaction>\
+             </OrderTransactionArray>{}</SoldList>",
+            item("2001"),
+            item("2002"),
+            item("2003"),
+            pagination(1)
+        ))
+    } else if tag(body, "DeletedFromSoldList").is_some() {
+        response(&format!(
+            "<DeletedFromSoldList><OrderTransactionArray><OrderTransaction><Transaction><TransactionID>T4</TransactionID>{}</Transaction></OrderTransaction></OrderTransactionArray>{}</DeletedFromSoldList>",
+            item("3001"),
+            pagination(1)
+        ))
+    } else {
+        response("")
+    }
+}
+
+fn summary(items: &[ListedItem]) -> Vec<(SellingList, &str, Option<&str>)> {
+    items
+        .iter()
+        .map(|listed| {
+            (
+                listed.list,
+                listed.item.item_id.as_str(),
+                listed
+                    .transaction
+                    .as_ref()
+                    .and_then(|t| t.transaction_id.as_deref()),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn lists_are_paged_to_their_last_page() {
+    let server = MockServer::start(my_ebay).await;
+    let client = client(&server);
+
+    let items = fetcher(&client)
+        .fetch_list(SellingList::Active)
+        .await
+        .unwrap();
+    assert_eq!(
+        summary(&items),
+        [
+            (SellingList::Active, "1001", None),
+            (SellingList::Active, "1002", None),
+            (SellingList::Active, "1003", None),
+        ]
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    for (request, page) in requests.iter().zip(["1", "2"]) {
+        let active = tag(&request.body, "ActiveList").unwrap();
+        assert_eq!(tag(active, "PageNumber"), Some(page));
+        assert_eq!(tag(active, "EntriesPerPage"), Some("2"));
+        assert_eq!(tag(active, "Sort"), Some("TimeLeft"));
+        assert!(tag(&request.body, "SoldList").is_none());
+    }
+}
+
+#[tokio::test]
+async fn sold_transactions_are_unwrapped_into_tagged_items() {
+    let server = MockServer::start(my_ebay).await;
+    let client = client(&server);
+
+    let items = fetcher(&client).fetch_all().await.unwrap();
+    assert_eq!(
+        summary(&items),
+        [
+            (SellingList::Active, "1001", None),
+            (SellingList::Active, "1002", None),
+            (SellingList::Active, "1003", None),
+            (SellingList::Sold, "2001", Some("T1")),
+            (SellingList::Sold, "2002", Some("T2")),
+            (SellingList::Sold, "2003", Some("T3")),
+            (SellingList::DeletedFromSold, "3001", Some("T4")),
+        ]
+    );
+    // The item is moved out of its transaction, not copied.
+    assert!(items
+        .iter()
+        .filter_map(|listed| listed.transaction.as_ref())
+        .all(|transaction| transaction.item.is_none()));
+
+    // Every list is asked for once, plus the second Active page.
+    let requests = server.requests();
+    assert_eq!(requests.len(), SellingList::ALL.len() + 1);
+    let sold = requests
+        .iter()
+        .find_map(|request| tag(&request.body, "SoldList"))
+        .unwrap();
+    assert_eq!(tag(sold, "DurationInDays"), Some("60"));
+}
+
+#[tokio::test]
+async fn item_level_time_left_is_used() {
+    let server = MockServer::start(|_| {
+        response(&format!(
+            "<ActiveList><ItemArray><Item><ItemID>1001</ItemID><TimeLeft>P2DT12H</TimeLeft></Item></ItemArray>{}</ActiveList>",
+            pagination(1)
+        ))
+    })
+    .await;
+    let client = client(&server);
+
+    let items = fetcher(&client)
+        .fetch_list(SellingList::Active)
+        .await
+        .unwrap();
+    let rows = process_listed_items(&items);
+    assert_eq!(rows[0].get("Time Left"), &Cell::Text("P2DT12H".to_string()));
+    assert_eq!(rows[0].get("Time Left (Days)"), &Cell::Number(2.5));
+}
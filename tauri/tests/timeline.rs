@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, TimeZone, Utc};
+use ebay_connect::internal::ebay::listing_rows::{Cell, ListingRow};
+use ebay_connect::internal::ebay::timeline::{Metric, Timeline};
+use ebay_connect::internal::ebay::warehouse::{RowSource, SyncKind, Warehouse};
+use rusqlite::Connection;
+
+const ACCOUNT: &str = "main-store";
+
+fn row(item_id: &str, price: f64, watchers: i64) -> ListingRow {
+    let mut row = ListingRow::default();
+    row.set("Item ID", Cell::Text(item_id.to_string()));
+    row.set("Title", Cell::Text(format!("Listing {item_id}")));
+    row.set("Currency", Cell::Text("USD".to_string()));
+    row.set("Current Price", Cell::Number(price));
+    row.set("Watch Count", Cell::Integer(watchers));
+    row
+}
+
+fn at(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, day, 9, 0, 0).unwrap()
+}
+
+fn database(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-timeline", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+    path
+}
+
+/// A sync that started on `started`, recorded now.
+fn sync_started(
+    warehouse: &Warehouse,
+    db: &Connection,
+    started: DateTime<Utc>,
+    rows: &[ListingRow],
+) -> i64 {
+    let sync = warehouse
+        .begin_sync(ACCOUNT, SyncKind::Full, None, None)
+        .unwrap();
+    db.execute(
+        "UPDATE syncs SET started_at = ?1 WHERE id = ?2",
+        (started.timestamp(), sync.id),
+    )
+    .unwrap();
+    warehouse
+        .record(sync.id, RowSource::SellingLists, rows)
+        .unwrap();
+    warehouse.finish_sync(sync.id).unwrap();
+    sync.id
+}
+
+#[test]
+fn points_are_dated_when_their_sync_started() {
+    let path = database("record.db");
+    let warehouse = Warehouse::open(&path).unwrap();
+    let db = Connection::open(&path).unwrap();
+
+    sync_started(&warehouse, &db, at(1), &[row("1001", 20.0, 2)]);
+    let second = sync_started(&warehouse, &db, at(8), &[row("1001", 18.0, 5)]);
+
+    let timeline = Timeline::new(&warehouse, ACCOUNT);
+    let points = timeline.points("1001").unwrap();
+    let dates: Vec<_> = points.iter().map(|p| p.recorded_at).collect();
+    assert_eq!(dates, [at(1), at(8)]);
+
+    let changes = timeline.price_changes(at(5)).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].changed_at, at(8));
+    assert_eq!(changes[0].sync_id, second);
+    assert_eq!((changes[0].before, changes[0].after), (20.0, 18.0));
+    assert!(timeline.price_changes(at(9)).unwrap().is_empty());
+
+    let grown = timeline.growth(Metric::WatchCount, at(1), 2.0).unwrap();
+    assert_eq!(grown.len(), 1);
+    assert_eq!(grown[0].ratio, 2.5);
+}
+
+#[test]
+fn backfilled_points_match_recorded_ones() {
+    let path = database("backfill.db");
+    {
+        let warehouse = Warehouse::open(&path).unwrap();
+        let db = Connection::open(&path).unwrap();
+        sync_started(&warehouse, &db, at(1), &[row("1001", 20.0, 2)]);
+        sync_started(&warehouse, &db, at(8), &[row("1001", 18.0, 5)]);
+    }
+    let recorded = Timeline::new(&Warehouse::open(&path).unwrap(), ACCOUNT)
+        .points("1001")
+        .unwrap();
+
+    // A database from before `listing_metrics` existed.
+    let db = Connection::open(&path).unwrap();
+    db.execute("DELETE FROM listing_metrics", ()).unwrap();
+    db.pragma_update(None, "user_version", 1).unwrap();
+    drop(db);
+
+    let warehouse = Warehouse::open(&path).unwrap();
+    let backfilled = Timeline::new(&warehouse, ACCOUNT).points("1001").unwrap();
+    assert_eq!(backfilled, recorded);
+    assert_eq!(backfilled[0].recorded_at, at(1));
+}
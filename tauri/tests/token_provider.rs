@@ -0,0 +1,164 @@
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use ebay_connect::adapters::ebay::vault::unix_now;
+use ebay_connect::adapters::ebay::{
+    send_authorized, OAuthClient, OAuthConfig, StoredTokens, TokenSource, TokenVault,
+    UserTokenProvider, VaultKey,
+};
+use ebay_connect::{Environment, Error};
+use tokio::task::JoinSet;
+
+use common::{MockResponse, MockServer};
+
+const REFRESHED: &str =
+    r#"{"access_token":"fresh","expires_in":7200,"token_type":"User Access Token"}"#;
+
+fn vault_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-provider", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{name}.vault"));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn tokens(access_token: &str, expires_at: u64) -> StoredTokens {
+    StoredTokens {
+        access_token: access_token.to_string(),
+        refresh_token: Some("refresh".to_string()),
+        expires_at,
+        refresh_token_expires_at: None,
+        scopes: vec!["https://api.ebay.com/oauth/api_scope".to_string()],
+        environment: Environment::Sandbox,
+    }
+}
+
+/// Provider whose vault holds `stored` and whose token endpoint is `server`.
+fn provider(server: &MockServer, name: &str, stored: StoredTokens) -> UserTokenProvider {
+    let vault = TokenVault::new(vault_path(name), VaultKey::Machine([3; 32]));
+    vault.save(&stored).unwrap();
+    let oauth = OAuthClient::new(OAuthConfig::new(
+        "client-id",
+        "client-secret",
+        "ru-name",
+        Environment::Sandbox,
+    ))
+    .with_token_url(server.url("/identity/v1/oauth2/token"));
+    UserTokenProvider::new(oauth, vault)
+}
+
+fn refreshes(server: &MockServer) -> usize {
+    server
+        .requests()
+        .iter()
+        .filter(|r| r.path() == "/identity/v1/oauth2/token")
+        .count()
+}
+
+async fn token_server() -> MockServer {
+    MockServer::start(|request| match request.path() {
+        "/identity/v1/oauth2/token" => MockResponse::json(200, REFRESHED),
+        // Only the refreshed token is accepted.
+        _ if request.header("Authorization") == Some("Bearer fresh") => {
+            MockResponse::json(200, "{}")
+        }
+        _ => MockResponse::json(401, r#"{"errors":[{"errorId":1001}]}"#),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn concurrent_callers_share_one_refresh() {
+    let server = token_server().await;
+    let provider = Arc::new(provider(&server, "concurrent", tokens("expired", 0)));
+
+    let mut callers = JoinSet::new();
+    for _ in 0..8 {
+        let provider = provider.clone();
+        callers.spawn(async move { provider.access_token().await });
+    }
+    while let Some(token) = callers.join_next().await {
+        assert_eq!(token.unwrap().unwrap(), "fresh");
+    }
+
+    assert_eq!(refreshes(&server), 1);
+    let request = &server.requests()[0];
+    assert_eq!(
+        request.form_value("grant_type").as_deref(),
+        Some("refresh_token")
+    );
+    assert_eq!(
+        request.form_value("refresh_token").as_deref(),
+        Some("refresh")
+    );
+    // The rotated token set keeps the refresh token eBay did not send back.
+    let saved = provider.vault().load().unwrap().unwrap();
+    assert_eq!(saved.access_token, "fresh");
+    assert_eq!(saved.refresh_token.as_deref(), Some("refresh"));
+}
+
+#[tokio::test]
+async fn valid_token_is_not_refreshed() {
+    let server = token_server().await;
+    let provider = provider(&server, "valid", tokens("current", unix_now() + 3600));
+
+    assert_eq!(provider.access_token().await.unwrap(), "current");
+    // Another task already replaced this one.
+    provider.refresh_if_current("older").await.unwrap();
+    assert_eq!(refreshes(&server), 0);
+}
+
+#[tokio::test]
+async fn unauthorized_request_is_retried_once_with_a_fresh_token() {
+    let server = token_server().await;
+    let provider = provider(&server, "retry", tokens("revoked", unix_now() + 3600));
+    let http = reqwest::Client::new();
+    let url = server.url("/sell/marketing/v1/ad_campaign");
+
+    let response = send_authorized(&provider, |token| http.get(url.clone()).bearer_auth(token))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(refreshes(&server), 1);
+    let sent: Vec<_> = server
+        .requests()
+        .iter()
+        .filter(|r| r.path() == "/sell/marketing/v1/ad_campaign")
+        .map(|r| r.header("Authorization").unwrap_or_default().to_string())
+        .collect();
+    assert_eq!(sent, ["Bearer revoked", "Bearer fresh"]);
+}
+
+#[tokio::test]
+async fn second_unauthorized_response_is_returned() {
+    let server = MockServer::start(|request| match request.path() {
+        "/identity/v1/oauth2/token" => MockResponse::json(200, REFRESHED),
+        _ => MockResponse::json(401, "{}"),
+    })
+    .await;
+    let provider = provider(&server, "denied", tokens("revoked", unix_now() + 3600));
+    let http = reqwest::Client::new();
+    let url = server.url("/sell/account/v1/privilege");
+
+    let response = send_authorized(&provider, |token| http.get(url.clone()).bearer_auth(token))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 401);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn expired_refresh_token_needs_reauthorization() {
+    let server = token_server().await;
+    let mut stored = tokens("expired", 0);
+    stored.refresh_token_expires_at = Some(unix_now() - 60);
+    let provider = provider(&server, "lapsed", stored);
+
+    let err = provider.bearer_token().await.unwrap_err();
+    assert!(matches!(err, Error::NotAuthorized(_)), "{err}");
+    assert!(server.requests().is_empty());
+}
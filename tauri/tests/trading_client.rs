@@ -0,0 +1,204 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ebay_connect::adapters::ebay::retry::{Retrier, RetryPolicy};
+use ebay_connect::internal::ebay::trading::types::{Ack, Pagination};
+use ebay_connect::internal::ebay::trading::{GetSellerList, TradingClient};
+use ebay_connect::{Environment, Error};
+
+use common::{MockResponse, MockServer, StaticToken};
+
+fn response(ack: &str, errors: &str, items: &[&str], page: u32, pages: u32) -> MockResponse {
+    let items: String = items
+        .iter()
+        .map(|id| format!("<Item><ItemID>{id}</ItemID></Item>"))
+        .collect();
+    MockResponse::xml(
+        200,
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><GetSellerListResponse xmlns="urn:ebay:apis:eBLBaseComponents"><Ack>{ack}</Ack>{errors}<PaginationResult><TotalNumberOfPages>{pages}</TotalNumberOfPages><TotalNumberOfEntries>3</TotalNumberOfEntries></PaginationResult><HasMoreItems>{more}</HasMoreItems><ItemArray>{items}</ItemArray><PageNumber>{page}</PageNumber></GetSellerListResponse>"#,
+            more = page < pages
+        ),
+    )
+}
+
+fn error(code: &str, severity: &str, message: &str) -> String {
+    format!(
+        "<Errors><ShortMessage>{message}</ShortMessage><ErrorCode>{code}</ErrorCode><SeverityCode>{severity}</SeverityCode><ErrorClassification>RequestError</ErrorClassification></Errors>"
+    )
+}
+
+fn client(server: &MockServer) -> TradingClient {
+    TradingClient::new(
+        Environment::Production,
+        "app-id",
+        3,
+        Arc::new(StaticToken("user")),
+    )
+    .with_endpoint(server.url("/ws/api.dll"))
+}
+
+fn page(number: u32) -> GetSellerList {
+    GetSellerList {
+        pagination: Some(Pagination {
+            entries_per_page: 2,
+            page_number: number,
+        }),
+        ..GetSellerList::default()
+    }
+}
+
+fn item_ids(
+    response: ebay_connect::internal::ebay::trading::calls::GetSellerListResponse,
+) -> Vec<String> {
+    response
+        .into_items()
+        .into_iter()
+        .map(|item| item.item_id)
+        .collect()
+}
+
+#[tokio::test]
+async fn pages_are_requested_with_the_trading_headers() {
+    let server = MockServer::start(|request| {
+        if request.body.contains("<PageNumber>2</PageNumber>") {
+            response("Success", "", &["1003"], 2, 2)
+        } else {
+            response("Success", "", &["1001", "1002"], 1, 2)
+        }
+    })
+    .await;
+    let client = client(&server);
+
+    let first = client.execute(&page(1)).await.unwrap();
+    assert!(first.has_more_items);
+    assert_eq!(
+        first
+            .pagination_result
+            .as_ref()
+            .unwrap()
+            .total_number_of_pages,
+        2
+    );
+    assert_eq!(item_ids(first), ["1001", "1002"]);
+    let second = client.execute(&page(2)).await.unwrap();
+    assert!(!second.has_more_items);
+    assert_eq!(item_ids(second), ["1003"]);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    let request = &requests[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path(), "/ws/api.dll");
+    assert_eq!(
+        request.header("X-EBAY-API-CALL-NAME"),
+        Some("GetSellerList")
+    );
+    assert_eq!(request.header("X-EBAY-API-IAF-TOKEN"), Some("user"));
+    assert_eq!(request.header("X-EBAY-API-SITEID"), Some("3"));
+    assert_eq!(request.header("X-EBAY-API-APP-NAME"), Some("app-id"));
+    assert!(request
+        .body
+        .contains("<GetSellerListRequest xmlns=\"urn:ebay:apis:eBLBaseComponents\">"));
+    assert!(request.body.contains("<EntriesPerPage>2</EntriesPerPage>"));
+}
+
+#[tokio::test]
+async fn partial_failure_keeps_the_page() {
+    let server = MockServer::start(|_| {
+        response(
+            "PartialFailure",
+            &error("21919188", "Error", "Some listings could not be returned"),
+            &["1001"],
+            1,
+            1,
+        )
+    })
+    .await;
+
+    let page = client(&server).execute(&page(1)).await.unwrap();
+    assert_eq!(item_ids(page), ["1001"]);
+}
+
+#[tokio::test]
+async fn failure_ack_is_an_error() {
+    let server = MockServer::start(|_| {
+        response(
+            "Failure",
+            &error("340", "Error", "Page number is out of range"),
+            &[],
+            1,
+            1,
+        )
+    })
+    .await;
+
+    let err = client(&server).execute(&page(9)).await.unwrap_err();
+    match err {
+        Error::Trading(errors) => {
+            assert_eq!(errors.ack, Ack::Failure);
+            assert_eq!(errors.call_name, "GetSellerList");
+            assert!(errors.has_code("340"));
+        }
+        other => panic!("expected a Trading error, got {other}"),
+    }
+}
+
+#[tokio::test]
+async fn rejected_token_is_retried_once() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server = MockServer::start({
+        let calls = calls.clone();
+        move |_| match calls.fetch_add(1, Ordering::SeqCst) {
+            0 => response(
+                "Failure",
+                &error("931", "Error", "Auth token is invalid"),
+                &[],
+                1,
+                1,
+            ),
+            _ => response("Success", "", &["1001"], 1, 1),
+        }
+    })
+    .await;
+
+    let page = client(&server).execute(&page(1)).await.unwrap();
+    assert_eq!(item_ids(page), ["1001"]);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn http_errors_without_an_xml_body_are_api_errors() {
+    let server = MockServer::start(|_| MockResponse::xml(503, "Service Unavailable")).await;
+
+    let err = client(&server).execute(&page(1)).await.unwrap_err();
+    assert!(matches!(err, Error::Api { status: 503, .. }), "{err}");
+}
+
+#[tokio::test]
+async fn failures_open_the_trading_circuit() {
+    let server = MockServer::start(|_| MockResponse::xml(503, "Service Unavailable")).await;
+    let retrier = Arc::new(Retrier::new(RetryPolicy {
+        max_attempts: 1,
+        failure_threshold: 2,
+        open_for: Duration::from_secs(60),
+        ..RetryPolicy::default()
+    }));
+    let client = client(&server).with_retrier(retrier.clone());
+
+    for _ in 0..2 {
+        client.execute(&page(1)).await.unwrap_err();
+    }
+    let err = client.execute(&page(1)).await.unwrap_err();
+    assert!(
+        matches!(&err, Error::CircuitOpen(message) if message.starts_with("trading ")),
+        "{err}"
+    );
+    assert_eq!(server.requests().len(), 2);
+    // The circuit is named after the API, not the endpoint URL.
+    let err = retrier.run("trading", || async { Ok(()) }).await;
+    assert!(matches!(err, Err(Error::CircuitOpen(_))));
+}
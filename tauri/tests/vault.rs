@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+
+use ebay_connect::adapters::ebay::{StoredTokens, TokenVault, VaultKey};
+use ebay_connect::{Environment, Error};
+
+fn vault_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-vault", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn passphrase(value: &str) -> VaultKey {
+    VaultKey::Passphrase(value.to_string())
+}
+
+fn tokens() -> StoredTokens {
+    StoredTokens {
+        access_token: "v^1.1#i^1#access".to_string(),
+        refresh_token: Some("v^1.1#i^1#refresh".to_string()),
+        expires_at: 1_700_007_200,
+        refresh_token_expires_at: Some(1_747_304_000),
+        scopes: vec!["https://api.ebay.com/oauth/api_scope/sell.inventory".to_string()],
+        environment: Environment::Production,
+    }
+}
+
+#[test]
+fn tokens_round_trip_encrypted() {
+    let path = vault_path("round-trip.vault");
+    let vault = TokenVault::new(&path, passphrase("correct horse"));
+    assert_eq!(vault.load().unwrap(), None);
+
+    vault.save(&tokens()).unwrap();
+    assert_eq!(vault.load().unwrap(), Some(tokens()));
+    let raw = std::fs::read_to_string(&path).unwrap();
+    assert!(!raw.contains("refresh"), "tokens written in plaintext");
+
+    // A second instance with the same passphrase reads what the first wrote.
+    let reopened = TokenVault::new(&path, passphrase("correct horse"));
+    assert_eq!(reopened.load().unwrap(), Some(tokens()));
+
+    reopened.revoke().unwrap();
+    assert!(!path.exists());
+    assert_eq!(vault.load().unwrap(), None);
+}
+
+#[test]
+fn wrong_passphrase_is_rejected() {
+    let path = vault_path("wrong-key.vault");
+    TokenVault::new(&path, passphrase("correct horse"))
+        .save(&tokens())
+        .unwrap();
+
+    let err = TokenVault::new(&path, passphrase("battery staple"))
+        .load()
+        .unwrap_err();
+    assert!(matches!(err, Error::Vault(_)), "{err}");
+    let err = TokenVault::new(&path, VaultKey::Machine([0; 32]))
+        .load()
+        .unwrap_err();
+    assert!(matches!(err, Error::Vault(_)), "{err}");
+}
+
+#[test]
+fn rekey_moves_tokens_to_the_new_key() {
+    let path = vault_path("rekey.vault");
+    let mut vault = TokenVault::new(&path, passphrase("old"));
+    vault.save(&tokens()).unwrap();
+
+    vault.rekey(VaultKey::Machine([9; 32])).unwrap();
+    assert_eq!(vault.load().unwrap(), Some(tokens()));
+    assert!(TokenVault::new(&path, passphrase("old")).load().is_err());
+}
+
+#[test]
+fn corrupted_files_are_errors() {
+    let path = vault_path("corrupted.vault");
+    let vault = TokenVault::new(&path, VaultKey::Machine([1; 32]));
+    vault.save(&tokens()).unwrap();
+
+    // Flip a byte of the ciphertext: authentication fails.
+    let mut envelope: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    let ciphertext = envelope["ciphertext"].as_str().unwrap().to_string();
+    let flipped = if ciphertext.starts_with('A') {
+        "B"
+    } else {
+        "A"
+    };
+    envelope["ciphertext"] = format!("{flipped}{}", &ciphertext[1..]).into();
+    std::fs::write(&path, serde_json::to_vec(&envelope).unwrap()).unwrap();
+    assert!(matches!(vault.load(), Err(Error::Vault(_))));
+
+    envelope["ciphertext"] = "not base64!".into();
+    std::fs::write(&path, serde_json::to_vec(&envelope).unwrap()).unwrap();
+    assert!(matches!(vault.load(), Err(Error::Vault(_))));
+
+    envelope["version"] = 2.into();
+    std::fs::write(&path, serde_json::to_vec(&envelope).unwrap()).unwrap();
+    assert!(matches!(vault.load(), Err(Error::Vault(_))));
+
+    // Cut off mid-write (the atomic rename should prevent this, but a copied
+    // or hand-edited file can still be truncated).
+    std::fs::write(&path, b"{\"version\":1,\"salt\":").unwrap();
+    assert!(vault.load().is_err());
+}
+
+#[test]
+fn key_file_is_generated_once() {
+    let path = vault_path("machine.key");
+    let first = VaultKey::from_key_file(&path).unwrap();
+    let vault = TokenVault::new(vault_path("machine.vault"), first);
+    vault.save(&tokens()).unwrap();
+
+    let second = VaultKey::from_key_file(&path).unwrap();
+    let reopened = TokenVault::new(vault.path(), second);
+    assert_eq!(reopened.load().unwrap(), Some(tokens()));
+
+    std::fs::write(&path, b"short").unwrap();
+    assert!(matches!(
+        VaultKey::from_key_file(&path),
+        Err(Error::Vault(_))
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn vault_and_key_files_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let key_path = vault_path("private.key");
+    let key = VaultKey::from_key_file(&key_path).unwrap();
+    let path = vault_path("private.vault");
+    TokenVault::new(&path, key).save(&tokens()).unwrap();
+
+    for path in [&key_path, &path] {
+        let mode = std::fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600, "{}", path.display());
+    }
+}
@@ -0,0 +1,176 @@
+use ebay_connect::internal::ebay::listing_rows::{
+    process_item, specific_column, Cell, LISTING_STATUS_CATEGORY,
+};
+use ebay_connect::internal::ebay::selling_lists::SellingList;
+use ebay_connect::internal::ebay::trading::types::{
+    BestOfferDetails, ItemSpecifics, NameValueList,
+};
+use ebay_connect::internal::ebay::trading::Item;
+use ebay_connect::internal::ebay::warehouse::{RowSource, SyncKind, Warehouse};
+use ebay_connect::Error;
+
+fn full_item() -> Item {
+    Item {
+        item_id: "110001".to_string(),
+        title: Some("Linen sheet set".to_string()),
+        description: Some("<p>Queen size, stonewashed.</p>".to_string()),
+        item_specifics: Some(ItemSpecifics {
+            name_value_list: vec![
+                NameValueList {
+                    name: "Brand".to_string(),
+                    values: vec!["Acme".to_string()],
+                },
+                NameValueList {
+                    name: "Thread Count".to_string(),
+                    values: vec!["400".to_string()],
+                },
+            ],
+        }),
+        ..Item::default()
+    }
+}
+
+/// What `GetSellerEvents` returns for the same listing: no description and
+/// no item specifics.
+fn sparse_item() -> Item {
+    Item {
+        item_id: "110001".to_string(),
+        title: Some("Linen sheet set, queen".to_string()),
+        ..Item::default()
+    }
+}
+
+#[test]
+fn sparse_rows_keep_stored_columns() {
+    let warehouse = Warehouse::open_in_memory().unwrap();
+
+    let full = warehouse
+        .begin_sync("main-store", SyncKind::Full, None, None)
+        .unwrap();
+    warehouse
+        .record(
+            full.id,
+            RowSource::SellingLists,
+            &[process_item(&full_item(), Some(SellingList::Active))],
+        )
+        .unwrap();
+    warehouse.finish_sync(full.id).unwrap();
+
+    let incremental = warehouse
+        .begin_sync("main-store", SyncKind::Incremental, None, None)
+        .unwrap();
+    let sparse = process_item(&sparse_item(), None);
+    assert!(sparse.get("Description").is_empty());
+    warehouse
+        .record(incremental.id, RowSource::SellerEvents, &[sparse])
+        .unwrap();
+    warehouse.finish_sync(incremental.id).unwrap();
+
+    let stored = warehouse.listing("main-store", "110001").unwrap().unwrap();
+    assert_eq!(stored.last_sync, incremental.id);
+    assert_eq!(
+        stored.row.get("Title"),
+        &Cell::Text("Linen sheet set, queen".to_string())
+    );
+    assert_eq!(
+        stored.row.get("Description"),
+        &Cell::Text("Queen size, stonewashed.".to_string())
+    );
+    assert_eq!(stored.row.get("Brand"), &Cell::Text("Acme".to_string()));
+    assert_eq!(
+        stored.row.get(&specific_column("Thread Count")),
+        &Cell::Text("400".to_string())
+    );
+    assert_eq!(
+        stored.row.get(LISTING_STATUS_CATEGORY),
+        &Cell::Text("Active".to_string())
+    );
+}
+
+#[test]
+fn removed_values_are_cleared() {
+    let warehouse = Warehouse::open_in_memory().unwrap();
+    let record = |source: RowSource, item: &Item| {
+        let sync = warehouse
+            .begin_sync("main-store", SyncKind::Full, None, None)
+            .unwrap();
+        warehouse
+            .record(sync.id, source, &[process_item(item, None)])
+            .unwrap();
+        warehouse.finish_sync(sync.id).unwrap();
+        warehouse
+            .listing("main-store", "110001")
+            .unwrap()
+            .unwrap()
+            .row
+    };
+
+    let mut item = full_item();
+    item.sub_title = Some("Free returns".to_string());
+    item.best_offer_details = Some(BestOfferDetails {
+        best_offer_enabled: Some(true),
+        ..BestOfferDetails::default()
+    });
+    let row = record(RowSource::SellerList, &item);
+    assert_eq!(row.get("Subtitle"), &Cell::Text("Free returns".to_string()));
+    assert_eq!(row.get("Best Offer Enabled"), &Cell::Bool(true));
+
+    // GetSellerEvents returns the subtitle and best offer, so their removal
+    // is stored even though the row has no description or specifics.
+    let mut sparse = sparse_item();
+    sparse.best_offer_details = Some(BestOfferDetails {
+        best_offer_enabled: Some(false),
+        ..BestOfferDetails::default()
+    });
+    let row = record(RowSource::SellerEvents, &sparse);
+    assert!(row.get("Subtitle").is_empty());
+    assert_eq!(row.get("Best Offer Enabled"), &Cell::Bool(false));
+    assert_eq!(
+        row.get(&specific_column("Thread Count")),
+        &Cell::Text("400".to_string())
+    );
+
+    // A call that does return item specifics drops the ones that are gone.
+    let mut item = full_item();
+    item.item_specifics.as_mut().unwrap().name_value_list.pop();
+    let row = record(RowSource::SellerList, &item);
+    assert_eq!(row.get("Brand"), &Cell::Text("Acme".to_string()));
+    assert!(row.get(&specific_column("Thread Count")).is_empty());
+}
+
+#[test]
+fn unknown_sync_kinds_and_statuses_fail_the_read() {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-warehouse", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("syncs.db");
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+    let warehouse = Warehouse::open(&path).unwrap();
+    let sync = warehouse
+        .begin_sync("main-store", SyncKind::Incremental, None, None)
+        .unwrap();
+    warehouse.finish_sync(sync.id).unwrap();
+    assert_eq!(warehouse.syncs("main-store").unwrap().len(), 1);
+
+    let db = rusqlite::Connection::open(&path).unwrap();
+    for (column, value) in [("kind", "partial"), ("status", "paused")] {
+        db.execute(&format!("UPDATE syncs SET {column} = ?1"), [value])
+            .unwrap();
+        let err = warehouse.syncs("main-store").unwrap_err();
+        assert!(matches!(err, Error::Sqlite(_)), "{err}");
+        db.execute(
+            "UPDATE syncs SET kind = 'incremental', status = 'complete'",
+            [],
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        warehouse
+            .last_complete_sync("main-store")
+            .unwrap()
+            .unwrap()
+            .kind,
+        SyncKind::Incremental
+    );
+}
@@ -0,0 +1,70 @@
+use std::fs::File;
+use std::io::Read;
+
+use calamine::{open_workbook, Data, Reader, Xlsx};
+use ebay_connect::internal::ebay::listing_rows::{Cell, ListingRow};
+use ebay_connect::internal::ebay::workbook::{columns_for, export_to_excel, read_listings};
+
+fn row(item_id: &str, days_left: Option<f64>) -> ListingRow {
+    let mut row = ListingRow::default();
+    row.set("Item ID", Cell::Text(item_id.to_string()));
+    row.set("Title", Cell::Text(format!("Listing {item_id}")));
+    if let Some(days) = days_left {
+        row.set("Time Left (Days)", Cell::Number(days));
+    }
+    row
+}
+
+fn sheet_xml(path: &std::path::Path, sheet: &str) -> String {
+    let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+    let mut xml = String::new();
+    archive
+        .by_name(&format!("xl/worksheets/{sheet}.xml"))
+        .unwrap()
+        .read_to_string(&mut xml)
+        .unwrap();
+    xml
+}
+
+#[test]
+fn time_left_highlight_skips_blank_cells() {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-workbook", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("time-left.xlsx");
+    let rows = [
+        row("1001", Some(0.5)),
+        row("1002", None),
+        row("1003", Some(2.5)),
+    ];
+    export_to_excel(&rows, &path).unwrap();
+
+    let days = columns_for(&rows)
+        .iter()
+        .position(|c| c.name == "Time Left (Days)")
+        .unwrap();
+    let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+    let sheet = workbook.worksheet_range("All Listings").unwrap();
+    let sheet: Vec<&[Data]> = sheet.rows().collect();
+    assert_eq!(sheet[1][days], Data::Float(0.5));
+    assert_eq!(sheet[2][days], Data::Empty);
+    assert_eq!(sheet[3][days], Data::Float(2.5));
+
+    // Formula rules on the first data cell, not `cellIs` rules, which would
+    // treat the blank as 0.
+    let column = rust_xlsxwriter::column_number_to_name(days as u16);
+    let xml = sheet_xml(&path, "sheet1");
+    assert!(!xml.contains(r#"type="cellIs""#), "{xml}");
+    assert!(xml.contains(&format!(
+        "<formula>AND(ISNUMBER({column}2),{column}2&lt;1)</formula>"
+    )));
+    assert!(xml.contains(&format!(
+        "<formula>AND(ISNUMBER({column}2),{column}2&gt;=1,{column}2&lt;=3)</formula>"
+    )));
+    assert!(xml.contains(&format!(r#"sqref="{column}2:{column}4""#)));
+
+    let read = read_listings(&path).unwrap();
+    assert_eq!(read.len(), 3);
+    assert!(read[1].get("Time Left (Days)").is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use calamine::{open_workbook, Data, Reader, Xlsx};
+use ebay_connect::internal::ebay::listing_rows::{specific_column, Cell, ColumnKind, ListingRow};
+use ebay_connect::internal::ebay::warehouse::{RowSource, SyncKind, Warehouse};
+use ebay_connect::internal::ebay::workbook::{columns_for, Column};
+use ebay_connect::internal::ebay::workbook_stream::{export_listings_streaming, StreamingWorkbook};
+use ebay_connect::Error;
+
+fn row(n: usize) -> ListingRow {
+    let mut row = ListingRow::default();
+    row.set("Item ID", Cell::Text(format!("{}", 1000 + n)));
+    row.set("Title", Cell::Text(format!("Listing {n}")));
+    row.set("Currency", Cell::Text("USD".to_string()));
+    row.set("Current Price", Cell::Number(10.0 + n as f64));
+    row
+}
+
+const ACCOUNT: &str = "main-store";
+
+fn warehouse(rows: &[ListingRow]) -> Warehouse {
+    let warehouse = Warehouse::open_in_memory().unwrap();
+    let sync = warehouse
+        .begin_sync(ACCOUNT, SyncKind::Full, None, None)
+        .unwrap();
+    warehouse
+        .record(sync.id, RowSource::SellingLists, rows)
+        .unwrap();
+    warehouse.finish_sync(sync.id).unwrap();
+    warehouse
+}
+
+fn export_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ebay-connect-{}-stream", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+fn sheet_rows(
+    workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+    name: &str,
+) -> Vec<Vec<Data>> {
+    let range = workbook.worksheet_range(name).unwrap();
+    range.rows().map(<[Data]>::to_vec).collect()
+}
+
+#[test]
+fn full_sheets_roll_over_with_the_header_repeated() {
+    let path = export_path("rollover.xlsx");
+    let columns = vec![
+        Column::new("Item ID", ColumnKind::Text),
+        Column::new("Current Price", ColumnKind::Price),
+    ];
+    // Fixed widths: rows go straight to the constant-memory sheets.
+    let mut writer = StreamingWorkbook::create(&path, "Listings", columns)
+        .with_column_widths(vec![12.0, 14.0])
+        .with_max_rows_per_sheet(2);
+    writer
+        .append_rows(&(0..5).map(row).collect::<Vec<_>>())
+        .unwrap();
+    assert_eq!(writer.rows_written(), 5);
+
+    let result = writer
+        .finish(|workbook| {
+            workbook.add_worksheet().set_name("Summary")?;
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(result.record_count, 5);
+
+    let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+    assert_eq!(
+        workbook.sheet_names(),
+        ["Listings", "Listings (2)", "Listings (3)", "Summary"]
+    );
+    let header = vec![
+        Data::String("Item ID".to_string()),
+        Data::String("Current Price".to_string()),
+    ];
+    let first = sheet_rows(&mut workbook, "Listings");
+    assert_eq!(first.len(), 3);
+    assert_eq!(first[0], header);
+    assert_eq!(first[1][0], Data::String("1000".to_string()));
+    let last = sheet_rows(&mut workbook, "Listings (3)");
+    assert_eq!(
+        last,
+        [
+            header,
+            vec![Data::String("1004".to_string()), Data::Float(14.0)]
+        ]
+    );
+}
+
+#[test]
+fn streaming_export_includes_item_specifics_and_the_summary() {
+    let path = export_path("specifics.xlsx");
+    let mut rows: Vec<ListingRow> = (0..3).map(row).collect();
+    rows[1].set(
+        specific_column("thread count"),
+        Cell::Text("300".to_string()),
+    );
+    rows[2].set(
+        specific_column("Thread Count"),
+        Cell::Text("400".to_string()),
+    );
+    rows[2].set("Listing Status Category", Cell::Text("Active".to_string()));
+
+    let result = export_listings_streaming(&warehouse(&rows), ACCOUNT, &path).unwrap();
+    assert_eq!(result.record_count, 3);
+
+    let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+    assert_eq!(workbook.sheet_names(), ["All Listings", "Summary"]);
+    let listings = sheet_rows(&mut workbook, "All Listings");
+    // The same columns the in-memory export picks, in one pass over the rows.
+    let expected: Vec<Data> = columns_for(&rows)
+        .into_iter()
+        .map(|column| Data::String(column.name))
+        .collect();
+    assert_eq!(listings[0], expected);
+    let column = listings[0]
+        .iter()
+        .position(|cell| *cell == Data::String(specific_column("thread count")))
+        .expect("specific column");
+    assert_eq!(listings[1][column], Data::Empty);
+    assert_eq!(listings[2][column], Data::String("300".to_string()));
+    assert_eq!(listings[3][column], Data::String("400".to_string()));
+    assert!(!sheet_rows(&mut workbook, "Summary").is_empty());
+}
+
+#[test]
+fn streaming_nothing_is_refused() {
+    let path = export_path("empty.xlsx");
+    let err = export_listings_streaming(&warehouse(&[]), ACCOUNT, &path).unwrap_err();
+    assert!(matches!(err, Error::Spreadsheet(_)), "{err}");
+}